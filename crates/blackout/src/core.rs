@@ -1,5 +1,6 @@
 use std::{
     cmp::{max, min},
+    collections::HashMap,
     fmt::Display,
 };
 
@@ -38,6 +39,7 @@ impl From<(usize, usize)> for Coordinates {
 pub struct Area {
     pub rect: mapgen::geometry::Rect,
     pub description: Option<String>,
+    pub light: f32,
 }
 
 impl Area {
@@ -293,6 +295,20 @@ pub trait PointLike {
         MovementDirection::new(heading.to_degrees())
     }
 
+    fn clock_hour(&self, other: &dyn PointLike, facing: Angle) -> u32 {
+        let relative = Angle::Radians(self.bearing(other) - facing.radians()).degrees();
+        let hour = ((360. - relative) / 30.).round() as u32 % 12;
+        if hour == 0 {
+            12
+        } else {
+            hour
+        }
+    }
+
+    fn clock_direction(&self, other: &dyn PointLike, facing: Angle) -> String {
+        format!("{} o'clock", self.clock_hour(other, facing))
+    }
+
     fn distance_and_direction(&self, other: &dyn PointLike) -> String {
         let mut tokens: Vec<String> = vec![];
         let distance = self.distance(other).round() as i32;
@@ -382,6 +398,44 @@ impl From<&dyn PointLike> for (i32, i32) {
 #[reflect(Component)]
 pub struct Player;
 
+// Buckets entities by tile so nearby ones can be found without scanning them all. Queries
+// return entities in insertion order.
+#[derive(Clone, Debug, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<(i32, i32), Vec<(usize, Entity)>>,
+    len: usize,
+}
+
+impl SpatialGrid {
+    pub fn insert(&mut self, entity: Entity, point: &dyn PointLike) {
+        let cell = (point.x().floor() as i32, point.y().floor() as i32);
+        self.cells
+            .entry(cell)
+            .or_insert_with(Vec::new)
+            .push((self.len, entity));
+        self.len += 1;
+    }
+
+    // Entities in every cell touched by the square of `radius` around `point`. Callers still
+    // need their own exact distance check.
+    pub fn near(&self, point: &dyn PointLike, radius: f32) -> Vec<Entity> {
+        let min_x = (point.x() - radius).floor() as i32;
+        let max_x = (point.x() + radius).floor() as i32;
+        let min_y = (point.y() - radius).floor() as i32;
+        let max_y = (point.y() + radius).floor() as i32;
+        let mut found = vec![];
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                if let Some(entities) = self.cells.get(&(x, y)) {
+                    found.extend(entities.iter().copied());
+                }
+            }
+        }
+        found.sort_by_key(|(index, _)| *index);
+        found.into_iter().map(|(_, entity)| entity).collect()
+    }
+}
+
 fn copy_coordinates_to_transform(
     config: Res<CoreConfig>,
     mut query: Query<(&Coordinates, &mut Transform), Changed<Coordinates>>,
@@ -403,6 +457,39 @@ impl Default for CoreConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CoordinateOrigin {
+    BottomLeft,
+    TopLeft,
+}
+
+impl Default for CoordinateOrigin {
+    fn default() -> Self {
+        CoordinateOrigin::BottomLeft
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoordinateConfig {
+    pub origin: CoordinateOrigin,
+    // Append the current area's description, if it has one, when speaking coordinates.
+    pub include_area: bool,
+}
+
+impl CoordinateConfig {
+    pub fn spoken(&self, point: &dyn PointLike, height: usize) -> (i32, i32) {
+        let (x, y) = point.i32();
+        match self.origin {
+            CoordinateOrigin::BottomLeft => (x, y),
+            CoordinateOrigin::TopLeft => (x, height as i32 - 1 - y),
+        }
+    }
+}
+
+// Set by the game while it is paused, so engine systems that spawn sounds or run timers hold off.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct Paused(pub bool);
+
 pub struct CorePlugin;
 
 impl Plugin for CorePlugin {
@@ -410,7 +497,11 @@ impl Plugin for CorePlugin {
         if !app.world().contains_resource::<CoreConfig>() {
             app.insert_resource(CoreConfig::default());
         }
-        app.register_type::<Coordinates>()
+        if !app.world().contains_resource::<CoordinateConfig>() {
+            app.insert_resource(CoordinateConfig::default());
+        }
+        app.init_resource::<Paused>()
+            .register_type::<Coordinates>()
             .add_system(copy_coordinates_to_transform.system())
             .add_system_to_stage(
                 CoreStage::PostUpdate,
@@ -428,6 +519,52 @@ impl PluginGroup for CorePlugins {
         group
             .add(crate::bevy_tts::TtsPlugin)
             .add(crate::bevy_openal::OpenAlPlugin)
-            .add(CorePlugin);
+            .add(CorePlugin)
+            .add(crate::localization::LocalizationPlugin)
+            .add(crate::speech::SpeechPlugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn spatial_grid_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let points = (0..200)
+            .map(|_| (rng.gen_range(0. ..40.), rng.gen_range(0. ..40.)))
+            .collect::<Vec<(f32, f32)>>();
+        let mut grid = SpatialGrid::default();
+        for (index, point) in points.iter().enumerate() {
+            grid.insert(Entity::new(index as u32), point);
+        }
+        for _ in 0..50 {
+            let center: (f32, f32) = (rng.gen_range(0. ..40.), rng.gen_range(0. ..40.));
+            let radius = rng.gen_range(0.5..5.);
+            let expected = points
+                .iter()
+                .enumerate()
+                .filter(|(_, point)| center.distance(*point) <= radius)
+                .map(|(index, _)| Entity::new(index as u32))
+                .collect::<Vec<Entity>>();
+            let found = grid
+                .near(&center, radius)
+                .into_iter()
+                .filter(|entity| center.distance(&points[entity.id() as usize]) <= radius)
+                .collect::<Vec<Entity>>();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn top_left_origin_flips_the_spoken_y() {
+        let mut config = CoordinateConfig::default();
+        assert_eq!(config.spoken(&(3, 1), 10), (3, 1));
+        config.origin = CoordinateOrigin::TopLeft;
+        assert_eq!(config.spoken(&(3, 1), 10), (3, 8));
+        assert_eq!(config.spoken(&(3, 9), 10), (3, 0));
     }
 }
@@ -1,4 +1,8 @@
-use std::error::Error;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    time::Instant,
+};
 
 use bevy::prelude::*;
 use bevy_input_actionmap::InputMap;
@@ -7,10 +11,13 @@ use derive_more::{Deref, DerefMut};
 use mapgen::TileType;
 
 use crate::{
-    core::{Coordinates, Player, PointLike},
+    core::{Angle, CoordinateConfig, Coordinates, Paused, Player, PointLike},
     error::error_handler,
-    map::Map,
+    localization::Localization,
+    map::{Areas, Map, MapConfig},
     pathfinding::Destination,
+    sound::spawn_sound_at,
+    speech::SpeechQueue,
     visibility::{RevealedTiles, Viewshed, VisibleTiles},
 };
 
@@ -56,10 +63,37 @@ impl Into<&str> for ExplorationType {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExplorationCursorMode {
+    Free,
+    StopAtWalls,
+    WrapInArea,
+}
+
+impl Default for ExplorationCursorMode {
+    fn default() -> Self {
+        ExplorationCursorMode::Free
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Deref, DerefMut, Reflect)]
 #[reflect(Component)]
 pub struct Exploring(pub (f32, f32));
 
+// Counts down to returning an idle explorer's cursor when `MapConfig::exploration_timeout` is set.
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct ExplorationIdle(pub Timer);
+
+#[derive(Clone, Copy, Debug)]
+pub struct RememberedEntity {
+    pub coordinates: Coordinates,
+    pub exploration_type: ExplorationType,
+    pub seen: Instant,
+}
+
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct RememberedEntities(pub HashMap<Entity, RememberedEntity>);
+
 impl_pointlike_for_tuple_component!(Exploring);
 
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
@@ -78,9 +112,15 @@ pub const ACTION_EXPLORE_FOCUS_PREV: &str = "explore_focus_prev";
 pub const ACTION_EXPLORE_SELECT_NEXT_TYPE: &str = "explore_select_next_type";
 pub const ACTION_EXPLORE_SELECT_PREV_TYPE: &str = "explore_select_prev_type";
 pub const ACTION_NAVIGATE_TO_EXPLORED: &str = "navigate_to";
+pub const ACTION_GUIDE_TO_FRONTIER: &str = "guide_to_frontier";
+pub const ACTION_SPEAK_SURROUNDINGS: &str = "speak_surroundings";
+pub const ACTION_JUMP_TO_NEAREST_ENEMY: &str = "jump_to_nearest_enemy";
+pub const ACTION_JUMP_TO_NEAREST_EXIT: &str = "jump_to_nearest_exit";
+pub const ACTION_JUMP_TO_NEAREST_ITEM: &str = "jump_to_nearest_item";
 
 fn exploration_type_change(
     mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
     input: Res<InputMap<String>>,
     mut explorers: Query<(&Player, &Viewshed, &mut FocusedExplorationType)>,
     features: Query<(&Coordinates, &ExplorationType)>,
@@ -103,7 +143,7 @@ fn exploration_type_change(
         types.sort();
         types.dedup();
         if types.is_empty() {
-            tts.speak("Nothing visible.", true)?;
+            tts.speak(localization.get("exploration.nothing_visible"), true)?;
         } else if input.just_active(ACTION_EXPLORE_SELECT_PREV_TYPE) {
             if let Some(t) = &focused.0 {
                 if let Some(i) = types.iter().position(|v| *v == *t) {
@@ -147,6 +187,7 @@ fn exploration_type_focus(
     mut commands: Commands,
     input: Res<InputMap<String>>,
     mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
     explorers: Query<(
         Entity,
         &Player,
@@ -176,7 +217,7 @@ fn exploration_type_focus(
             features.retain(|(_, t)| **t == *focused);
         }
         if features.is_empty() {
-            tts.speak("Nothing visible.", true)?;
+            tts.speak(localization.get("exploration.nothing_visible"), true)?;
         } else {
             let mut target: Option<&(&Coordinates, &ExplorationType)> = None;
             if input.just_active(ACTION_EXPLORE_FOCUS_NEXT) {
@@ -207,8 +248,51 @@ fn exploration_type_focus(
     Ok(())
 }
 
+fn jump_to_nearest_feature(
+    mut commands: Commands,
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    explorers: Query<(Entity, &Player, &Coordinates, &Viewshed)>,
+    features: Query<(&Coordinates, &ExplorationType)>,
+) -> Result<(), Box<dyn Error>> {
+    let exploration_type = if input.just_active(ACTION_JUMP_TO_NEAREST_ENEMY) {
+        ExplorationType::Enemy
+    } else if input.just_active(ACTION_JUMP_TO_NEAREST_EXIT) {
+        ExplorationType::Exit
+    } else if input.just_active(ACTION_JUMP_TO_NEAREST_ITEM) {
+        ExplorationType::Item
+    } else {
+        return Ok(());
+    };
+    for (entity, _, coordinates, viewshed) in explorers.iter() {
+        let nearest = features
+            .iter()
+            .filter(|(c, t)| **t == exploration_type && viewshed.is_visible(*c))
+            .map(|(c, _)| c)
+            .min_by(|a, b| {
+                coordinates
+                    .distance(*a)
+                    .partial_cmp(&coordinates.distance(*b))
+                    .unwrap()
+            });
+        if let Some(nearest) = nearest {
+            commands.entity(entity).insert(Exploring(**nearest));
+        } else {
+            let t: &str = exploration_type.into();
+            let t = localization.get(&format!("exploration_type.{}", t));
+            tts.speak(
+                localization.format("exploration.none_visible", &[&t.to_lowercase()]),
+                true,
+            )?;
+        }
+    }
+    Ok(())
+}
+
 fn exploration_type_changed_announcement(
     mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
     focused: Query<
         (
             &FocusedExplorationType,
@@ -223,24 +307,92 @@ fn exploration_type_changed_announcement(
         }
         match &focused.0 {
             Some(v) => {
-                let v: String = (*v).into();
-                tts.speak(v, true)?;
+                let v: &str = (*v).into();
+                tts.speak(localization.get(&format!("exploration_type.{}", v)), true)?;
             }
             None => {
-                tts.speak("Everything", true)?;
+                tts.speak(localization.get("exploration.everything"), true)?;
             }
         };
     }
     Ok(())
 }
 
+/// Steps `from` by `delta` within its area, wrapping past either edge to the opposite one
+/// and skipping over walls.
+fn wrap_in_area(
+    from: (f32, f32),
+    delta: (f32, f32),
+    map: &Map,
+    areas: &Areas,
+) -> Option<(f32, f32)> {
+    let area = areas.iter().find(|a| a.contains(&from))?;
+    let rect = area.rect;
+    let width = (rect.x2 - rect.x1 + 1) as i32;
+    let height = (rect.y2 - rect.y1 + 1) as i32;
+    let mut x = from.0 as i32 - rect.x1 as i32;
+    let mut y = from.1 as i32 - rect.y1 as i32;
+    for _ in 0..width.max(height) {
+        x = (x + delta.0 as i32).rem_euclid(width);
+        y = (y + delta.1 as i32).rem_euclid(height);
+        let point = ((rect.x1 as i32 + x) as f32, (rect.y1 as i32 + y) as f32);
+        if point.0 < map.width() as f32
+            && point.1 < map.height() as f32
+            && map.base.tiles[point.to_index(map.width())] != TileType::Wall
+        {
+            return Some(point);
+        }
+    }
+    None
+}
+
+/// Whether moving from a wall tile by `delta` would carry the cursor out through that wall.
+fn leaves_through_wall(from: (f32, f32), delta: (f32, f32), map: &Map) -> bool {
+    if map.base.tiles[from.to_index(map.width())] != TileType::Wall {
+        return false;
+    }
+    let behind = (from.0 - delta.0, from.1 - delta.1);
+    behind.0 >= 0.
+        && behind.1 >= 0.
+        && behind.0 < map.width() as f32
+        && behind.1 < map.height() as f32
+        && map.base.tiles[behind.to_index(map.width())] != TileType::Wall
+}
+
+// Where the cursor lands when stepped from `from` by `delta` onto an in-bounds tile, or `None`
+// if the configured wall handling blocks it.
+fn step_cursor(
+    from: (f32, f32),
+    delta: (f32, f32),
+    map: &Map,
+    areas: Option<&Areas>,
+    config: &MapConfig,
+) -> Option<(f32, f32)> {
+    if config.stop_exploration_at_walls && leaves_through_wall(from, delta, map) {
+        return None;
+    }
+    let to = (from.0 + delta.0, from.1 + delta.1);
+    if map.base.tiles[to.to_index(map.width())] != TileType::Wall {
+        return Some(to);
+    }
+    match config.exploration_cursor_mode {
+        ExplorationCursorMode::Free => Some(to),
+        ExplorationCursorMode::StopAtWalls => None,
+        ExplorationCursorMode::WrapInArea => areas
+            .and_then(|areas| wrap_in_area(from, delta, map, areas))
+            .filter(|wrapped| *wrapped != from),
+    }
+}
+
 fn exploration_focus(
     mut commands: Commands,
     input: Res<InputMap<String>>,
-    map: Query<&Map>,
+    asset_server: Res<AssetServer>,
+    config: Res<MapConfig>,
+    map: Query<(Entity, &Map, Option<&Areas>)>,
     explorers: Query<(Entity, &Player, &Coordinates, Option<&Exploring>)>,
 ) {
-    for map in map.iter() {
+    for (map_entity, map, areas) in map.iter() {
         for (entity, _, coordinates, exploring) in explorers.iter() {
             let coordinates = **coordinates;
             let coordinates = (coordinates.0.floor(), coordinates.1.floor());
@@ -265,7 +417,60 @@ fn exploration_focus(
                 && exploring.1 >= 0.
                 && exploring.1 < map.height() as f32
             {
-                commands.entity(entity).insert(Exploring(exploring));
+                let delta = (exploring.0 - orig.0, exploring.1 - orig.1);
+                if let Some(exploring) = step_cursor(orig, delta, map, areas, &config) {
+                    commands.entity(entity).insert(Exploring(exploring));
+                } else if let Some(sound) = config.exploration_blocked_sound {
+                    spawn_sound_at(
+                        &mut commands,
+                        map_entity,
+                        asset_server.get_handle(sound),
+                        &orig,
+                        Default::default(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn exploration_timeout(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<MapConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+    mut explorers: Query<(Entity, Option<&mut ExplorationIdle>), With<Exploring>>,
+    changed: Query<Entity, Changed<Exploring>>,
+    focused: Query<Entity, With<ExplorationFocused>>,
+    paused: Res<Paused>,
+) {
+    if **paused {
+        return;
+    }
+    if let Some(timeout) = config.exploration_timeout {
+        for (entity, idle) in explorers.iter_mut() {
+            let mut idle = if let Some(idle) = idle {
+                idle
+            } else {
+                commands
+                    .entity(entity)
+                    .insert(ExplorationIdle(Timer::from_seconds(timeout, false)));
+                continue;
+            };
+            if changed.get(entity).is_ok() {
+                idle.reset();
+            }
+            idle.tick(time.delta());
+            if idle.finished() {
+                commands
+                    .entity(entity)
+                    .remove::<Exploring>()
+                    .remove::<ExplorationIdle>();
+                for entity in focused.iter() {
+                    commands.entity(entity).remove::<ExplorationFocused>();
+                }
+                speech.push(localization.get("exploration.returned"));
             }
         }
     }
@@ -291,17 +496,143 @@ fn navigate_to_explored(
     }
 }
 
+pub fn nearest_frontier(
+    from: &dyn PointLike,
+    map: &Map,
+    revealed_tiles: &RevealedTiles,
+) -> Option<(usize, usize)> {
+    let width = map.width();
+    let height = map.height();
+    let mut nearest: Option<((usize, usize), f32)> = None;
+    for x in 0..width {
+        for y in 0..height {
+            let idx = (x, y).to_index(width);
+            if !revealed_tiles[idx] || map.base.tiles[idx] != TileType::Floor {
+                continue;
+            }
+            let borders_unrevealed = map
+                .neighbors(&(x, y), false)
+                .iter()
+                .any(|neighbor| !revealed_tiles[neighbor.to_index(width)]);
+            if borders_unrevealed {
+                let distance = from.distance(&(x, y));
+                if nearest.map_or(true, |(_, d)| distance < d) {
+                    nearest = Some(((x, y), distance));
+                }
+            }
+        }
+    }
+    nearest.map(|(point, _)| point)
+}
+
+fn guide_to_frontier(
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    map: Query<(&Map, &RevealedTiles)>,
+    player: Query<(&Player, &Coordinates)>,
+) -> Result<(), Box<dyn Error>> {
+    if input.just_active(ACTION_GUIDE_TO_FRONTIER) {
+        if let Ok((_, coordinates)) = player.single() {
+            if let Ok((map, revealed_tiles)) = map.single() {
+                if let Some(frontier) = nearest_frontier(coordinates, map, revealed_tiles) {
+                    tts.speak(
+                        localization.format(
+                            "exploration.unexplored",
+                            &[&localization.distance_and_direction(coordinates, &frontier)],
+                        ),
+                        true,
+                    )?;
+                } else {
+                    tts.speak(localization.get("exploration.fully_explored"), true)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn speak_surroundings(
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    explorers: Query<(&Player, &Coordinates, &Viewshed)>,
+    features: Query<(&Coordinates, &ExplorationType)>,
+) -> Result<(), Box<dyn Error>> {
+    if !input.just_active(ACTION_SPEAK_SURROUNDINGS) {
+        return Ok(());
+    }
+    for (_, coordinates, viewshed) in explorers.iter() {
+        let mut groups: BTreeMap<ExplorationType, (usize, Coordinates)> = BTreeMap::new();
+        for (feature_coordinates, t) in features.iter() {
+            if !viewshed.is_visible(feature_coordinates) {
+                continue;
+            }
+            let distance = coordinates.distance(feature_coordinates);
+            let group = groups.entry(*t).or_insert((0, *feature_coordinates));
+            group.0 += 1;
+            if distance < coordinates.distance(&group.1) {
+                group.1 = *feature_coordinates;
+            }
+        }
+        if groups.is_empty() {
+            tts.speak(localization.get("exploration.nothing_visible"), true)?;
+            continue;
+        }
+        let summary: Vec<String> = groups
+            .iter()
+            .map(|(t, (count, nearest))| {
+                let t: &str = (*t).into();
+                localization.format_plural(
+                    &format!("exploration.surroundings.{}", t),
+                    *count,
+                    &[
+                        count,
+                        &localization.distance_and_direction(coordinates, &nearest.i32()),
+                    ],
+                )
+            })
+            .collect();
+        tts.speak(summary.join(", "), true)?;
+    }
+    Ok(())
+}
+
+fn exploration_details(
+    localization: &Localization,
+    config: &MapConfig,
+    coordinate_config: &CoordinateConfig,
+    direction: String,
+    exploring: &Exploring,
+    height: usize,
+    fog_of_war: bool,
+) -> String {
+    let mut tokens: Vec<String> = vec![direction];
+    if config.speak_exploration_coordinates {
+        let (x, y) = coordinate_config.spoken(exploring, height);
+        tokens.push(localization.format("exploration.coordinates", &[&x, &y]));
+    }
+    if fog_of_war && config.announce_fog_of_war {
+        tokens.push(localization.get("exploration.fog_of_war"));
+    }
+    tokens.join(", ")
+}
+
 fn exploration_changed_announcement(
     mut commands: Commands,
     mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    config: Res<MapConfig>,
+    coordinate_config: Res<CoordinateConfig>,
     map: Query<(&Map, &RevealedTiles, &VisibleTiles)>,
-    explorers: Query<(&Coordinates, &Exploring), Changed<Exploring>>,
+    explorers: Query<(&Coordinates, &Exploring, Option<&Transform>), Changed<Exploring>>,
     focused: Query<(Entity, &ExplorationFocused)>,
     names: Query<&Name>,
     types: Query<&ExplorationType>,
     mappables: Query<&Mappable>,
+    remembered: Res<RememberedEntities>,
 ) -> Result<(), Box<dyn Error>> {
-    for (coordinates, exploring) in explorers.iter() {
+    for (coordinates, exploring, transform) in explorers.iter() {
         let coordinates = **coordinates;
         let coordinates = (coordinates.0.floor(), coordinates.1.floor());
         for (map, revealed_tiles, visible_tiles) in map.iter() {
@@ -311,7 +642,7 @@ fn exploration_changed_announcement(
             let visible = visible_tiles[idx];
             let fog_of_war = known && !visible;
             let description = if known {
-                let mut tokens: Vec<&str> = vec![];
+                let mut tokens: Vec<String> = vec![];
                 for (entity, _) in focused.iter() {
                     commands.entity(entity).remove::<ExplorationFocused>();
                 }
@@ -321,44 +652,108 @@ fn exploration_changed_announcement(
                         .insert(ExplorationFocused::default());
                     if visible || mappables.get(*entity).is_ok() {
                         if let Ok(name) = names.get(*entity) {
-                            tokens.push(name.as_str());
+                            tokens.push(name.to_string());
                         }
                         if tokens.is_empty() {
                             if let Ok(t) = types.get(*entity) {
-                                tokens.push((*t).into());
+                                let t: &str = (*t).into();
+                                tokens.push(localization.get(&format!("exploration_type.{}", t)));
                             }
                         }
                     }
                 }
+                if tokens.is_empty() && fog_of_war {
+                    for (entity, entry) in remembered.iter() {
+                        if entry.coordinates.to_index(map.width()) != idx {
+                            continue;
+                        }
+                        let label = if let Ok(name) = names.get(*entity) {
+                            name.to_string()
+                        } else {
+                            let t: &str = entry.exploration_type.into();
+                            localization.get(&format!("exploration_type.{}", t))
+                        };
+                        tokens.push(localization.format("exploration.last_seen", &[&label]));
+                    }
+                }
                 if tokens.is_empty() {
                     match map.base.tiles[idx] {
-                        TileType::Floor => "Floor".to_string(),
-                        TileType::Wall => "Wall".to_string(),
+                        TileType::Floor => localization.get("exploration.floor"),
+                        TileType::Wall => localization.get("exploration.wall"),
                     }
                 } else {
                     tokens.join(": ")
                 }
             } else {
-                "Unknown".to_string()
+                localization.get("exploration.unknown")
             };
-            let mut tokens: Vec<String> = vec![coordinates.distance_and_direction(exploring)];
-            if fog_of_war {
-                tokens.push("in the fog of war".into());
-            }
-            tts.speak(format!("{}: {}", description, tokens.join(", ")), true)?;
+            let direction = match (config.use_clock_directions, transform) {
+                (true, Some(transform)) => {
+                    let forward = transform.local_x();
+                    let yaw = Angle::Radians(forward.y.atan2(forward.x));
+                    localization.distance_and_clock_direction(&coordinates, exploring, yaw)
+                }
+                _ => localization.distance_and_direction(&coordinates, exploring),
+            };
+            let details = exploration_details(
+                &localization,
+                &config,
+                &coordinate_config,
+                direction,
+                exploring,
+                map.height(),
+                fog_of_war,
+            );
+            tts.speak(
+                localization.format("exploration.description", &[&description, &details]),
+                true,
+            )?;
         }
     }
     Ok(())
 }
 
+fn remember_entities(
+    config: Res<MapConfig>,
+    mut remembered: ResMut<RememberedEntities>,
+    map: Query<(&Map, &VisibleTiles)>,
+    entities: Query<(Entity, &Coordinates, &ExplorationType), Without<Mappable>>,
+) {
+    let duration = if let Some(duration) = config.remember_entities_for {
+        duration
+    } else {
+        remembered.clear();
+        return;
+    };
+    if let Ok((map, visible_tiles)) = map.single() {
+        for (entity, coordinates, exploration_type) in entities.iter() {
+            if visible_tiles[coordinates.to_index(map.width())] {
+                remembered.insert(
+                    entity,
+                    RememberedEntity {
+                        coordinates: *coordinates,
+                        exploration_type: *exploration_type,
+                        seen: Instant::now(),
+                    },
+                );
+            }
+        }
+    }
+    remembered.retain(|entity, v| {
+        entities.get(*entity).is_ok() && v.seen.elapsed().as_secs_f32() <= duration
+    });
+}
+
 pub struct ExplorationPlugin;
 
 impl Plugin for ExplorationPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.register_type::<ExplorationFocused>()
+        app.init_resource::<RememberedEntities>()
+            .register_type::<ExplorationFocused>()
             .register_type::<ExplorationType>()
             .register_type::<Mappable>()
             .add_system(exploration_focus.system())
+            .add_system(remember_entities.system())
             .add_system(
                 exploration_type_focus
                     .system()
@@ -370,6 +765,14 @@ impl Plugin for ExplorationPlugin {
                     .chain(error_handler.system()),
             )
             .add_system(navigate_to_explored.system())
+            .add_system(exploration_timeout.system())
+            .add_system(guide_to_frontier.system().chain(error_handler.system()))
+            .add_system(speak_surroundings.system().chain(error_handler.system()))
+            .add_system(
+                jump_to_nearest_feature
+                    .system()
+                    .chain(error_handler.system()),
+            )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 exploration_type_changed_announcement
@@ -384,3 +787,124 @@ impl Plugin for ExplorationPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::open_map;
+
+    fn room_with_wall_at(x: usize) -> Map {
+        let mut map = open_map(8, 3);
+        for y in 0..3 {
+            map.base.tiles[(x, y).to_index(8)] = TileType::Wall;
+        }
+        map
+    }
+
+    #[test]
+    fn stop_at_walls_refuses_to_enter_a_wall() {
+        let map = room_with_wall_at(4);
+        let mut config = MapConfig::default();
+        assert_eq!(
+            step_cursor((3., 1.), (1., 0.), &map, None, &config),
+            Some((4., 1.))
+        );
+        config.exploration_cursor_mode = ExplorationCursorMode::StopAtWalls;
+        assert_eq!(step_cursor((3., 1.), (1., 0.), &map, None, &config), None);
+        assert_eq!(
+            step_cursor((3., 1.), (-1., 0.), &map, None, &config),
+            Some((2., 1.))
+        );
+    }
+
+    #[test]
+    fn stopping_at_walls_keeps_the_cursor_from_passing_through() {
+        let mut map = room_with_wall_at(4);
+        for y in 0..3 {
+            map.base.tiles[(5, y).to_index(8)] = TileType::Wall;
+        }
+        let config = MapConfig {
+            stop_exploration_at_walls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            step_cursor((3., 1.), (1., 0.), &map, None, &config),
+            Some((4., 1.))
+        );
+        assert_eq!(step_cursor((4., 1.), (1., 0.), &map, None, &config), None);
+        assert_eq!(
+            step_cursor((4., 1.), (-1., 0.), &map, None, &config),
+            Some((3., 1.))
+        );
+    }
+
+    #[test]
+    fn cursor_returns_after_idle_timeout() {
+        let mut world = World::default();
+        world.insert_resource(MapConfig {
+            exploration_timeout: Some(0.05),
+            ..Default::default()
+        });
+        world.insert_resource(Time::default());
+        world.insert_resource(Paused::default());
+        world.insert_resource(SpeechQueue::default());
+        world.insert_resource(Localization::default());
+        let player = world.spawn().insert(Exploring((2., 2.))).id();
+        let focused = world.spawn().insert(ExplorationFocused).id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(exploration_timeout.system());
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert!(world.get::<Exploring>(player).is_some());
+        let mut idle = world.get_mut::<ExplorationIdle>(player).unwrap();
+        let duration = idle.duration();
+        idle.tick(duration);
+        stage.run(&mut world);
+        assert!(world.get::<Exploring>(player).is_none());
+        assert!(world.get::<ExplorationFocused>(focused).is_none());
+        assert_eq!(world.get_resource::<SpeechQueue>().unwrap().pending(), 1);
+    }
+
+    #[test]
+    fn fog_suffix_is_omitted_when_the_toggle_is_off() {
+        let localization = Localization::default();
+        let coordinate_config = CoordinateConfig::default();
+        let exploring = Exploring((2., 2.));
+        let details = |config: &MapConfig, fog_of_war| {
+            exploration_details(
+                &localization,
+                config,
+                &coordinate_config,
+                "2 tiles north".into(),
+                &exploring,
+                5,
+                fog_of_war,
+            )
+        };
+        let mut config = MapConfig::default();
+        assert_eq!(details(&config, true), "2 tiles north, in the fog of war");
+        assert_eq!(details(&config, false), "2 tiles north");
+        config.announce_fog_of_war = false;
+        assert_eq!(details(&config, true), "2 tiles north");
+    }
+
+    #[test]
+    fn nearest_frontier_borders_unrevealed_space() {
+        let map = open_map(8, 3);
+        let mut revealed = RevealedTiles(vec![false; 8 * 3]);
+        for y in 0..3 {
+            for x in 0..4 {
+                revealed[(x, y).to_index(8)] = true;
+            }
+        }
+        let frontier = nearest_frontier(&(1, 1), &map, &revealed).unwrap();
+        assert_eq!(frontier, (3, 1));
+        assert!(revealed[frontier.to_index(8)]);
+        assert!(map
+            .neighbors(&frontier, false)
+            .iter()
+            .any(|neighbor| !revealed[neighbor.to_index(8)]));
+        let revealed = RevealedTiles(vec![true; 8 * 3]);
+        assert!(nearest_frontier(&(1, 1), &map, &revealed).is_none());
+    }
+}
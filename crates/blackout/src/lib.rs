@@ -11,6 +11,7 @@ pub use derive_more;
 pub mod error;
 pub mod exploration;
 pub use gilrs;
+pub mod localization;
 pub mod log;
 pub mod map;
 pub use mapgen;
@@ -18,4 +19,7 @@ pub mod navigation;
 pub mod pathfinding;
 pub use rand;
 pub mod sound;
+pub mod speech;
+#[cfg(test)]
+mod testing;
 pub mod visibility;
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, fmt::Display};
+
+use bevy::prelude::*;
+
+use crate::core::{Angle, CardinalDirection, MovementDirection, PointLike};
+
+pub const ENGLISH: &[(&str, &str)] = &[
+    ("direction.north", "north"),
+    ("direction.north_northeast", "north northeast"),
+    ("direction.northeast", "northeast"),
+    ("direction.east_northeast", "east northeast"),
+    ("direction.east", "east"),
+    ("direction.east_southeast", "east southeast"),
+    ("direction.southeast", "southeast"),
+    ("direction.south_southeast", "south southeast"),
+    ("direction.south", "south"),
+    ("direction.south_southwest", "south southwest"),
+    ("direction.southwest", "southwest"),
+    ("direction.west_southwest", "west southwest"),
+    ("direction.west", "west"),
+    ("direction.west_northwest", "west northwest"),
+    ("direction.northwest", "northwest"),
+    ("direction.north_northwest", "north northwest"),
+    ("direction.clock", "{} o'clock"),
+    ("distance.one", "{} tile {}"),
+    ("distance.other", "{} tiles {}"),
+    ("distance.clock.one", "{} tile at {}"),
+    ("distance.clock.other", "{} tiles at {}"),
+    ("exploration.coordinates", "({}, {})"),
+    ("exploration.description", "{}: {}"),
+    ("exploration.everything", "Everything"),
+    ("exploration.floor", "Floor"),
+    ("exploration.fog_of_war", "in the fog of war"),
+    ("exploration.fully_explored", "Fully explored."),
+    ("exploration.last_seen", "{} (last seen)"),
+    ("exploration.none_visible", "No {} visible."),
+    ("exploration.nothing_visible", "Nothing visible."),
+    ("exploration.returned", "Cursor returned."),
+    ("exploration.surroundings.ally.one", "{} ally {}"),
+    ("exploration.surroundings.ally.other", "{} allies {}"),
+    ("exploration.surroundings.character.one", "{} character {}"),
+    (
+        "exploration.surroundings.character.other",
+        "{} characters {}",
+    ),
+    ("exploration.surroundings.enemy.one", "{} enemy {}"),
+    ("exploration.surroundings.enemy.other", "{} enemies {}"),
+    ("exploration.surroundings.exit.one", "{} exit {}"),
+    ("exploration.surroundings.exit.other", "{} exits {}"),
+    ("exploration.surroundings.item.one", "{} item {}"),
+    ("exploration.surroundings.item.other", "{} items {}"),
+    ("exploration.unexplored", "Unexplored: {}"),
+    ("exploration.unknown", "Unknown"),
+    ("exploration.wall", "Wall"),
+    ("exploration_type.exit", "Exit"),
+    ("exploration_type.item", "Item"),
+    ("exploration_type.character", "Character"),
+    ("exploration_type.ally", "Ally"),
+    ("exploration_type.enemy", "Enemy"),
+    ("map.area_size", "{} by {} area."),
+    ("speech.queue.current", "{}, speaking: {}"),
+    ("speech.queue.pending.one", "{} utterance pending"),
+    ("speech.queue.pending.other", "{} utterances pending"),
+    ("visibility.seen", "{}: {}"),
+];
+
+#[derive(Clone, Debug)]
+pub struct Localization {
+    pub catalog: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        let mut localization = Self {
+            catalog: HashMap::new(),
+            fallback: HashMap::new(),
+        };
+        localization.add_fallbacks(ENGLISH);
+        localization
+    }
+}
+
+impl Localization {
+    pub fn add_fallbacks(&mut self, entries: &[(&str, &str)]) {
+        for (key, value) in entries {
+            self.fallback.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.catalog
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut message = self.get(key);
+        let mut start = 0;
+        for arg in args {
+            if let Some(index) = message[start..].find("{}") {
+                let index = start + index;
+                let arg = arg.to_string();
+                message.replace_range(index..index + 2, &arg);
+                start = index + arg.len();
+            }
+        }
+        message
+    }
+
+    pub fn format_plural(&self, key: &str, count: usize, args: &[&dyn Display]) -> String {
+        let suffix = if count == 1 { "one" } else { "other" };
+        self.format(&format!("{}.{}", key, suffix), args)
+    }
+
+    pub fn direction(&self, direction: MovementDirection) -> String {
+        let english: String = direction.into();
+        self.get(&format!("direction.{}", english.replace(' ', "_")))
+    }
+
+    pub fn cardinal_direction(&self, direction: CardinalDirection) -> String {
+        let english: String = direction.into();
+        self.get(&format!("direction.{}", english))
+    }
+
+    pub fn distance_and_direction(&self, from: &dyn PointLike, to: &dyn PointLike) -> String {
+        let distance = from.distance(to).round() as i32;
+        if distance > 0 {
+            self.format_plural(
+                "distance",
+                distance as usize,
+                &[&distance, &self.direction(from.direction(to))],
+            )
+        } else {
+            "".into()
+        }
+    }
+
+    pub fn distance_and_clock_direction(
+        &self,
+        from: &dyn PointLike,
+        to: &dyn PointLike,
+        facing: Angle,
+    ) -> String {
+        let distance = from.distance(to).round() as i32;
+        if distance > 0 {
+            let direction = self.format("direction.clock", &[&from.clock_hour(to, facing)]);
+            self.format_plural(
+                "distance.clock",
+                distance as usize,
+                &[&distance, &direction],
+            )
+        } else {
+            "".into()
+        }
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if !app.world().contains_resource::<Localization>() {
+            app.insert_resource(Localization::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_catalog_speaks_directions_in_that_language() {
+        let mut localization = Localization::default();
+        assert_eq!(localization.direction(MovementDirection::North), "north");
+        localization.catalog = [
+            ("direction.north", "nord"),
+            ("distance.other", "{} cases {}"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(localization.direction(MovementDirection::North), "nord");
+        assert_eq!(
+            localization.distance_and_direction(&(0, 0), &(0, 3)),
+            "3 cases nord"
+        );
+        assert_eq!(localization.direction(MovementDirection::South), "south");
+    }
+}
@@ -1,27 +1,55 @@
-use std::{error::Error, time::Instant};
+use std::time::Instant;
 
 use bevy::prelude::*;
-use bevy_tts::Tts;
+use bevy_input_actionmap::InputMap;
 use derive_more::{Deref, DerefMut};
 
-use crate::error::error_handler;
+use crate::speech::SpeechQueue;
 
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct Log(pub Vec<LogEntry>);
 
 impl Log {
     pub fn push<S: Into<String>>(&mut self, message: S) {
+        self.push_with_priority(message, LogPriority::Normal);
+    }
+
+    pub fn push_with_priority<S: Into<String>>(&mut self, message: S, priority: LogPriority) {
         self.0.push(LogEntry {
             time: Instant::now(),
             message: message.into(),
+            priority,
         })
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogPriority {
+    Low,
+    Normal,
+    Urgent,
+}
+
+impl Default for LogPriority {
+    fn default() -> Self {
+        LogPriority::Normal
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LogEntry {
     pub time: Instant,
     pub message: String,
+    pub priority: LogPriority,
+}
+
+pub const ACTION_REVIEW_LOG_PREV: &str = "REVIEW_LOG_PREV";
+pub const ACTION_REVIEW_LOG_NEXT: &str = "REVIEW_LOG_NEXT";
+pub const ACTION_REVIEW_LOG_LAST: &str = "REVIEW_LOG_LAST";
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogReview {
+    pub cursor: Option<usize>,
 }
 
 fn setup(mut commands: Commands) {
@@ -29,31 +57,72 @@ fn setup(mut commands: Commands) {
 }
 
 fn read_log(
-    mut tts: ResMut<Tts>,
+    mut queue: ResMut<SpeechQueue>,
     mut position: Local<usize>,
     log: Query<&Log, Changed<Log>>,
-) -> Result<(), Box<dyn Error>> {
+) {
     for log in log.iter() {
-        for (index, entry) in log.iter().enumerate() {
-            if index >= *position {
-                tts.speak(entry.message.clone(), false)?;
-                *position = index + 1;
+        let pending = log.iter().skip(*position);
+        let urgent = pending
+            .clone()
+            .any(|entry| entry.priority == LogPriority::Urgent);
+        let mut interrupted = false;
+        for entry in pending {
+            if urgent && entry.priority == LogPriority::Low {
+                continue;
             }
+            // Interrupting flushes anything already queued, so only do it once per batch.
+            let interrupt = entry.priority == LogPriority::Urgent && !interrupted;
+            interrupted |= interrupt;
+            if interrupt {
+                queue.interrupt(entry.message.clone());
+            } else {
+                queue.push(entry.message.clone());
+            }
+        }
+        *position = log.len();
+    }
+}
+
+fn review_log(
+    input: Res<InputMap<String>>,
+    mut speech: ResMut<SpeechQueue>,
+    mut review: ResMut<LogReview>,
+    log: Query<&Log>,
+) {
+    if let Ok(log) = log.single() {
+        if log.is_empty() {
+            return;
+        }
+        let last = log.len() - 1;
+        let cursor = if input.just_active(ACTION_REVIEW_LOG_PREV) {
+            Some(review.cursor.map_or(last, |c| c.saturating_sub(1)))
+        } else if input.just_active(ACTION_REVIEW_LOG_NEXT) {
+            Some(review.cursor.map_or(last, |c| (c + 1).min(last)))
+        } else if input.just_active(ACTION_REVIEW_LOG_LAST) {
+            Some(last)
+        } else {
+            None
+        };
+        if let Some(cursor) = cursor {
+            review.cursor = Some(cursor);
+            speech.interrupt(log[cursor].message.clone());
         }
     }
-    Ok(())
 }
 
 pub struct LogPlugin;
 
 impl Plugin for LogPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_startup_system(setup.system()).add_system_to_stage(
-            CoreStage::PostUpdate,
-            read_log
-                .system()
-                .chain(error_handler.system())
-                .after(crate::visibility::LOG_VISIBLE_LABEL),
-        );
+        app.init_resource::<LogReview>()
+            .add_startup_system(setup.system())
+            .add_system(review_log.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                read_log
+                    .system()
+                    .after(crate::visibility::LOG_VISIBLE_LABEL),
+            );
     }
 }
@@ -1,14 +1,15 @@
 use std::collections::{HashMap, HashSet};
 
-use bevy::prelude::*;
+use bevy::{asset::HandleId, prelude::*};
 use derive_more::{Deref, DerefMut};
 use mapgen::{geometry::Rect as MRect, Map as MapgenMap, MapFilter, TileType};
 use maze_generator::{prelude::*, recursive_backtracking::RbGenerator};
-use rand::prelude::StdRng;
+use rand::prelude::{Rng, StdRng};
 
 use crate::{
     core::{Area, Coordinates, Player, PointLike},
-    exploration::{ExplorationType, Mappable},
+    exploration::{ExplorationCursorMode, ExplorationType, Mappable},
+    localization::Localization,
     log::Log,
 };
 
@@ -58,6 +59,27 @@ impl Map {
     pub fn exit(&self) -> Option<mapgen::geometry::Point> {
         self.base.exit_point
     }
+
+    pub fn neighbors(&self, point: &dyn PointLike, diagonals: bool) -> Vec<(i32, i32)> {
+        let (x, y) = point.i32();
+        let mut neighbors = vec![];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) == (0, 0) || (!diagonals && dx != 0 && dy != 0) {
+                    continue;
+                }
+                let (nx, ny) = (x + dx, y + dy);
+                if nx >= 0
+                    && ny >= 0
+                    && (nx as usize) < self.width()
+                    && (ny as usize) < self.height()
+                {
+                    neighbors.push((nx, ny));
+                }
+            }
+        }
+        neighbors
+    }
 }
 
 pub trait ITileType {
@@ -83,17 +105,39 @@ impl ITileType for TileType {
 
 #[derive(Clone, Debug)]
 pub struct MapConfig {
+    pub announce_fog_of_war: bool,
     pub autospawn_exits: bool,
+    pub exploration_blocked_sound: Option<HandleId>,
+    pub exploration_cursor_mode: ExplorationCursorMode,
+    pub exploration_timeout: Option<f32>,
+    pub max_exits: Option<usize>,
+    // Seconds to remember where entities were last seen once they drop out of view.
+    pub remember_entities_for: Option<f32>,
+    pub seed: Option<u64>,
     pub speak_area_descriptions: bool,
+    // Append the cursor's coordinates, as configured by `CoordinateConfig`, to exploration announcements.
+    pub speak_exploration_coordinates: bool,
     pub start_revealed: bool,
+    pub stop_exploration_at_walls: bool,
+    pub use_clock_directions: bool,
 }
 
 impl Default for MapConfig {
     fn default() -> Self {
         Self {
+            announce_fog_of_war: true,
             autospawn_exits: true,
+            exploration_blocked_sound: None,
+            exploration_cursor_mode: Default::default(),
+            exploration_timeout: None,
+            max_exits: None,
+            remember_entities_for: None,
+            seed: None,
             speak_area_descriptions: true,
+            speak_exploration_coordinates: false,
             start_revealed: false,
+            stop_exploration_at_walls: false,
+            use_clock_directions: false,
         }
     }
 }
@@ -134,6 +178,7 @@ pub struct GridBuilder {
     height_in_rooms: u32,
     room_width: u32,
     room_height: u32,
+    seed: Option<u64>,
 }
 
 impl GridBuilder {
@@ -142,20 +187,28 @@ impl GridBuilder {
         height_in_rooms: u32,
         room_width: u32,
         room_height: u32,
+        seed: Option<u64>,
     ) -> Box<GridBuilder> {
         Box::new(GridBuilder {
             width_in_rooms,
             height_in_rooms,
             room_width,
             room_height,
+            seed,
         })
     }
 }
 
+fn maze_seed(seed: u64) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    bytes
+}
+
 impl MapFilter for GridBuilder {
     fn modify_map(&self, _rng: &mut StdRng, map: &MapgenMap) -> MapgenMap {
         let mut map = map.clone();
-        let mut generator = RbGenerator::new(None);
+        let mut generator = RbGenerator::new(self.seed.map(maze_seed));
         let maze = generator.generate(self.width_in_rooms as i32, self.height_in_rooms as i32);
         let total_height = (self.room_height + 1) * self.height_in_rooms + 1;
         for y in 0..self.height_in_rooms {
@@ -201,6 +254,169 @@ impl MapFilter for GridBuilder {
     }
 }
 
+const CAVE_AREA_SIZE: usize = 8;
+
+pub struct CaveBuilder {
+    fill_percent: f32,
+    smoothing_passes: u32,
+}
+
+impl CaveBuilder {
+    pub fn new(fill_percent: f32, smoothing_passes: u32) -> Box<CaveBuilder> {
+        Box::new(CaveBuilder {
+            fill_percent,
+            smoothing_passes,
+        })
+    }
+}
+
+impl MapFilter for CaveBuilder {
+    fn modify_map(&self, rng: &mut StdRng, map: &MapgenMap) -> MapgenMap {
+        let mut map = map.clone();
+        let width = map.width;
+        let height = map.height;
+        if width < 5 || height < 5 {
+            return map;
+        }
+        let mut walls = vec![true; width * height];
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                walls[y * width + x] = rng.gen::<f32>() < self.fill_percent;
+            }
+        }
+        for _ in 0..self.smoothing_passes {
+            let previous = walls.clone();
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    let mut neighbors = 0;
+                    for ny in y - 1..=y + 1 {
+                        for nx in x - 1..=x + 1 {
+                            if (nx, ny) != (x, y) && previous[ny * width + nx] {
+                                neighbors += 1;
+                            }
+                        }
+                    }
+                    if neighbors > 4 {
+                        walls[y * width + x] = true;
+                    } else if neighbors < 4 {
+                        walls[y * width + x] = false;
+                    }
+                }
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let tile = if walls[y * width + x] {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                };
+                map.set_tile(x, y, tile);
+            }
+        }
+        // Open a small clearing around each anchor and chain them together, so the flood fill
+        // below always starts on floor and the cave stays in one piece.
+        let anchor_count = (width * height / 150).max(1);
+        let mut anchors = vec![];
+        for _ in 0..anchor_count {
+            let x = rng.gen_range(2..width - 2);
+            let y = rng.gen_range(2..height - 2);
+            for cy in y - 1..=y + 1 {
+                for cx in x - 1..=x + 1 {
+                    map.set_tile(cx, cy, TileType::Floor);
+                }
+            }
+            anchors.push((x, y));
+        }
+        for pair in anchors.windows(2) {
+            let (x1, y1) = pair[0];
+            let (x2, y2) = pair[1];
+            for x in x1.min(x2)..=x1.max(x2) {
+                map.set_tile(x, y1, TileType::Floor);
+            }
+            for y in y1.min(y2)..=y1.max(y2) {
+                map.set_tile(x2, y, TileType::Floor);
+            }
+        }
+        let mut reachable = vec![false; width * height];
+        let mut frontier = vec![anchors[0]];
+        reachable[anchors[0].1 * width + anchors[0].0] = true;
+        while let Some((x, y)) = frontier.pop() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].iter() {
+                let idx = ny * width + nx;
+                if !reachable[idx] && map.at(*nx, *ny) == TileType::Floor {
+                    reachable[idx] = true;
+                    frontier.push((*nx, *ny));
+                }
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                if !reachable[y * width + x] {
+                    map.set_tile(x, y, TileType::Wall);
+                }
+            }
+        }
+        // Bound the floor in each chunk of the cave as a room. These are pushed directly rather
+        // than through `add_room`, which would carve the whole rectangle out as floor.
+        for chunk_y in (0..height).step_by(CAVE_AREA_SIZE) {
+            for chunk_x in (0..width).step_by(CAVE_AREA_SIZE) {
+                let mut bounds: Option<(usize, usize, usize, usize)> = None;
+                for y in chunk_y..(chunk_y + CAVE_AREA_SIZE).min(height) {
+                    for x in chunk_x..(chunk_x + CAVE_AREA_SIZE).min(width) {
+                        if reachable[y * width + x] {
+                            bounds = Some(match bounds {
+                                Some((x1, y1, x2, y2)) => {
+                                    (x1.min(x), y1.min(y), x2.max(x), y2.max(y))
+                                }
+                                None => (x, y, x, y),
+                            });
+                        }
+                    }
+                }
+                if let Some((x1, y1, x2, y2)) = bounds {
+                    map.rooms.push(MRect::new_i32(
+                        x1 as i32,
+                        y1 as i32,
+                        (x2 - x1 + 1) as i32,
+                        (y2 - y1 + 1) as i32,
+                    ));
+                }
+            }
+        }
+        map
+    }
+}
+
+fn spread_exits(mut candidates: Vec<(f32, f32)>, max: usize) -> Vec<(f32, f32)> {
+    if candidates.len() <= max {
+        return candidates;
+    }
+    let mut exits: Vec<(f32, f32)> = vec![];
+    if max == 0 {
+        return exits;
+    }
+    exits.push(candidates.remove(0));
+    while exits.len() < max {
+        let mut best: Option<(usize, f32)> = None;
+        for (index, candidate) in candidates.iter().enumerate() {
+            let nearest = exits
+                .iter()
+                .map(|e| e.distance(candidate))
+                .fold(f32::MAX, f32::min);
+            if best.map_or(true, |(_, d)| nearest > d) {
+                best = Some((index, nearest));
+            }
+        }
+        if let Some((index, _)) = best {
+            exits.push(candidates.remove(index));
+        } else {
+            break;
+        }
+    }
+    exits
+}
+
 fn exit_spawner(
     mut commands: Commands,
     map: Query<(Entity, &Map), Added<Map>>,
@@ -212,25 +428,26 @@ fn exit_spawner(
             for x in 1..map.width() {
                 for y in 1..map.height() {
                     let mut spawn_exit = false;
-                    if map.base.get_available_exits(x, y).len() > 2 {
-                        let idx = (x, y).to_index(map.width());
-                        if map.base.tiles[idx] == TileType::Floor
-                            && (x > 1 && map.base.tiles[idx - 1] == TileType::Floor)
-                            && (x < map.width() - 2 && map.base.tiles[idx + 1] == TileType::Floor)
-                            && (y > 1
-                                && map.base.tiles[idx - map.width() as usize] == TileType::Wall)
-                            && (y < map.height() - 2
-                                && map.base.tiles[idx + map.width() as usize] == TileType::Wall)
+                    if map.base.get_available_exits(x, y).len() > 2
+                        && map.base.at(x, y) == TileType::Floor
+                    {
+                        let neighbors = map.neighbors(&(x, y), false);
+                        let is = |dx: i32, dy: i32, tile: TileType| {
+                            let point = (x as i32 + dx, y as i32 + dy);
+                            neighbors.contains(&point)
+                                && map.base.at(point.0 as usize, point.1 as usize) == tile
+                        };
+                        if is(-1, 0, TileType::Floor)
+                            && is(1, 0, TileType::Floor)
+                            && is(0, -1, TileType::Wall)
+                            && is(0, 1, TileType::Wall)
                         {
                             spawn_exit = true;
                         }
-                        if map.base.tiles[idx] == TileType::Floor
-                            && (x > 1 && map.base.tiles[idx - 1] == TileType::Wall)
-                            && (x < map.width() - 2 && map.base.tiles[idx + 1] == TileType::Wall)
-                            && (y > 1
-                                && map.base.tiles[idx - map.width() as usize] == TileType::Floor)
-                            && (y < map.height() - 2
-                                && map.base.tiles[idx + map.width() as usize] == TileType::Floor)
+                        if is(-1, 0, TileType::Wall)
+                            && is(1, 0, TileType::Wall)
+                            && is(0, -1, TileType::Floor)
+                            && is(0, 1, TileType::Floor)
                         {
                             spawn_exit = true;
                         }
@@ -244,6 +461,9 @@ fn exit_spawner(
                     }
                 }
             }
+            if let Some(max_exits) = config.max_exits {
+                exits = spread_exits(exits, max_exits);
+            }
             for exit in exits {
                 let x = exit.0 as f32;
                 let y = exit.1 as f32;
@@ -262,42 +482,63 @@ fn exit_spawner(
     }
 }
 
-fn area_description(
-    mut prev_area: Local<Option<Area>>,
-    query: Query<(&Player, &Coordinates), Changed<Coordinates>>,
-    map: Query<(&Map, &Areas)>,
-    mut log: Query<&mut Log>,
+#[derive(Clone, Debug)]
+pub struct AreaChanged {
+    pub entity: Entity,
+    pub from: Option<Area>,
+    pub to: Area,
+}
+
+fn area_change(
+    // The last area each entity was in, and whether it's still inside it.
+    mut cache: Local<HashMap<Entity, (Area, bool)>>,
+    mut events: EventWriter<AreaChanged>,
+    new_areas: Query<&Areas, Added<Areas>>,
+    areas: Query<&Areas>,
+    query: Query<(Entity, &Player, &Coordinates), Changed<Coordinates>>,
 ) {
-    for (_, coordinates) in query.iter() {
-        for (_, areas) in map.iter() {
-            let mut should_describe_area = false;
-            let mut current_area: Option<Area> = None;
-            for area in areas.iter() {
-                if area.contains(&*coordinates) {
-                    current_area = Some(area.clone());
-                    if let Some(prev_area) = &*prev_area {
-                        if prev_area != area {
-                            should_describe_area = true;
-                        }
-                    } else {
-                        should_describe_area = true;
-                    }
-                    break;
-                }
-            }
-            if should_describe_area {
-                if let Some(ref area) = current_area {
-                    let description = if area.description.is_some() {
-                        area.description.as_ref().unwrap().clone()
-                    } else {
-                        format!("{} by {} area.", area.rect.width(), area.rect.height())
-                    };
-                    for mut log in log.iter_mut() {
-                        log.push(description.clone());
+    if new_areas.iter().next().is_some() {
+        cache.clear();
+    }
+    for (entity, _, coordinates) in query.iter() {
+        for areas in areas.iter() {
+            let area = areas.iter().find(|a| a.contains(coordinates));
+            if let Some((last, inside)) = cache.get_mut(&entity) {
+                match area {
+                    // Coming back from a corridor into the same room is a transition too.
+                    Some(area) if *area != *last || !*inside => {
+                        events.send(AreaChanged {
+                            entity,
+                            from: Some(last.clone()),
+                            to: area.clone(),
+                        });
+                        *last = area.clone();
+                        *inside = true;
                     }
+                    Some(_) => {}
+                    None => *inside = false,
                 }
+            } else if let Some(area) = area {
+                // Whoever places the player on a new map announces that first area.
+                cache.insert(entity, (area.clone(), true));
             }
-            *prev_area = current_area;
+        }
+    }
+}
+
+fn area_description(
+    localization: Res<Localization>,
+    mut events: EventReader<AreaChanged>,
+    mut log: Query<&mut Log>,
+) {
+    for AreaChanged { to: area, .. } in events.iter() {
+        let description = if area.description.is_some() {
+            area.description.as_ref().unwrap().clone()
+        } else {
+            localization.format("map.area_size", &[&area.rect.width(), &area.rect.height()])
+        };
+        for mut log in log.iter_mut() {
+            log.push(description.clone());
         }
     }
 }
@@ -324,6 +565,22 @@ fn entity_indexing(
     }
 }
 
+fn remove_entity_index(
+    mut map: Query<&mut Map>,
+    mut previous_index: ResMut<PreviousIndex>,
+    removed: RemovedComponents<Coordinates>,
+) {
+    for entity in removed.iter() {
+        if let Some(prev_idx) = previous_index.remove(&entity) {
+            for mut map in map.iter_mut() {
+                if prev_idx < map.entities.len() {
+                    map.entities[prev_idx].retain(|&e| e != entity);
+                }
+            }
+        }
+    }
+}
+
 fn add_areas(mut commands: Commands, query: Query<(Entity, &Map), (Added<Map>, Without<Areas>)>) {
     for (entity, map) in query.iter() {
         let mut v = vec![];
@@ -331,6 +588,7 @@ fn add_areas(mut commands: Commands, query: Query<(Entity, &Map), (Added<Map>, W
             v.push(Area {
                 rect: *room,
                 description: None,
+                light: 1.,
             });
         }
         commands.entity(entity).insert(Areas(v));
@@ -338,6 +596,7 @@ fn add_areas(mut commands: Commands, query: Query<(Entity, &Map), (Added<Map>, W
 }
 
 pub const UPDATE_ENTITY_INDEX_LABEL: &str = "UPDATE_ENTITY_INDEX";
+pub const AREA_CHANGE_LABEL: &str = "AREA_CHANGE";
 
 pub struct MapPlugin;
 
@@ -349,8 +608,12 @@ impl Plugin for MapPlugin {
         let config = app.world().get_resource::<MapConfig>().unwrap().clone();
         const SPAWN_EXITS: &str = "SPAWN_EXITS";
         app.register_type::<Exit>()
+            .add_event::<AreaChanged>()
             .insert_resource(PreviousIndex::default())
-            .add_system(entity_indexing.system().label(UPDATE_ENTITY_INDEX_LABEL))
+            .add_system_to_stage(
+                CoreStage::Update,
+                entity_indexing.system().label(UPDATE_ENTITY_INDEX_LABEL),
+            )
             .add_system(
                 exit_spawner
                     .system()
@@ -361,10 +624,143 @@ impl Plugin for MapPlugin {
                 CoreStage::PostUpdate,
                 entity_indexing.system().label(UPDATE_ENTITY_INDEX_LABEL),
             )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                remove_entity_index
+                    .system()
+                    .before(UPDATE_ENTITY_INDEX_LABEL),
+            )
             .add_system_to_stage(CoreStage::Update, add_areas.system())
-            .add_system_to_stage(CoreStage::PostUpdate, add_areas.system());
+            .add_system_to_stage(CoreStage::PostUpdate, add_areas.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                area_change.system().label(AREA_CHANGE_LABEL),
+            );
         if config.speak_area_descriptions {
-            app.add_system_to_stage(CoreStage::PostUpdate, area_description.system());
+            app.add_system_to_stage(
+                CoreStage::PostUpdate,
+                area_description.system().after(AREA_CHANGE_LABEL),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+
+    use super::*;
+
+    #[test]
+    fn despawned_entity_leaves_its_former_tile() {
+        let mut world = World::default();
+        world.insert_resource(PreviousIndex::default());
+        let map = world.spawn().insert(Map::new(MapgenMap::new(4, 4))).id();
+        let entity = world.spawn().insert(Coordinates((2., 1.))).id();
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(entity_indexing.system())
+            .add_system(remove_entity_index.system());
+        stage.run(&mut world);
+        let idx = (2, 1).to_index(4);
+        assert!(world.get::<Map>(map).unwrap().entities[idx].contains(&entity));
+        world.despawn(entity);
+        stage.run(&mut world);
+        assert!(!world.get::<Map>(map).unwrap().entities[idx].contains(&entity));
+    }
+
+    fn corridor_with_doorways() -> Map {
+        let mut base = MapgenMap::new(12, 5);
+        for x in 1..11 {
+            base.tiles[2 * 12 + x] = TileType::Floor;
+        }
+        for x in (2..10).step_by(2) {
+            base.tiles[12 + x] = TileType::Floor;
+        }
+        Map::new(base)
+    }
+
+    fn spawned_exits(max_exits: Option<usize>) -> usize {
+        let mut world = World::default();
+        world.insert_resource(MapConfig {
+            max_exits,
+            ..Default::default()
+        });
+        world.spawn().insert(corridor_with_doorways());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(exit_spawner.system());
+        stage.run(&mut world);
+        world.query::<&Exit>().iter(&world).count()
+    }
+
+    #[test]
+    fn max_exits_caps_spawned_exits() {
+        assert_eq!(spawned_exits(None), 4);
+        assert_eq!(spawned_exits(Some(2)), 2);
+    }
+
+    #[test]
+    fn neighbors_stay_in_bounds_at_corners_and_edges() {
+        let map = Map::new(MapgenMap::new(5, 4));
+        let counts = |point: (i32, i32)| {
+            (
+                map.neighbors(&point, false).len(),
+                map.neighbors(&point, true).len(),
+            )
+        };
+        assert_eq!(counts((0, 0)), (2, 3));
+        assert_eq!(counts((4, 3)), (2, 3));
+        assert_eq!(counts((2, 0)), (3, 5));
+        assert_eq!(counts((0, 2)), (3, 5));
+        assert_eq!(counts((2, 2)), (4, 8));
+        assert!(map
+            .neighbors(&(4, 0), true)
+            .iter()
+            .all(|(x, y)| (3..=4).contains(x) && (0..=1).contains(y)));
+    }
+
+    #[test]
+    fn crossing_an_area_boundary_sends_one_event() {
+        let mut world = World::default();
+        world.insert_resource(Events::<AreaChanged>::default());
+        let room = |x| Area {
+            rect: MRect::new(x, 0, 3, 3),
+            description: None,
+            light: 1.,
+        };
+        world.spawn().insert(Areas(vec![room(0), room(6)]));
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((1., 1.)))
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(area_change.system());
+        let mut reader = world
+            .get_resource::<Events<AreaChanged>>()
+            .unwrap()
+            .get_reader();
+        let mut changes = vec![];
+        for x in [1., 2., 5., 1., 7.].iter() {
+            *world.get_mut::<Coordinates>(player).unwrap() = Coordinates((*x, 1.));
+            stage.run(&mut world);
+            let events = world.get_resource::<Events<AreaChanged>>().unwrap();
+            changes.push(
+                reader
+                    .iter(events)
+                    .map(|event| (event.from.as_ref().map(|a| a.rect.x1), event.to.rect.x1))
+                    .collect::<Vec<_>>(),
+            );
         }
+        assert_eq!(
+            changes,
+            vec![
+                vec![],
+                vec![],
+                vec![],
+                vec![(Some(0), 0)],
+                vec![(Some(0), 6)]
+            ]
+        );
     }
 }
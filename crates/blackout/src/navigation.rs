@@ -9,8 +9,10 @@ use crate::{
     core::{Angle, CardinalDirection, Coordinates, Player, PointLike},
     error::error_handler,
     exploration::{ExplorationFocused, Exploring},
+    localization::Localization,
     map::{ITileType, Map},
     pathfinding::Destination,
+    speech::SpeechQueue,
 };
 
 #[derive(Clone, Copy, Debug, Default, Reflect)]
@@ -53,6 +55,15 @@ impl Default for RotationSpeed {
 #[reflect(Component)]
 pub struct Speed(pub f32);
 
+#[derive(Clone, Copy, Debug, Deref, DerefMut)]
+pub struct AutoWalkSpeed(pub f32);
+
+impl Default for AutoWalkSpeed {
+    fn default() -> Self {
+        Self(1. / 3.)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Reflect)]
 #[reflect(Component)]
 pub struct Sprinting;
@@ -75,11 +86,14 @@ pub const ACTION_RIGHT: &str = "right";
 pub const ACTION_ROTATE_LEFT: &str = "ROTATE_LEFT";
 pub const ACTION_ROTATE_RIGHT: &str = "ROTATE_RIGHT";
 pub const ACTION_SPRINT: &str = "SPRINT";
+pub const ACTION_SPEAK_HEADING: &str = "SPEAK_HEADING";
 
-fn movement_controls(
+fn movement_controls<S>(
     mut commands: Commands,
     input: Res<InputMap<String>>,
     time: Res<Time>,
+    config: Res<NavigationConfig<S>>,
+    auto_walk_speed: Res<AutoWalkSpeed>,
     mut query: Query<(
         Entity,
         &Player,
@@ -91,7 +105,9 @@ fn movement_controls(
         Option<&Destination>,
     )>,
     exploration_focused: Query<(Entity, &ExplorationFocused)>,
-) {
+) where
+    S: bevy::ecs::component::Component + Clone + Debug + Eq + Hash,
+{
     for (
         entity,
         _,
@@ -145,9 +161,9 @@ fn movement_controls(
             let y = if right_y > left_y { right_y } else { left_y };
             let strength = Vec3::new(x, y, 0.);
             let s = if sprinting {
-                **max_speed
+                **max_speed * config.sprint_multiplier
             } else {
-                **max_speed / 3.
+                **max_speed / config.walk_divisor
             };
             speed.0 = s;
             direction *= s;
@@ -163,20 +179,40 @@ fn movement_controls(
         } else if destination.is_none() {
             **velocity = Vec2::ZERO;
             speed.0 = 0.;
-        } else if sprinting {
-            speed.0 = max_speed.0;
         } else {
-            speed.0 = max_speed.0 / 3.;
+            speed.0 = auto_walk(
+                **max_speed,
+                sprinting,
+                config.sprint_multiplier,
+                &auto_walk_speed,
+            );
         }
     }
 }
 
-fn movement(
+// Speed while following a `Destination`; sprinting still overrides the chosen pace.
+fn auto_walk(
+    max_speed: f32,
+    sprinting: bool,
+    sprint_multiplier: f32,
+    auto_walk_speed: &AutoWalkSpeed,
+) -> f32 {
+    if sprinting {
+        max_speed * sprint_multiplier
+    } else {
+        max_speed * **auto_walk_speed
+    }
+}
+
+fn movement<S>(
     time: Res<Time>,
+    config: Res<NavigationConfig<S>>,
     mut collision_events: EventWriter<Collision>,
     map: Query<(&Map, &MotionBlocked, &CollisionsMonitored)>,
     mut entities: Query<(Entity, &Velocity, &mut Coordinates, Option<&BlocksMotion>)>,
-) {
+) where
+    S: bevy::ecs::component::Component + Clone + Debug + Eq + Hash,
+{
     for (entity, velocity, mut coordinates, blocks_motion) in entities.iter_mut() {
         if **velocity != Vec2::ZERO {
             let displacement = **velocity * time.delta_seconds();
@@ -191,6 +227,18 @@ fn movement(
                         && motion_blocked[idx]
                         && !current_entities.contains(&entity)
                     {
+                        let free = |point: (f32, f32)| {
+                            let idx = point.to_index(map.width());
+                            idx < map.base.tiles.len()
+                                && (!motion_blocked[idx] || map.entities[idx].contains(&entity))
+                        };
+                        let x_only = (point.0, coordinates.y());
+                        let y_only = (coordinates.x(), point.1);
+                        if displacement.x != 0. && free(x_only) {
+                            **coordinates = x_only;
+                        } else if displacement.y != 0. && free(y_only) {
+                            **coordinates = y_only;
+                        }
                         collision_events.send(Collision {
                             entity,
                             coordinates: point,
@@ -209,7 +257,62 @@ fn movement(
                     }
                 }
             } else {
-                **coordinates = point;
+                **coordinates = unmapped_destination(config.missing_map, **coordinates, point);
+            }
+        }
+    }
+}
+
+// Where an entity trying to move from `from` to `to` ends up while no map is loaded.
+fn unmapped_destination(
+    behavior: MissingMapBehavior,
+    from: (f32, f32),
+    to: (f32, f32),
+) -> (f32, f32) {
+    match behavior {
+        MissingMapBehavior::Freeze => from,
+        MissingMapBehavior::MoveFreely => to,
+    }
+}
+
+// Moves `point` `factor` of the way toward the center line of the one-tile-wide corridor it's in.
+fn corridor_centered(
+    point: (f32, f32),
+    map: &Map,
+    motion_blocked: &MotionBlocked,
+    factor: f32,
+) -> (f32, f32) {
+    let (x, y) = point.i32();
+    if x < 1 || y < 1 || x >= map.width() as i32 - 1 || y >= map.height() as i32 - 1 {
+        return point;
+    }
+    let blocked = |x: i32, y: i32| motion_blocked[(x, y).to_index(map.width())];
+    if blocked(x - 1, y) && blocked(x + 1, y) && !blocked(x, y - 1) && !blocked(x, y + 1) {
+        let target = x as f32 + 0.5;
+        (point.0 + (target - point.0) * factor, point.1)
+    } else if blocked(x, y - 1) && blocked(x, y + 1) && !blocked(x - 1, y) && !blocked(x + 1, y) {
+        let target = y as f32 + 0.5;
+        (point.0, point.1 + (target - point.1) * factor)
+    } else {
+        point
+    }
+}
+
+fn center_in_corridor(
+    time: Res<Time>,
+    map: Query<(&Map, &MotionBlocked)>,
+    mut players: Query<(&Player, &Velocity, &mut Coordinates)>,
+) {
+    const CENTERING_RATE: f32 = 4.;
+    if let Ok((map, motion_blocked)) = map.single() {
+        let factor = (CENTERING_RATE * time.delta_seconds()).min(1.);
+        for (_, velocity, mut coordinates) in players.iter_mut() {
+            if **velocity == Vec2::ZERO {
+                continue;
+            }
+            let centered = corridor_centered(**coordinates, map, motion_blocked, factor);
+            if centered != **coordinates {
+                **coordinates = centered;
             }
         }
     }
@@ -361,24 +464,54 @@ fn add_collision_indices(
     }
 }
 
-fn speak_direction(
-    mut tts: ResMut<Tts>,
+fn speak_direction<S>(
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+    config: Res<NavigationConfig<S>>,
     mut cache: Local<HashMap<Entity, CardinalDirection>>,
     player: Query<(Entity, &Player, &Transform), Changed<Transform>>,
-) -> Result<(), Box<dyn Error>> {
+) where
+    S: bevy::ecs::component::Component + Clone + Debug + Eq + Hash,
+{
     if let Ok((entity, _, transform)) = player.single() {
-        let forward = transform.local_x();
-        let yaw = Angle::Radians(forward.y.atan2(forward.x));
-        if let Some(old_direction) = cache.get(&entity) {
-            let old_direction = *old_direction;
-            let direction: CardinalDirection = yaw.into();
-            if old_direction != direction {
-                let direction: String = direction.into();
-                tts.speak(direction, false)?;
+        let direction = heading(transform);
+        // Keep tracking while locked so unlocking doesn't announce a stale change.
+        if let Some(old_direction) = cache.insert(entity, direction) {
+            if let Some(direction) =
+                turn_announcement(old_direction, direction, config.heading_lock)
+            {
+                speech.push(localization.cardinal_direction(direction));
             }
-            cache.insert(entity, direction);
-        } else {
-            cache.insert(entity, yaw.into());
+        }
+    }
+}
+
+fn heading(transform: &Transform) -> CardinalDirection {
+    let forward = transform.local_x();
+    Angle::Radians(forward.y.atan2(forward.x)).into()
+}
+
+fn turn_announcement(
+    old: CardinalDirection,
+    new: CardinalDirection,
+    heading_lock: bool,
+) -> Option<CardinalDirection> {
+    if old != new && !heading_lock {
+        Some(new)
+    } else {
+        None
+    }
+}
+
+fn speak_heading(
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    player: Query<(&Player, &Transform)>,
+) -> Result<(), Box<dyn Error>> {
+    if input.just_active(ACTION_SPEAK_HEADING) {
+        if let Ok((_, transform)) = player.single() {
+            tts.speak(localization.cardinal_direction(heading(transform)), true)?;
         }
     }
     Ok(())
@@ -386,10 +519,27 @@ fn speak_direction(
 
 pub const MOVEMENT_LABEL: &str = "MOVEMENT";
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MissingMapBehavior {
+    Freeze,
+    MoveFreely,
+}
+
+impl Default for MissingMapBehavior {
+    fn default() -> Self {
+        MissingMapBehavior::Freeze
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct NavigationConfig<S> {
     pub movement_states: Vec<S>,
     pub movement_control_states: Vec<S>,
+    pub center_in_corridors: bool,
+    pub heading_lock: bool,
+    pub missing_map: MissingMapBehavior,
+    pub walk_divisor: f32,
+    pub sprint_multiplier: f32,
 }
 
 impl<S> Default for NavigationConfig<S> {
@@ -397,6 +547,11 @@ impl<S> Default for NavigationConfig<S> {
         Self {
             movement_states: vec![],
             movement_control_states: vec![],
+            center_in_corridors: false,
+            heading_lock: false,
+            missing_map: Default::default(),
+            walk_divisor: 3.,
+            sprint_multiplier: 1.,
         }
     }
 }
@@ -418,6 +573,9 @@ where
         if !app.world().contains_resource::<NavigationConfig<S>>() {
             app.insert_resource(NavigationConfig::<S>::default());
         }
+        if !app.world().contains_resource::<AutoWalkSpeed>() {
+            app.insert_resource(AutoWalkSpeed::default());
+        }
         let config = app
             .world()
             .get_resource::<NavigationConfig<S>>()
@@ -463,38 +621,129 @@ where
                     .label(UPDATE_COLLISION_INDEX_LABEL),
             )
             .add_system(add_collision_indices.system())
-            .add_system(speak_direction.system().chain(error_handler.system()))
+            .add_system(speak_direction::<S>.system())
+            .add_system(speak_heading.system().chain(error_handler.system()))
             .add_system_to_stage(CoreStage::PostUpdate, add_collision_indices.system());
+        // Centering adjusts coordinates after movement, so it shares movement's stage and
+        // states and finishes before the entity index is rebuilt from them.
         if config.movement_states.is_empty() {
-            app.add_system(
-                movement
+            app.add_system_to_stage(
+                CoreStage::Update,
+                movement::<S>
                     .system()
                     .label(MOVEMENT_LABEL)
                     .before(crate::map::UPDATE_ENTITY_INDEX_LABEL),
             );
+            if config.center_in_corridors {
+                app.add_system_to_stage(
+                    CoreStage::Update,
+                    center_in_corridor
+                        .system()
+                        .after(MOVEMENT_LABEL)
+                        .before(crate::map::UPDATE_ENTITY_INDEX_LABEL),
+                );
+            }
         } else {
             let states = config.movement_states;
             for state in states {
-                app.add_system_set(
-                    SystemSet::on_update(state).with_system(
-                        movement
+                let mut set = SystemSet::on_update(state).with_system(
+                    movement::<S>
+                        .system()
+                        .label(MOVEMENT_LABEL)
+                        .before(crate::map::UPDATE_ENTITY_INDEX_LABEL),
+                );
+                if config.center_in_corridors {
+                    set = set.with_system(
+                        center_in_corridor
                             .system()
-                            .label(MOVEMENT_LABEL)
+                            .after(MOVEMENT_LABEL)
                             .before(crate::map::UPDATE_ENTITY_INDEX_LABEL),
-                    ),
-                );
+                    );
+                }
+                app.add_system_set_to_stage(CoreStage::Update, set);
             }
         }
         if config.movement_control_states.is_empty() {
-            app.add_system(movement_controls.system().before(MOVEMENT_LABEL));
+            app.add_system(movement_controls::<S>.system().before(MOVEMENT_LABEL));
         } else {
             let states = config.movement_control_states;
             for state in states {
                 app.add_system_set(
                     SystemSet::on_update(state)
-                        .with_system(movement_controls.system().before(MOVEMENT_LABEL)),
+                        .with_system(movement_controls::<S>.system().before(MOVEMENT_LABEL)),
                 );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use mapgen::TileType;
+
+    use super::*;
+    use crate::testing::open_map;
+
+    #[test]
+    fn off_center_movement_in_a_corridor_converges_on_its_center_line() {
+        let mut map = open_map(5, 7);
+        for y in 0..7 {
+            map.base.tiles[(1, y).to_index(5)] = TileType::Wall;
+            map.base.tiles[(3, y).to_index(5)] = TileType::Wall;
+        }
+        let motion_blocked = MotionBlocked(
+            map.base
+                .tiles
+                .iter()
+                .map(|tile| *tile == TileType::Wall)
+                .collect(),
+        );
+        let mut point = (2.1, 3.5);
+        let mut offsets = vec![];
+        for _ in 0..10 {
+            point = corridor_centered(point, &map, &motion_blocked, 0.25);
+            offsets.push((point.0 - 2.5).abs());
+        }
+        assert!(offsets.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(offsets[9] < 0.05);
+        assert_eq!(point.1, 3.5);
+    }
+
+    #[test]
+    fn heading_lock_silences_turns_but_not_the_current_heading() {
+        let east = Transform::default();
+        let north = Transform::from_rotation(Quat::from_rotation_z(PI / 2.));
+        assert_eq!(heading(&east), CardinalDirection::East);
+        assert_eq!(heading(&north), CardinalDirection::North);
+        let (old, new) = (heading(&east), heading(&north));
+        assert_eq!(
+            turn_announcement(old, new, false),
+            Some(CardinalDirection::North)
+        );
+        assert_eq!(turn_announcement(old, new, true), None);
+        assert_eq!(turn_announcement(new, new, false), None);
+    }
+
+    #[test]
+    fn auto_walking_uses_the_configured_speed() {
+        let slow = AutoWalkSpeed(0.25);
+        let fast = AutoWalkSpeed(0.75);
+        assert_eq!(auto_walk(8., false, 1.5, &slow), 2.);
+        assert_eq!(auto_walk(8., false, 1.5, &fast), 6.);
+        assert_eq!(auto_walk(8., true, 1.5, &slow), 12.);
+    }
+
+    #[test]
+    fn nothing_moves_without_a_map_by_default() {
+        let (from, to) = ((2., 2.), (3., 2.));
+        let behavior = MissingMapBehavior::default();
+        assert_eq!(behavior, MissingMapBehavior::Freeze);
+        assert_eq!(unmapped_destination(behavior, from, to), from);
+        assert_eq!(
+            unmapped_destination(MissingMapBehavior::MoveFreely, from, to),
+            to
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
 
 use bevy::{prelude::*, tasks::prelude::*};
 use crossbeam_channel::{unbounded, Receiver};
@@ -7,8 +7,8 @@ use pathfinding::prelude::*;
 
 use crate::{
     core::{Coordinates, PointLike},
-    map::Map,
-    navigation::{MotionBlocked, RotationSpeed, Speed, Velocity},
+    map::{ITileType, Map},
+    navigation::{MotionBlocked, NavigationConfig, RotationSpeed, Speed, Velocity},
 };
 
 #[derive(Clone, Copy, Debug, Default, Deref, DerefMut, Eq, Hash, PartialEq, Reflect)]
@@ -21,25 +21,78 @@ impl_pointlike_for_tuple_component!(Destination);
 #[reflect(Component)]
 pub struct Path(pub Vec<(i32, i32)>);
 
-pub fn find_path(
+const CARDINAL_COST: f32 = 100.;
+const DIAGONAL_COST: f32 = CARDINAL_COST * std::f32::consts::SQRT_2;
+
+// Cost comes from geometry alone, since mapgen's exit weights already favor cardinal steps.
+fn step_cost(from: (i32, i32), to: (i32, i32)) -> u32 {
+    let diagonal = from.0 != to.0 && from.1 != to.1;
+    let cost = if diagonal {
+        DIAGONAL_COST
+    } else {
+        CARDINAL_COST
+    };
+    cost.round() as u32
+}
+
+fn octile_distance(from: (i32, i32), to: (i32, i32)) -> u32 {
+    let dx = (to.0 - from.0).abs() as f32;
+    let dy = (to.1 - from.1).abs() as f32;
+    let (min, max) = if dx < dy { (dx, dy) } else { (dy, dx) };
+    (DIAGONAL_COST * min + CARDINAL_COST * (max - min)) as u32
+}
+
+const OCCUPIED_COST: u32 = 500;
+
+fn find_weighted_path(
     start: &dyn PointLike,
     destination: &dyn PointLike,
     map: &Map,
+    occupied: Option<&[bool]>,
 ) -> Option<(Vec<(i32, i32)>, u32)> {
+    let goal: (i32, i32) = destination.into();
     astar(
         &start.into(),
         |p| {
             let mut successors: Vec<((i32, i32), u32)> = vec![];
             for tile in map.base.get_available_exits(p.0 as usize, p.1 as usize) {
-                successors.push(((tile.0 as i32, tile.1 as i32), (tile.2 * 100.) as u32));
+                let next = (tile.0 as i32, tile.1 as i32);
+                let mut cost = step_cost(*p, next);
+                if next != goal && occupied.map_or(false, |o| o[next.to_index(map.width())]) {
+                    cost += OCCUPIED_COST;
+                }
+                successors.push((next, cost));
             }
             successors
         },
-        |p| (p.distance_squared(destination) * 100.) as u32,
-        |p| *p == destination.into(),
+        |p| octile_distance(*p, goal),
+        |p| *p == goal,
     )
 }
 
+pub fn find_path(
+    start: &dyn PointLike,
+    destination: &dyn PointLike,
+    map: &Map,
+) -> Option<(Vec<(i32, i32)>, u32)> {
+    find_weighted_path(start, destination, map, None)
+}
+
+/// Like `find_path`, but routes around tiles currently held by other motion blockers where it can.
+pub fn find_path_around_blockers(
+    start: &dyn PointLike,
+    destination: &dyn PointLike,
+    map: &Map,
+    motion_blocked: &MotionBlocked,
+) -> Option<(Vec<(i32, i32)>, u32)> {
+    let occupied: Vec<bool> = motion_blocked
+        .iter()
+        .zip(map.base.tiles.iter())
+        .map(|(blocked, tile)| *blocked && !tile.blocks_motion())
+        .collect();
+    find_weighted_path(start, destination, map, Some(&occupied))
+}
+
 fn nearest_extreme(from: f32, to: i32) -> f32 {
     let to = to as f32;
     let range = to..=(to + 0.999);
@@ -83,7 +136,7 @@ fn calculate_path(
     mut calculating: Local<HashMap<Entity, Receiver<Path>>>,
     query: Query<(Entity, &Destination, &Coordinates), Changed<Destination>>,
     destinations: Query<&Destination>,
-    map: Query<&Map>,
+    map: Query<(&Map, &MotionBlocked)>,
 ) {
     let calculating_clone = calculating.clone();
     for (entity, rx) in calculating_clone.iter() {
@@ -100,13 +153,19 @@ fn calculate_path(
         if !calculating.contains_key(&entity) {
             let (tx, rx) = unbounded();
             calculating.insert(entity, rx);
-            for map in map.iter() {
+            for (map, motion_blocked) in map.iter() {
                 let start_clone = *coordinates;
                 let destination_clone = *destination;
                 let map_clone = map.clone();
+                let motion_blocked_clone = motion_blocked.clone();
                 let tx_clone = tx.clone();
                 pool.spawn(async move {
-                    if let Some(result) = find_path(&start_clone, &destination_clone, &map_clone) {
+                    if let Some(result) = find_path_around_blockers(
+                        &start_clone,
+                        &destination_clone,
+                        &map_clone,
+                        &motion_blocked_clone,
+                    ) {
                         tx_clone.send(Path(result.0)).expect("Channel should exist");
                     }
                 })
@@ -205,15 +264,65 @@ fn negotiate_path(
     }
 }
 
-pub struct PathfindingPlugin;
+pub struct PathfindingPlugin<'a, S>(std::marker::PhantomData<&'a S>);
 
-impl Plugin for PathfindingPlugin {
+impl<'a, S> Default for PathfindingPlugin<'a, S> {
+    fn default() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<'a, S> Plugin for PathfindingPlugin<'a, S>
+where
+    S: bevy::ecs::component::Component + Clone + Debug + Eq + Hash,
+    'a: 'static,
+{
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_to_stage(CoreStage::PostUpdate, calculate_path.system())
-            .add_system(
+        let config = app
+            .world()
+            .get_resource::<NavigationConfig<S>>()
+            .cloned()
+            .unwrap_or_default();
+        app.add_system_to_stage(CoreStage::PostUpdate, calculate_path.system());
+        if config.movement_states.is_empty() {
+            app.add_system(
                 negotiate_path
                     .system()
                     .before(crate::navigation::MOVEMENT_LABEL),
             );
+        } else {
+            for state in config.movement_states {
+                app.add_system_set(
+                    SystemSet::on_update(state).with_system(
+                        negotiate_path
+                            .system()
+                            .before(crate::navigation::MOVEMENT_LABEL),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapgen::TileType;
+
+    use super::*;
+
+    #[test]
+    fn diagonal_corridor_yields_straight_diagonal_path() {
+        let mut base = mapgen::Map::new(6, 6);
+        for i in 0..6 {
+            for j in 0..6 {
+                if (i as i32 - j as i32).abs() <= 1 {
+                    base.tiles[j * 6 + i] = TileType::Floor;
+                }
+            }
+        }
+        let map = Map::new(base);
+        let (path, _) = find_path(&(0, 0), &(5, 5), &map).expect("No path found");
+        let expected: Vec<(i32, i32)> = (0..6).map(|i| (i, i)).collect();
+        assert_eq!(path, expected);
     }
 }
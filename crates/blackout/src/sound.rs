@@ -1,18 +1,25 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use bevy::{
     asset::{HandleId, LoadState},
     prelude::*,
     transform::TransformSystem,
 };
-use bevy_openal::{Buffer, Context, Sound, SoundState};
+use bevy_openal::{Buffer, Context, Listener, Sound, SoundState};
+use derive_more::{Deref, DerefMut};
+use mapgen::TileType;
 
 use rand::random;
 
 use crate::{
-    core::{Coordinates, CoreConfig, Player, PointLike},
-    exploration::ExplorationFocused,
-    visibility::Viewshed,
+    core::{Coordinates, CoreConfig, Paused, Player, PointLike},
+    exploration::{ExplorationFocused, Exploring},
+    map::Map,
+    navigation::{Sprinting, Velocity},
+    visibility::{RevealedTiles, Viewshed},
 };
 
 #[derive(Clone, Debug, Reflect)]
@@ -41,6 +48,25 @@ impl Default for Footstep {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Surface {
+    Floor,
+    Wall,
+}
+
+impl From<TileType> for Surface {
+    fn from(tile: TileType) -> Self {
+        match tile {
+            TileType::Floor => Surface::Floor,
+            TileType::Wall => Surface::Wall,
+        }
+    }
+}
+
+/// Overrides `Footstep.sound` based on the surface the walker stands on.
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct FootstepSurfaces(pub HashMap<Surface, HandleId>);
+
 #[derive(Clone, Debug)]
 pub struct SoundIcon {
     pub sound: HandleId,
@@ -72,6 +98,213 @@ impl Default for SoundIcon {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Elevation(pub f32);
+
+#[derive(Clone, Copy, Debug)]
+pub struct SoundConfig {
+    pub pitch_encodes_elevation: bool,
+    pub elevation_pitch_scale: f32,
+    pub preview_explored_icons: bool,
+    pub doppler: bool,
+    pub speed_of_sound: f32,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            pitch_encodes_elevation: false,
+            elevation_pitch_scale: 0.1,
+            preview_explored_icons: false,
+            doppler: false,
+            speed_of_sound: 343.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct OcclusionConfig {
+    pub enabled: bool,
+    pub gain_per_wall: f32,
+    pub pitch_per_wall: f32,
+    pub min_gain: f32,
+    pub min_pitch: f32,
+}
+
+impl Default for OcclusionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gain_per_wall: 0.5,
+            pitch_per_wall: 0.05,
+            min_gain: 0.1,
+            min_pitch: 0.7,
+        }
+    }
+}
+
+impl OcclusionConfig {
+    /// Returns the gain and pitch multipliers for a source heard through `walls` walls.
+    pub fn attenuation(&self, walls: usize) -> (f32, f32) {
+        if !self.enabled || walls == 0 {
+            return (1., 1.);
+        }
+        let gain = self.gain_per_wall.powi(walls as i32).max(self.min_gain);
+        let pitch = (1. - self.pitch_per_wall * walls as f32).max(self.min_pitch);
+        (gain, pitch)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ListenerMode {
+    // Leave the `Listener` wherever its own entity puts it.
+    Single,
+    // Anchor at the midpoint of every `ListenerTarget`.
+    Centroid,
+    // Anchor at whichever `ListenerTarget` is closest to the point.
+    Nearest(Coordinates),
+}
+
+impl Default for ListenerMode {
+    fn default() -> Self {
+        ListenerMode::Single
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListenerConfig {
+    pub mode: ListenerMode,
+}
+
+// Marks the entities the listener is anchored to outside of `ListenerMode::Single`.
+#[derive(Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ListenerTarget;
+
+pub fn listener_position(mode: &ListenerMode, targets: &[Vec3]) -> Option<Vec3> {
+    if targets.is_empty() {
+        return None;
+    }
+    match mode {
+        ListenerMode::Single => None,
+        ListenerMode::Centroid => {
+            Some(targets.iter().fold(Vec3::ZERO, |sum, v| sum + *v) / targets.len() as f32)
+        }
+        ListenerMode::Nearest(point) => targets.iter().copied().min_by(|a, b| {
+            let a = point.distance_squared(&(a.x, a.y));
+            let b = point.distance_squared(&(b.x, b.y));
+            a.partial_cmp(&b).unwrap()
+        }),
+    }
+}
+
+/// Counts the distinct wall tiles crossed between `from` and `to`, ignoring the tiles both
+/// endpoints stand on.
+pub fn walls_between(from: &dyn PointLike, to: &dyn PointLike, map: &Map) -> usize {
+    const STEP: f32 = 0.5;
+    let width = map.width();
+    let endpoints = [from.to_index(width), to.to_index(width)];
+    let distance = from.distance(to);
+    let steps = (distance / STEP).ceil() as u32;
+    let mut walls: HashSet<usize> = HashSet::new();
+    for step in 1..steps {
+        let t = step as f32 * STEP / distance;
+        let point = (
+            from.x() + (to.x() - from.x()) * t,
+            from.y() + (to.y() - from.y()) * t,
+        );
+        let index = point.to_index(width);
+        if !endpoints.contains(&index) && map.base.tiles[index] == TileType::Wall {
+            walls.insert(index);
+        }
+    }
+    walls.len()
+}
+
+fn occlusion(
+    config: &OcclusionConfig,
+    source: &dyn PointLike,
+    listener: Option<&Coordinates>,
+    map: &Query<&Map>,
+) -> (f32, f32) {
+    if !config.enabled {
+        return (1., 1.);
+    }
+    if let (Some(listener), Ok(map)) = (listener, map.single()) {
+        config.attenuation(walls_between(source, listener, map))
+    } else {
+        (1., 1.)
+    }
+}
+
+pub fn elevation_pitch_offset(emitter: f32, listener: f32, scale: f32) -> f32 {
+    (emitter - listener).max(-1.).min(1.) * scale
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SoundParams {
+    pub gain: f32,
+    pub pitch: f32,
+    pub looping: bool,
+    pub reference_distance: f32,
+    pub max_distance: f32,
+    pub rolloff_factor: f32,
+}
+
+impl Default for SoundParams {
+    fn default() -> Self {
+        Self {
+            gain: 1.,
+            pitch: 1.,
+            looping: false,
+            reference_distance: 1.,
+            max_distance: f32::MAX,
+            rolloff_factor: 1.,
+        }
+    }
+}
+
+pub fn spawn_sound_at(
+    commands: &mut Commands,
+    parent: Entity,
+    buffer: Handle<Buffer>,
+    coordinates: &dyn PointLike,
+    params: SoundParams,
+) -> Entity {
+    let sound = commands
+        .spawn()
+        .insert(Transform::from_translation(Vec3::new(
+            coordinates.x(),
+            coordinates.y(),
+            0.,
+        )))
+        .insert(GlobalTransform::default())
+        .insert(Sound {
+            buffer,
+            state: SoundState::Playing,
+            gain: params.gain,
+            pitch: params.pitch,
+            looping: params.looping,
+            reference_distance: params.reference_distance,
+            max_distance: params.max_distance,
+            rolloff_factor: params.rolloff_factor,
+            ..Default::default()
+        })
+        .id();
+    commands.entity(parent).push_children(&[sound]);
+    sound
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Heard {
+    pub coordinates: Coordinates,
+    pub loudness: f32,
+}
+
+pub const FOOTSTEP_LOUDNESS: f32 = 4.;
+pub const SPRINTING_FOOTSTEP_LOUDNESS: f32 = 10.;
+
 #[derive(Bundle, Default)]
 pub struct FootstepBundle {
     pub footstep: Footstep,
@@ -93,6 +326,12 @@ fn footstep(
     footsteps: Query<(Entity, &Footstep, &Parent, Option<&Children>), Changed<GlobalTransform>>,
     coordinates_storage: Query<&Coordinates>,
     mut sounds: Query<&mut Sound>,
+    mut heard: EventWriter<Heard>,
+    players: Query<Option<&Sprinting>, With<Player>>,
+    listeners: Query<&Coordinates, With<Player>>,
+    map: Query<&Map>,
+    occlusion_config: Res<OcclusionConfig>,
+    surfaces: Option<Res<FootstepSurfaces>>,
 ) {
     for (entity, footstep, parent, children) in footsteps.iter() {
         let coordinates = coordinates_storage.get(**parent).unwrap();
@@ -103,17 +342,45 @@ fn footstep(
                     last_step_distance.insert(entity, (0., *coordinates));
                     let sound = children[0];
                     if let Ok(mut sound) = sounds.get_mut(sound) {
-                        sound.gain = footstep.gain;
+                        let surface_sound = surfaces.as_ref().and_then(|surfaces| {
+                            let map = map.single().ok()?;
+                            let tile = map.base.at(coordinates.x_usize(), coordinates.y_usize());
+                            surfaces.get(&tile.into()).copied()
+                        });
+                        let buffer = assets.get_handle(surface_sound.unwrap_or(footstep.sound));
+                        if sound.buffer != buffer {
+                            sound.stop();
+                            sound.buffer = buffer;
+                        }
+                        let (gain, occluded_pitch) = occlusion(
+                            &occlusion_config,
+                            coordinates,
+                            listeners.single().ok(),
+                            &map,
+                        );
+                        sound.gain = footstep.gain * gain;
                         sound.reference_distance = footstep.reference_distance;
                         sound.max_distance = footstep.max_distance;
                         sound.rolloff_factor = footstep.rolloff_factor;
+                        let mut pitch = 1.;
                         if let Some(pitch_variation) = footstep.pitch_variation {
-                            let mut pitch = 1. - pitch_variation / 2.;
+                            pitch -= pitch_variation / 2.;
                             pitch += random::<f32>() * pitch_variation;
-                            sound.pitch = pitch;
                         }
+                        sound.pitch = pitch * occluded_pitch;
                         sound.play();
                     }
+                    if let Ok(sprinting) = players.get(**parent) {
+                        let loudness = if sprinting.is_some() {
+                            SPRINTING_FOOTSTEP_LOUDNESS
+                        } else {
+                            FOOTSTEP_LOUDNESS
+                        };
+                        heard.send(Heard {
+                            coordinates: *coordinates,
+                            loudness,
+                        });
+                    }
                 } else if last.1 != *coordinates {
                     last_step_distance.insert(entity, (distance, *coordinates));
                 }
@@ -143,7 +410,9 @@ fn sound_icon(
     mut commands: Commands,
     time: Res<Time>,
     asset_server: Res<AssetServer>,
-    viewers: Query<(&Player, &Viewshed)>,
+    config: Res<SoundConfig>,
+    viewers: Query<(&Player, &Viewshed, &Coordinates, Option<&Elevation>)>,
+    elevations: Query<&Elevation>,
     mut icons: Query<(
         Entity,
         &mut SoundIcon,
@@ -153,8 +422,14 @@ fn sound_icon(
     )>,
     coordinates_storage: Query<&Coordinates>,
     mut sounds: Query<&mut Sound>,
+    map: Query<&Map>,
+    occlusion_config: Res<OcclusionConfig>,
+    paused: Res<Paused>,
 ) {
-    for (_, viewer) in viewers.iter() {
+    if **paused {
+        return;
+    }
+    for (_, viewer, listener, listener_elevation) in viewers.iter() {
         for (entity, mut icon, coordinates, parent, children) in icons.iter_mut() {
             let coords = if let Some(coordinates) = coordinates {
                 *coordinates
@@ -165,14 +440,35 @@ fn sound_icon(
             } else {
                 panic!("No `Coordinates` on `SoundIcon` or parent");
             };
+            let pitch = if config.pitch_encodes_elevation {
+                let elevation = elevations
+                    .get(entity)
+                    .ok()
+                    .or_else(|| parent.and_then(|p| elevations.get(**p).ok()))
+                    .map(|e| e.0)
+                    .unwrap_or_default();
+                let listener_elevation = listener_elevation.map(|e| e.0).unwrap_or_default();
+                icon.pitch
+                    * (1.
+                        + elevation_pitch_offset(
+                            elevation,
+                            listener_elevation,
+                            config.elevation_pitch_scale,
+                        ))
+            } else {
+                icon.pitch
+            };
+            let (gain, occluded_pitch) =
+                occlusion(&occlusion_config, &coords, Some(listener), &map);
+            let pitch = pitch * occluded_pitch;
             if viewer.is_visible(&coords) {
                 let buffer = asset_server.get_handle(icon.sound);
                 if asset_server.get_load_state(&buffer) == LoadState::Loaded {
                     let looping = icon.interval.is_none();
                     let sound = Sound {
                         buffer,
-                        gain: icon.gain,
-                        pitch: icon.pitch,
+                        gain: icon.gain * gain,
+                        pitch,
                         looping,
                         state: SoundState::Playing,
                         ..Default::default()
@@ -212,8 +508,8 @@ fn sound_icon(
                                     sound.buffer = buffer;
                                     sound.play();
                                 }
-                                sound.gain = icon.gain;
-                                sound.pitch = icon.pitch;
+                                sound.gain = icon.gain * gain;
+                                sound.pitch = pitch;
                                 sound.reference_distance = icon.reference_distance;
                                 sound.max_distance = icon.max_distance;
                                 sound.rolloff_factor = icon.rolloff_factor;
@@ -247,6 +543,123 @@ fn sound_icon_exploration_focus_removed(
     }
 }
 
+fn preview_explored_icon(
+    mut commands: Commands,
+    config: Res<SoundConfig>,
+    asset_server: Res<AssetServer>,
+    explorers: Query<&Exploring, Changed<Exploring>>,
+    map: Query<(Entity, &Map, &RevealedTiles)>,
+    icons: Query<&SoundIcon>,
+    children: Query<&Children>,
+) {
+    if !config.preview_explored_icons {
+        return;
+    }
+    for exploring in explorers.iter() {
+        for (map_entity, map, revealed_tiles) in map.iter() {
+            let idx = exploring.to_index(map.width());
+            if idx >= map.entities.len() || !revealed_tiles[idx] {
+                continue;
+            }
+            let icon = map.entities[idx].iter().find_map(|entity| {
+                icons.get(*entity).ok().or_else(|| {
+                    children
+                        .get(*entity)
+                        .ok()
+                        .and_then(|children| children.iter().find_map(|c| icons.get(*c).ok()))
+                })
+            });
+            if let Some(icon) = icon {
+                spawn_sound_at(
+                    &mut commands,
+                    map_entity,
+                    asset_server.get_handle(icon.sound),
+                    &**exploring,
+                    SoundParams {
+                        gain: icon.gain,
+                        pitch: icon.pitch,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub fn doppler_factor(
+    source: Vec2,
+    source_velocity: Vec2,
+    listener: Vec2,
+    listener_velocity: Vec2,
+    speed_of_sound: f32,
+) -> f32 {
+    let offset = listener - source;
+    let distance = offset.length();
+    if distance == 0. || speed_of_sound <= 0. {
+        return 1.;
+    }
+    let direction = offset / distance;
+    // Keep sources from ever reaching the speed of sound, where the shift blows up.
+    let limit = speed_of_sound * 0.9;
+    let source_speed = source_velocity.dot(direction).min(limit);
+    let listener_speed = listener_velocity.dot(direction).min(limit);
+    (speed_of_sound - listener_speed) / (speed_of_sound - source_speed)
+}
+
+// The pitch some other system last set, and what we replaced it with.
+#[derive(Clone, Copy, Debug)]
+struct DopplerPitch {
+    base: f32,
+    applied: f32,
+}
+
+fn doppler(
+    mut commands: Commands,
+    config: Res<SoundConfig>,
+    listener: Query<(&Player, &Coordinates, Option<&Velocity>)>,
+    mut sounds: Query<(
+        Entity,
+        &mut Sound,
+        &Coordinates,
+        &Velocity,
+        Option<&mut DopplerPitch>,
+    )>,
+) {
+    if !config.doppler {
+        return;
+    }
+    if let Ok((_, listener_coordinates, listener_velocity)) = listener.single() {
+        let listener_position = Vec2::new(listener_coordinates.x(), listener_coordinates.y());
+        let listener_velocity = listener_velocity.map(|v| **v).unwrap_or_default();
+        for (entity, mut sound, coordinates, velocity, doppler_pitch) in sounds.iter_mut() {
+            let factor = doppler_factor(
+                Vec2::new(coordinates.x(), coordinates.y()),
+                **velocity,
+                listener_position,
+                listener_velocity,
+                config.speed_of_sound,
+            );
+            // Combine with whatever pitch the owner set rather than compounding our own shift.
+            let base = match doppler_pitch {
+                Some(ref doppler_pitch) if sound.pitch == doppler_pitch.applied => {
+                    doppler_pitch.base
+                }
+                _ => sound.pitch,
+            };
+            let applied = base * factor;
+            sound.pitch = applied;
+            if let Some(mut doppler_pitch) = doppler_pitch {
+                doppler_pitch.base = base;
+                doppler_pitch.applied = applied;
+            } else {
+                commands
+                    .entity(entity)
+                    .insert(DopplerPitch { base, applied });
+            }
+        }
+    }
+}
+
 fn scale_sounds(config: Res<CoreConfig>, mut sounds: Query<&mut Sound>) {
     let pixels_per_unit = config.pixels_per_unit as f32;
     for mut sound in sounds.iter_mut() {
@@ -256,19 +669,51 @@ fn scale_sounds(config: Res<CoreConfig>, mut sounds: Query<&mut Sound>) {
         }
     }
 }
+fn anchor_listener(
+    config: Res<ListenerConfig>,
+    targets: Query<&GlobalTransform, (With<ListenerTarget>, Without<Listener>)>,
+    mut listener: Query<(&mut Transform, &mut GlobalTransform), With<Listener>>,
+) {
+    let targets: Vec<Vec3> = targets.iter().map(|t| t.translation).collect();
+    if let Some(position) = listener_position(&config.mode, &targets) {
+        if let Ok((mut transform, mut global_transform)) = listener.single_mut() {
+            transform.translation = position;
+            global_transform.translation = position;
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct SoundPlugin;
 
 impl Plugin for SoundPlugin {
     fn build(&self, app: &mut AppBuilder) {
         const SOUND_ICON_AND_EXPLORATION_STAGE: &str = "sound_icon_and_exploration";
+        if !app.world().contains_resource::<SoundConfig>() {
+            app.insert_resource(SoundConfig::default());
+        }
+        if !app.world().contains_resource::<OcclusionConfig>() {
+            app.insert_resource(OcclusionConfig::default());
+        }
+        if !app.world().contains_resource::<ListenerConfig>() {
+            app.insert_resource(ListenerConfig::default());
+        }
         let config = *app.world().get_resource::<CoreConfig>().unwrap();
         if let Some(context) = app.world().get_resource::<Context>() {
             context
                 .set_meters_per_unit(1. / config.pixels_per_unit as f32)
                 .unwrap();
         }
-        app.register_type::<Footstep>()
+        app.add_event::<Heard>()
+            .register_type::<Elevation>()
+            .register_type::<Footstep>()
+            .register_type::<ListenerTarget>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                anchor_listener
+                    .system()
+                    .after(TransformSystem::TransformPropagate),
+            )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 footstep.system().after(TransformSystem::TransformPropagate),
@@ -279,6 +724,7 @@ impl Plugin for SoundPlugin {
                     .system()
                     .after(TransformSystem::TransformPropagate),
             )
+            .add_system_to_stage(CoreStage::PostUpdate, doppler.system())
             .add_stage_after(
                 CoreStage::PostUpdate,
                 SOUND_ICON_AND_EXPLORATION_STAGE,
@@ -292,6 +738,153 @@ impl Plugin for SoundPlugin {
                 SOUND_ICON_AND_EXPLORATION_STAGE,
                 sound_icon_exploration_focus_removed.system(),
             )
+            .add_system_to_stage(
+                SOUND_ICON_AND_EXPLORATION_STAGE,
+                preview_explored_icon.system(),
+            )
             .add_system(scale_sounds.system());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+    use crate::testing::asset_world;
+
+    #[test]
+    fn exploring_onto_an_icon_previews_its_sound_once() {
+        let mut world = asset_world();
+        world.insert_resource(SoundConfig {
+            preview_explored_icons: true,
+            ..Default::default()
+        });
+        let icon = SoundIcon {
+            sound: "sfx/exit.flac".into(),
+            gain: 0.7,
+            pitch: 1.2,
+            ..Default::default()
+        };
+        let handle = world
+            .get_resource::<AssetServer>()
+            .unwrap()
+            .get_handle(icon.sound);
+        let icon = world.spawn().insert(icon).id();
+        let mut map = Map::new(mapgen::Map::new(5, 5));
+        map.entities[(2, 3).to_index(5)].insert(icon);
+        let map = world
+            .spawn()
+            .insert(map)
+            .insert(RevealedTiles(vec![true; 25]))
+            .id();
+        world.spawn().insert(Exploring((2., 3.)));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(preview_explored_icon.system());
+        stage.run(&mut world);
+        stage.run(&mut world);
+        let previews = world
+            .query::<(&Sound, &Transform, &Parent)>()
+            .iter(&world)
+            .map(|(sound, transform, parent)| {
+                (
+                    sound.buffer.clone(),
+                    sound.state,
+                    sound.gain,
+                    sound.pitch,
+                    transform.translation,
+                    **parent,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            previews,
+            vec![(
+                handle,
+                SoundState::Playing,
+                0.7,
+                1.2,
+                Vec3::new(2., 3., 0.),
+                map
+            )]
+        );
+    }
+
+    #[test]
+    fn spawn_sound_at_plays_a_positioned_child() {
+        let mut world = World::default();
+        let parent = world.spawn().id();
+        let mut queue = CommandQueue::default();
+        let sound = {
+            let mut commands = Commands::new(&mut queue, &world);
+            spawn_sound_at(
+                &mut commands,
+                parent,
+                Handle::default(),
+                &(3, 4),
+                SoundParams {
+                    gain: 0.5,
+                    ..Default::default()
+                },
+            )
+        };
+        queue.apply(&mut world);
+        assert_eq!(
+            world.get::<Transform>(sound).unwrap().translation,
+            Vec3::new(3., 4., 0.)
+        );
+        let playing = world.get::<Sound>(sound).unwrap();
+        assert_eq!(playing.state, SoundState::Playing);
+        assert_eq!(playing.gain, 0.5);
+        assert_eq!(**world.get::<Parent>(sound).unwrap(), parent);
+        assert!(world.get::<Children>(parent).unwrap().contains(&sound));
+    }
+
+    #[test]
+    fn emitters_above_the_listener_pitch_higher_than_those_below() {
+        let scale = SoundConfig::default().elevation_pitch_scale;
+        let above = elevation_pitch_offset(1., 0., scale);
+        let level = elevation_pitch_offset(0., 0., scale);
+        let below = elevation_pitch_offset(-1., 0., scale);
+        assert!(above > level);
+        assert!(level > below);
+        assert_eq!(level, 0.);
+        assert_eq!(elevation_pitch_offset(5., 0., scale), above);
+    }
+
+    #[test]
+    fn listener_anchors_to_the_centroid_or_nearest_target() {
+        let targets = [Vec3::new(0., 0., 0.), Vec3::new(4., 2., 0.)];
+        assert!(listener_position(&ListenerMode::Single, &targets).is_none());
+        assert_eq!(
+            listener_position(&ListenerMode::Centroid, &targets),
+            Some(Vec3::new(2., 1., 0.))
+        );
+        let nearest = ListenerMode::Nearest(Coordinates((5., 5.)));
+        assert_eq!(listener_position(&nearest, &targets), Some(targets[1]));
+        assert!(listener_position(&ListenerMode::Centroid, &[]).is_none());
+        let mut world = World::default();
+        world.insert_resource(ListenerConfig {
+            mode: ListenerMode::Centroid,
+        });
+        for target in targets.iter() {
+            world
+                .spawn()
+                .insert(ListenerTarget)
+                .insert(GlobalTransform::from_translation(*target));
+        }
+        let listener = world
+            .spawn()
+            .insert(Listener::default())
+            .insert(Transform::default())
+            .insert(GlobalTransform::default())
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(anchor_listener.system());
+        stage.run(&mut world);
+        assert_eq!(
+            world.get::<GlobalTransform>(listener).unwrap().translation,
+            Vec3::new(2., 1., 0.)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::VecDeque, error::Error};
+
+use bevy::prelude::*;
+use bevy_input_actionmap::InputMap;
+use bevy_tts::Tts;
+
+use crate::{error::error_handler, localization::Localization};
+
+pub const ACTION_SPEAK_QUEUE_STATUS: &str = "SPEAK_QUEUE_STATUS";
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpeechQueueConfig {
+    // Debug aid for diagnosing speech backlog.
+    pub status_action: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpeechQueue {
+    current: Option<String>,
+    pending: VecDeque<String>,
+    interrupt: bool,
+}
+
+impl SpeechQueue {
+    pub fn push<S: Into<String>>(&mut self, message: S) {
+        self.pending.push_back(message.into());
+    }
+
+    // Drops anything still pending and cuts off the current utterance.
+    pub fn interrupt<S: Into<String>>(&mut self, message: S) {
+        self.pending.clear();
+        self.pending.push_back(message.into());
+        self.interrupt = true;
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn status(&self, localization: &Localization) -> String {
+        let pending = self.pending();
+        let pending = localization.format_plural("speech.queue.pending", pending, &[&pending]);
+        if let Some(current) = self.current() {
+            localization.format("speech.queue.current", &[&pending, &current])
+        } else {
+            pending
+        }
+    }
+}
+
+fn speak_queue(mut tts: ResMut<Tts>, mut queue: ResMut<SpeechQueue>) -> Result<(), Box<dyn Error>> {
+    // Backends that can't report progress hand everything over and let the engine queue it.
+    if queue.current.is_some() && !queue.interrupt && tts.is_speaking().unwrap_or(false) {
+        return Ok(());
+    }
+    let interrupt = queue.interrupt;
+    queue.interrupt = false;
+    queue.current = queue.pending.pop_front();
+    if let Some(message) = queue.current.clone() {
+        tts.speak(message, interrupt)?;
+    }
+    Ok(())
+}
+
+fn speak_queue_status(
+    config: Res<SpeechQueueConfig>,
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    queue: Res<SpeechQueue>,
+) -> Result<(), Box<dyn Error>> {
+    if config.status_action && input.just_active(ACTION_SPEAK_QUEUE_STATUS) {
+        // Spoken directly so the report neither counts itself nor waits behind the backlog.
+        tts.speak(queue.status(&localization), false)?;
+    }
+    Ok(())
+}
+
+pub struct SpeechPlugin;
+
+impl Plugin for SpeechPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if !app.world().contains_resource::<SpeechQueueConfig>() {
+            app.insert_resource(SpeechQueueConfig::default());
+        }
+        app.init_resource::<SpeechQueue>()
+            .add_system(speak_queue_status.system().chain(error_handler.system()))
+            .add_system_to_stage(
+                CoreStage::Last,
+                speak_queue.system().chain(error_handler.system()),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_reports_pending_count() {
+        let localization = Localization::default();
+        let mut queue = SpeechQueue::default();
+        queue.push("One");
+        queue.push("Two");
+        queue.push("Three");
+        assert_eq!(queue.pending(), 3);
+        assert_eq!(queue.status(&localization), "3 utterances pending");
+        queue.current = queue.pending.pop_front();
+        assert_eq!(queue.pending(), 2);
+        assert_eq!(
+            queue.status(&localization),
+            "2 utterances pending, speaking: One"
+        );
+        queue.interrupt("Four");
+        assert_eq!(queue.pending(), 1);
+        assert_eq!(
+            queue.status(&localization),
+            "1 utterance pending, speaking: One"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+use mapgen::TileType;
+
+use crate::map::Map;
+
+pub(crate) fn asset_world() -> World {
+    let mut app = App::build();
+    app.add_plugin(bevy::core::CorePlugin)
+        .add_plugin(bevy::asset::AssetPlugin);
+    std::mem::take(app.world_mut())
+}
+
+pub(crate) fn open_map(width: usize, height: usize) -> Map {
+    let mut base = mapgen::Map::new(width, height);
+    for tile in base.tiles.iter_mut() {
+        *tile = TileType::Floor;
+    }
+    Map::new(base)
+}
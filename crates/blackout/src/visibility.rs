@@ -1,4 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+};
 
 use bevy::prelude::*;
 use coord_2d::{Coord, Size};
@@ -6,9 +9,11 @@ use derive_more::{Deref, DerefMut};
 use shadowcast::{vision_distance, Context, InputGrid};
 
 use crate::{
-    core::{Coordinates, Player, PointLike},
+    core::{Angle, Coordinates, Player, PointLike},
+    localization::Localization,
     log::Log,
-    map::{ITileType, Map, MapConfig},
+    map::{Areas, ITileType, Map, MapConfig},
+    navigation::{RotationSpeed, Velocity},
 };
 
 #[derive(Clone, Copy, Debug, Default, Reflect)]
@@ -23,6 +28,9 @@ pub struct RevealedTiles(pub Vec<bool>);
 pub struct Viewshed {
     pub visible: HashSet<(i32, i32)>,
     pub range: u32,
+    pub fov: Option<(Angle, Angle)>,
+    // Last direction of travel, in radians, used to aim the cone of viewers that don't turn.
+    pub heading: f32,
 }
 
 impl Default for Viewshed {
@@ -30,15 +38,62 @@ impl Default for Viewshed {
         Self {
             range: 15,
             visible: HashSet::new(),
+            fov: None,
+            heading: 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LightConfig {
+    pub enabled: bool,
+    pub ambient: f32,
+    pub min_range: u32,
+}
+
+impl Default for LightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ambient: 1.,
+            min_range: 1,
         }
     }
 }
 
+pub fn light_limited_range(range: u32, light: f32, min_range: u32) -> u32 {
+    ((range as f32 * light.max(0.).min(1.)).round() as u32)
+        .max(min_range)
+        .min(range)
+}
+
+fn in_cone(start: (i32, i32), point: (i32, i32), facing: f32, half_angle: f32) -> bool {
+    if start == point {
+        return true;
+    }
+    let bearing = ((point.1 - start.1) as f32).atan2((point.0 - start.0) as f32);
+    let mut delta = bearing - facing;
+    while delta > PI {
+        delta -= 2. * PI;
+    }
+    while delta < -PI {
+        delta += 2. * PI;
+    }
+    delta.abs() <= half_angle
+}
+
 #[allow(dead_code)]
 impl Viewshed {
     pub fn is_visible(&self, point: &dyn PointLike) -> bool {
         self.visible.contains(&point.into())
     }
+
+    pub fn visible_entities<'a>(&'a self, map: &'a Map) -> impl Iterator<Item = Entity> + 'a {
+        self.visible.iter().flat_map(move |point| {
+            let index = point.to_index(map.width());
+            map.entities[index].iter().copied()
+        })
+    }
 }
 
 #[derive(Clone, Debug, Default, Deref, DerefMut, Reflect)]
@@ -165,15 +220,54 @@ impl InputGrid for VisibilityGrid {
 
 fn update_viewshed(
     mut viewers: Query<
-        (&mut Viewshed, &Coordinates),
-        Or<(Changed<VisibilityBlocked>, Changed<Coordinates>)>,
+        (
+            &mut Viewshed,
+            &Coordinates,
+            Option<&Transform>,
+            Option<&Velocity>,
+            Option<&RotationSpeed>,
+        ),
+        Or<(
+            Changed<VisibilityBlocked>,
+            Changed<Coordinates>,
+            Changed<Transform>,
+            Changed<Velocity>,
+        )>,
     >,
-    map: Query<(&Map, &VisibilityBlocked)>,
+    map: Query<(&Map, &VisibilityBlocked, Option<&Areas>)>,
+    light: Res<LightConfig>,
 ) {
-    for (mut viewshed, start) in viewers.iter_mut() {
-        for (map, visibility_blocked) in map.iter() {
+    for (mut viewshed, start, transform, velocity, rotation_speed) in viewers.iter_mut() {
+        if let Some(velocity) = velocity {
+            if velocity.x != 0. || velocity.y != 0. {
+                viewshed.heading = velocity.y.atan2(velocity.x);
+            }
+        }
+        let heading = viewshed.heading;
+        let cone = viewshed.fov.map(|(half_angle, offset)| {
+            // Viewers that turn face along their transform, others along their travel.
+            let facing = match (rotation_speed, transform) {
+                (Some(_), Some(transform)) => {
+                    let forward = transform.local_x();
+                    forward.y.atan2(forward.x)
+                }
+                _ => heading,
+            };
+            (facing + offset.radians(), half_angle.radians())
+        });
+        let origin = start.i32();
+        for (map, visibility_blocked, areas) in map.iter() {
             let mut context: Context<u8> = Context::default();
-            let vision_distance = vision_distance::Circle::new(viewshed.range);
+            let range = if light.enabled {
+                let area_light = areas
+                    .and_then(|areas| areas.iter().find(|area| area.contains(start)))
+                    .map(|area| area.light)
+                    .unwrap_or(1.);
+                light_limited_range(viewshed.range, light.ambient * area_light, light.min_range)
+            } else {
+                viewshed.range
+            };
+            let vision_distance = vision_distance::Circle::new(range);
             let coord = Coord::new(start.x_i32(), start.y_i32());
             viewshed.visible.clear();
             let visibility_grid = VisibilityGrid(map.clone(), visibility_blocked.clone());
@@ -184,6 +278,11 @@ fn update_viewshed(
                 vision_distance,
                 255,
                 |coord, _directions, _visibility| {
+                    if let Some((facing, half_angle)) = cone {
+                        if !in_cone(origin, (coord.x, coord.y), facing, half_angle) {
+                            return;
+                        }
+                    }
                     viewshed.visible.insert((coord.x, coord.y));
                 },
             );
@@ -219,6 +318,7 @@ fn map_visibility(
 
 fn log_visible(
     time: Res<Time>,
+    localization: Res<Localization>,
     mut seen: Local<HashSet<Entity>>,
     mut recently_lost: Local<HashMap<Entity, Timer>>,
     mut log: Query<&mut Log>,
@@ -226,6 +326,7 @@ fn log_visible(
     map: Query<&Map>,
     names: Query<&Name>,
     players: Query<&Player>,
+    coordinates_storage: Query<&Coordinates>,
 ) {
     for timer in recently_lost.values_mut() {
         timer.tick(time.delta());
@@ -239,23 +340,26 @@ fn log_visible(
     let mut new_seen = HashSet::new();
     if let Ok(mut log) = log.single_mut() {
         for (viewshed, coordinates, _) in viewers.iter() {
-            for viewed_coordinates in &viewshed.visible {
-                for map in map.iter() {
-                    let index = viewed_coordinates.to_index(map.width());
-                    for entity in &map.entities[index] {
-                        if recently_lost.contains_key(&entity) {
-                            continue;
-                        }
-                        if let Ok(name) = names.get(*entity) {
-                            if players.get(*entity).is_err() {
-                                if !seen.contains(&*entity) {
+            for map in map.iter() {
+                for entity in viewshed.visible_entities(map) {
+                    if recently_lost.contains_key(&entity) {
+                        continue;
+                    }
+                    if let Ok(name) = names.get(entity) {
+                        if players.get(entity).is_err() {
+                            if !seen.contains(&entity) {
+                                if let Ok(entity_coordinates) = coordinates_storage.get(entity) {
                                     let name = name.to_string();
-                                    let location =
-                                        coordinates.distance_and_direction(viewed_coordinates);
-                                    log.push(format!("{}: {}", name, location));
+                                    let location = localization.distance_and_direction(
+                                        coordinates,
+                                        &entity_coordinates.i32(),
+                                    );
+                                    log.push(
+                                        localization.format("visibility.seen", &[&name, &location]),
+                                    );
                                 }
-                                new_seen.insert(*entity);
                             }
+                            new_seen.insert(entity);
                         }
                     }
                 }
@@ -278,6 +382,9 @@ impl Plugin for VisibilityPlugin {
         const UPDATE_VISIBILITY_INDEX: &str = "UPDATE_VISIBILITY_INDEX";
         const UPDATE_VIEWSHED: &str = "UPDATE_VIEWSHED";
         const MAP_VISIBILITY: &str = "MAP_VISIBILITY";
+        if !app.world().contains_resource::<LightConfig>() {
+            app.insert_resource(LightConfig::default());
+        }
         app.insert_resource(PreviousIndex::default())
             .add_system(add_visibility_indices.system())
             .add_system_to_stage(
@@ -322,3 +429,72 @@ impl Plugin for VisibilityPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mapgen::geometry::Rect;
+
+    use super::*;
+    use crate::{core::Area, testing::open_map};
+
+    #[test]
+    fn entering_a_dark_area_shrinks_the_viewshed() {
+        let mut world = World::default();
+        world.insert_resource(LightConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let areas = Areas(vec![
+            Area {
+                rect: Rect::new(0, 0, 9, 9),
+                description: None,
+                light: 1.,
+            },
+            Area {
+                rect: Rect::new(10, 0, 9, 9),
+                description: None,
+                light: 0.25,
+            },
+        ]);
+        world
+            .spawn()
+            .insert(open_map(20, 10))
+            .insert(VisibilityBlocked(vec![false; 200]))
+            .insert(areas);
+        let viewer = world
+            .spawn()
+            .insert(Viewshed {
+                range: 8,
+                ..Default::default()
+            })
+            .insert(Coordinates((4., 4.)))
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(update_viewshed.system());
+        stage.run(&mut world);
+        let lit = world.get::<Viewshed>(viewer).unwrap().visible.len();
+        *world.get_mut::<Coordinates>(viewer).unwrap() = Coordinates((14., 4.));
+        stage.run(&mut world);
+        let dark = world.get::<Viewshed>(viewer).unwrap().visible.len();
+        assert!(dark < lit);
+    }
+
+    #[test]
+    fn visible_entities_only_yields_entities_on_visible_tiles() {
+        let mut world = World::default();
+        let seen = world.spawn().id();
+        let also_seen = world.spawn().id();
+        let hidden = world.spawn().id();
+        let mut map = open_map(5, 5);
+        map.entities[(1, 1).to_index(5)].insert(seen);
+        map.entities[(1, 1).to_index(5)].insert(also_seen);
+        map.entities[(4, 4).to_index(5)].insert(hidden);
+        let viewshed = Viewshed {
+            visible: [(0, 0), (1, 1)].iter().copied().collect(),
+            ..Default::default()
+        };
+        let mut entities = viewshed.visible_entities(&map).collect::<Vec<Entity>>();
+        entities.sort();
+        assert_eq!(entities, vec![seen, also_seen]);
+    }
+}
@@ -3,15 +3,54 @@ use std::time::Instant;
 use bevy::prelude::*;
 
 use blackout::{
+    bevy_input_actionmap::InputMap,
     bevy_openal::{Buffer, Sound, SoundState},
+    core::{Coordinates, Player},
     derive_more::{Deref, DerefMut},
+    localization::Localization,
+    log::Log,
     map::Map,
+    sound::{spawn_sound_at, SoundParams},
+    speech::SpeechQueue,
 };
 
-use crate::game::{Reset, Sfx};
+use crate::game::{Reset, Sfx, TOGGLE_KILL_STREAKS};
 
 pub struct AwardBonus;
 
+#[derive(Clone, Copy, Debug)]
+pub struct KillStreakConfig {
+    pub enabled: bool,
+    pub window: f32,
+}
+
+impl Default for KillStreakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 3.,
+        }
+    }
+}
+
+pub fn streak_callout(kills: usize) -> Option<&'static str> {
+    match kills {
+        0 | 1 => None,
+        2 => Some("streak.double"),
+        3 => Some("streak.triple"),
+        4 => Some("streak.quad"),
+        _ => Some("streak.rampage"),
+    }
+}
+
+const BONUS_NOTES: [f32; 7] = [0., 2., 4., 5., 7., 9., 11.];
+
+// Walks up the major scale with each consecutive bonus, returning to the root after the seventh.
+pub fn bonus_pitch(streak: usize) -> f32 {
+    let index = streak.saturating_sub(1) % BONUS_NOTES.len();
+    2_f32.powf(BONUS_NOTES[index] / 12.)
+}
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct BonusTimes(pub Vec<Instant>);
 
@@ -26,36 +65,58 @@ fn bonus(
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
     level: Query<(&Map, Entity)>,
+    player: Query<&Coordinates, With<Player>>,
+    config: Res<KillStreakConfig>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
 ) {
     for _ in events.iter() {
-        if let Ok((_, map_entity)) = level.single() {
+        if let (Ok((_, map_entity)), Ok(coordinates)) = (level.single(), player.single()) {
             if let Ok(mut bonus_times) = bonus_times.single_mut() {
                 bonus_times.push(Instant::now());
-                let buffer = buffers.get_handle(sfx.bonus);
-                let recent_bonuses = bonus_times.len() % 7;
-                let notes = vec![0., 2., 4., 5., 7., 9., 11.];
-                let bonus_index = if recent_bonuses == 0 {
-                    0
-                } else {
-                    recent_bonuses - 1
-                };
-                let pitch = 1. + notes[bonus_index] / 12.;
-                let sound_id = commands
-                    .spawn()
-                    .insert(Sound {
-                        buffer,
-                        state: SoundState::Playing,
+                spawn_sound_at(
+                    &mut commands,
+                    map_entity,
+                    buffers.get_handle(sfx.bonus),
+                    coordinates,
+                    SoundParams {
                         gain: 3.,
-                        pitch,
+                        pitch: bonus_pitch(bonus_times.len()),
                         ..Default::default()
-                    })
-                    .id();
-                commands.entity(map_entity).push_children(&[sound_id]);
+                    },
+                );
+                if config.enabled {
+                    let streak = bonus_times
+                        .iter()
+                        .filter(|v| v.elapsed().as_secs_f32() <= config.window)
+                        .count();
+                    if let (Some(callout), Ok(mut log)) = (streak_callout(streak), log.single_mut())
+                    {
+                        log.push(localization.get(callout));
+                    }
+                }
             }
         }
     }
 }
 
+fn toggle_kill_streaks(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<KillStreakConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if input.just_active(TOGGLE_KILL_STREAKS) {
+        config.enabled = !config.enabled;
+        let message = if config.enabled {
+            "streak.on"
+        } else {
+            "streak.off"
+        };
+        speech.interrupt(localization.get(message));
+    }
+}
+
 fn bonus_clear(
     mut commands: Commands,
     mut bonus_times: Query<&mut BonusTimes>,
@@ -95,8 +156,73 @@ pub struct BonusPlugin;
 impl Plugin for BonusPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_event::<AwardBonus>()
+            .init_resource::<KillStreakConfig>()
             .add_startup_system(setup.system())
             .add_system(bonus.system())
+            .add_system(toggle_kill_streaks.system())
             .add_system(bonus_clear.system());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+
+    use super::*;
+    use crate::{
+        game::ENGLISH,
+        testing::{open_map, spawn_level, test_world},
+    };
+
+    #[test]
+    fn two_kills_within_the_window_call_a_double_kill() {
+        let mut world = test_world();
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        world.insert_resource(localization);
+        world.insert_resource(Sfx::default());
+        world.insert_resource(KillStreakConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        world.insert_resource(Events::<AwardBonus>::default());
+        spawn_level(&mut world, open_map(5, 5));
+        world.spawn().insert(Player).insert(Coordinates((2., 2.)));
+        world.spawn().insert(BonusTimes::default());
+        let log = world.spawn().insert(Log::default()).id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(bonus.system());
+        for _ in 0..2 {
+            world
+                .get_resource_mut::<Events<AwardBonus>>()
+                .unwrap()
+                .send(AwardBonus);
+            stage.run(&mut world);
+        }
+        let messages: Vec<String> = world
+            .get::<Log>(log)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.message.clone())
+            .collect();
+        assert_eq!(messages, vec!["Double kill!".to_string()]);
+    }
+
+    #[test]
+    fn bonus_pitch_cycles_through_streaks() {
+        let semitones = [0., 2., 4., 5., 7., 9., 11., 0., 2., 4., 5., 7., 9., 11.];
+        for (streak, semitone) in (1..=14).zip(semitones.iter()) {
+            assert_eq!(
+                bonus_pitch(streak),
+                2_f32.powf(semitone / 12.),
+                "streak {}",
+                streak
+            );
+        }
+    }
+
+    #[test]
+    fn bonus_pitch_handles_empty_streak() {
+        assert_eq!(bonus_pitch(0), 1.);
+    }
+}
@@ -3,25 +3,125 @@ use std::collections::HashMap;
 use bevy::{ecs::system::EntityCommands, prelude::*};
 use blackout::{
     bevy_openal::{Buffer, Sound, SoundState},
-    core::{Coordinates, Player, PointLike},
+    core::{Coordinates, Player, PointLike, SpatialGrid},
     derive_more::{Deref, DerefMut},
-    log::Log,
+    localization::Localization,
+    log::{Log, LogPriority},
     map::Map,
     mapgen::TileType,
-    navigation::Velocity,
+    navigation::{MaxSpeed, MotionBlocked, Speed, Velocity},
+    pathfinding::Destination,
     rand::prelude::*,
+    sound::{spawn_sound_at, walls_between, OcclusionConfig, SoundParams},
 };
 
 use crate::{
     bonus::AwardBonus,
     game::{AppState, Sfx, Sprites},
-    player::LifeLost,
-    robot::{CauseOfDeath, Robot, RobotKilled},
+    player::{DamageSource, LifeLost},
+    robot::{CauseOfDeath, Dodging, Health, Robot, RobotDamaged, RobotKilled},
 };
 
 #[derive(Clone, Copy, Debug)]
 pub struct Bullet(pub Entity);
 
+#[derive(Clone, Copy, Debug)]
+pub struct BulletConfig {
+    pub self_damage_after_ricochet: bool,
+    pub near_miss_cue: bool,
+    pub near_miss_radius: f32,
+    pub player_ricochets: u8,
+    pub robots_dodge: bool,
+    pub dodge_radius: f32,
+    pub dodge_distance: f32,
+    pub dodge_duration: f32,
+    // Tougher robots take several hits. Off until hits have their own sounds.
+    pub robot_health: bool,
+}
+
+impl Default for BulletConfig {
+    fn default() -> Self {
+        Self {
+            self_damage_after_ricochet: false,
+            near_miss_cue: false,
+            near_miss_radius: 2.5,
+            player_ricochets: 0,
+            robots_dodge: false,
+            dodge_radius: 2.,
+            dodge_distance: 2.,
+            dodge_duration: 1.,
+            robot_health: false,
+        }
+    }
+}
+
+const HIT_RADIUS: f32 = 1.;
+const ROBOT_HIT_RADIUS: f32 = 0.75;
+
+// Remaining wall bounces before the bullet is destroyed.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct Ricochet(pub u8);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ricocheted;
+
+// Reflect off the wall by flipping whichever axis carried the bullet from its last open tile
+// into the wall, or both when it came in through a corner.
+fn reflect(velocity: Vec2, from: (f32, f32), to: (f32, f32), map: &Map) -> Vec2 {
+    let from = (from.0 as usize, from.1 as usize);
+    let to = (to.0 as usize, to.1 as usize);
+    let x_blocked = map.base.at(to.0, from.1) == TileType::Wall;
+    let y_blocked = map.base.at(from.0, to.1) == TileType::Wall;
+    match (x_blocked, y_blocked) {
+        (true, false) => Vec2::new(-velocity.x, velocity.y),
+        (false, true) => Vec2::new(velocity.x, -velocity.y),
+        _ => -velocity,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct NearMiss {
+    closest: f32,
+    cued: bool,
+}
+
+// Sidestep perpendicular to the bullet's path, away from it if possible.
+pub fn dodge_destination(
+    bullet: &Coordinates,
+    velocity: &Velocity,
+    robot: &Coordinates,
+    distance: f32,
+    map: &Map,
+    motion_blocked: &MotionBlocked,
+) -> Option<(i32, i32)> {
+    let speed = velocity.length();
+    if speed == 0. {
+        return None;
+    }
+    let perpendicular = Vec2::new(-velocity.y, velocity.x) / speed;
+    let offset = Vec2::new(robot.x() - bullet.x(), robot.y() - bullet.y());
+    let side = if perpendicular.dot(offset) < 0. {
+        -1.
+    } else {
+        1.
+    };
+    for side in [side, -side].iter() {
+        let target = Vec2::new(robot.x(), robot.y()) + perpendicular * *side * distance;
+        let target = (target.x.round() as i32, target.y.round() as i32);
+        if target.0 < 0
+            || target.1 < 0
+            || target.0 as usize >= map.width()
+            || target.1 as usize >= map.height()
+        {
+            continue;
+        }
+        if !motion_blocked[target.to_index(map.width())] {
+            return Some(target);
+        }
+    }
+    None
+}
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct ShotTimer(pub Timer);
 
@@ -110,17 +210,53 @@ fn bullet(
     mut commands: Commands,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
-    mut bullets: Query<(&Bullet, Entity, &Coordinates, &ShotRange, &mut Sound)>,
+    mut bullets: Query<(
+        &Bullet,
+        Entity,
+        &mut Coordinates,
+        &ShotRange,
+        &mut Velocity,
+        &mut Sound,
+        Option<&mut Ricochet>,
+        Option<&Ricocheted>,
+        Option<&mut NearMiss>,
+    )>,
     mut active_bullets: Local<HashMap<Entity, ((f32, f32), f32)>>,
-    robots: Query<(&Robot, Entity, &Coordinates)>,
-    level: Query<(Entity, &Map)>,
+    mut robots: Query<(
+        &Robot,
+        Entity,
+        &Coordinates,
+        &mut Health,
+        &MaxSpeed,
+        Option<&Dodging>,
+    )>,
+    level: Query<(Entity, &Map, &MotionBlocked)>,
     mut robot_killed: EventWriter<RobotKilled>,
+    mut robot_damaged: EventWriter<RobotDamaged>,
     mut bonus: EventWriter<AwardBonus>,
     player: Query<(&Player, Entity, &Coordinates)>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     mut life_lost: EventWriter<LifeLost>,
+    config: Res<BulletConfig>,
+    occlusion: Res<OcclusionConfig>,
 ) {
-    for (bullet, entity, coordinates, range, mut sound) in bullets.iter_mut() {
+    let mut grid = SpatialGrid::default();
+    for (_, entity, coordinates, _, _, _) in robots.iter_mut() {
+        grid.insert(entity, coordinates);
+    }
+    for (
+        bullet,
+        entity,
+        mut coordinates,
+        range,
+        mut velocity,
+        mut sound,
+        ricochet,
+        ricocheted,
+        near_miss,
+    ) in bullets.iter_mut()
+    {
         if !active_bullets.contains_key(&entity) {
             active_bullets.insert(entity, ((coordinates.x(), coordinates.y()), 0.));
         }
@@ -128,25 +264,32 @@ fn bullet(
             sound.play();
         }
         let mut remove = false;
-        if let Ok((map_entity, map)) = level.single() {
+        if let Ok((map_entity, map, _)) = level.single() {
             if map.base.at(coordinates.x_usize(), coordinates.y_usize()) == TileType::Wall {
-                let transform =
-                    Transform::from_translation(Vec3::new(coordinates.x(), coordinates.y(), 0.));
-                let zap = commands
-                    .spawn()
-                    .insert(transform)
-                    .insert(Sound {
-                        buffer: buffers.get_handle(sfx.bullet_wall),
-                        state: SoundState::Playing,
+                spawn_sound_at(
+                    &mut commands,
+                    map_entity,
+                    buffers.get_handle(sfx.bullet_wall),
+                    &*coordinates,
+                    SoundParams {
                         gain: 0.8,
-                        pitch: (0.9 + random::<f32>() * 0.2),
+                        pitch: 0.9 + random::<f32>() * 0.2,
                         ..Default::default()
-                    })
-                    .id();
-                commands.entity(map_entity).push_children(&[zap]);
-                remove = true;
+                    },
+                );
+                match (ricochet, active_bullets.get(&entity)) {
+                    (Some(mut ricochet), Some((prev_coords, _))) if **ricochet > 0 => {
+                        **ricochet -= 1;
+                        **velocity = reflect(**velocity, *prev_coords, **coordinates, map);
+                        **coordinates = *prev_coords;
+                        commands.entity(entity).insert(Ricocheted);
+                    }
+                    _ => remove = true,
+                }
             }
         }
+        let coordinates = &*coordinates;
+        let velocity = &*velocity;
         if let Some((prev_coords, total_distance)) = active_bullets.get_mut(&entity) {
             *total_distance += prev_coords.distance(coordinates);
             if total_distance >= &mut (**range as f32) {
@@ -160,9 +303,51 @@ fn bullet(
             *prev_coords = (coordinates.x(), coordinates.y());
         }
         let Bullet(owner) = bullet;
-        for (Robot(robot_type), entity, robot_coordinates) in robots.iter() {
-            if *owner != entity && coordinates.distance(robot_coordinates) <= 0.75 {
-                if let Ok((_, map)) = level.single() {
+        let fired_by_player = player.single().map_or(false, |(_, e, _)| e == *owner);
+        let search_radius = if config.robots_dodge {
+            config.dodge_radius.max(ROBOT_HIT_RADIUS)
+        } else {
+            ROBOT_HIT_RADIUS
+        };
+        for entity in grid.near(coordinates, search_radius) {
+            let (Robot(robot_type), entity, robot_coordinates, mut health, max_speed, dodging) =
+                match robots.get_mut(entity) {
+                    Ok(robot) => robot,
+                    Err(_) => continue,
+                };
+            let distance = coordinates.distance(robot_coordinates);
+            if config.robots_dodge
+                && fired_by_player
+                && dodging.is_none()
+                && distance > ROBOT_HIT_RADIUS
+                && distance <= config.dodge_radius
+            {
+                if let Ok((_, map, motion_blocked)) = level.single() {
+                    if let Some(target) = dodge_destination(
+                        coordinates,
+                        velocity,
+                        robot_coordinates,
+                        config.dodge_distance,
+                        map,
+                        motion_blocked,
+                    ) {
+                        commands
+                            .entity(entity)
+                            .insert(Destination(target))
+                            .insert(Speed(**max_speed))
+                            .insert(Dodging(Timer::from_seconds(config.dodge_duration, false)));
+                    }
+                }
+            }
+            if *owner != entity && distance <= ROBOT_HIT_RADIUS {
+                **health = if config.robot_health {
+                    health.saturating_sub(1)
+                } else {
+                    0
+                };
+                if **health > 0 {
+                    robot_damaged.send(RobotDamaged(entity, *velocity));
+                } else if let Ok((_, map, _)) = level.single() {
                     let index = robot_coordinates.to_index(map.width());
                     robot_killed.send(RobotKilled(
                         entity,
@@ -177,13 +362,59 @@ fn bullet(
                 break;
             }
         }
-        if let Ok((_, entity, player_coordinates)) = player.single() {
-            if *owner != entity && coordinates.distance(player_coordinates) <= 1. {
+        if let Ok((_, player_entity, player_coordinates)) = player.single() {
+            if *owner != player_entity {
+                if let Ok((_, map, _)) = level.single() {
+                    let walls = walls_between(coordinates, player_coordinates, map);
+                    let (gain, _) = occlusion.attenuation(walls);
+                    sound.gain = gain;
+                }
+            }
+            let can_hit = *owner != player_entity
+                || (ricocheted.is_some() && config.self_damage_after_ricochet);
+            let distance = coordinates.distance(player_coordinates);
+            if can_hit && distance <= HIT_RADIUS {
                 if let Ok(mut log) = log.single_mut() {
-                    log.push("Ouch!");
+                    log.push_with_priority(localization.get("bullet.ouch"), LogPriority::Urgent);
                     life_lost.send(LifeLost);
                 }
+                // The shot came from wherever the bullet was travelling from.
+                let speed = velocity.length();
+                if speed > 0. {
+                    let source = (
+                        player_coordinates.x() - velocity.x / speed,
+                        player_coordinates.y() - velocity.y / speed,
+                    );
+                    commands
+                        .entity(player_entity)
+                        .insert(DamageSource(source.into()));
+                }
                 remove = true;
+            } else if config.near_miss_cue && *owner != player_entity {
+                // Only whiz once the bullet starts moving away, so direct hits stay silent.
+                if let Some(mut near_miss) = near_miss {
+                    if !near_miss.cued && distance > near_miss.closest {
+                        if let Ok((map_entity, _, _)) = level.single() {
+                            spawn_sound_at(
+                                &mut commands,
+                                map_entity,
+                                buffers.get_handle(sfx.bullet_whiz),
+                                coordinates,
+                                SoundParams {
+                                    pitch: 1.5,
+                                    ..Default::default()
+                                },
+                            );
+                        }
+                        near_miss.cued = true;
+                    }
+                    near_miss.closest = near_miss.closest.min(distance);
+                } else if distance <= config.near_miss_radius {
+                    commands.entity(entity).insert(NearMiss {
+                        closest: distance,
+                        cued: false,
+                    });
+                }
             }
         }
         if remove {
@@ -197,7 +428,123 @@ pub struct BulletPlugin;
 
 impl Plugin for BulletPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(post_process_bullet.system())
+        app.init_resource::<BulletConfig>()
+            .add_system(post_process_bullet.system())
             .add_system_set(SystemSet::on_update(AppState::InGame).with_system(bullet.system()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+
+    use super::*;
+    use crate::{
+        robot::RobotType,
+        testing::{open_map, sent, spawn_level, test_world},
+    };
+
+    fn level_world(config: BulletConfig) -> (World, Entity) {
+        let mut world = test_world();
+        world.insert_resource(config);
+        world.insert_resource(OcclusionConfig::default());
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Localization::default());
+        world.insert_resource(Events::<RobotKilled>::default());
+        world.insert_resource(Events::<RobotDamaged>::default());
+        world.insert_resource(Events::<AwardBonus>::default());
+        world.insert_resource(Events::<LifeLost>::default());
+        spawn_level(&mut world, open_map(10, 10));
+        world.spawn().insert(Log::default());
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((5.5, 5.5)))
+            .id();
+        (world, player)
+    }
+
+    fn spawn_bullet(world: &mut World, owner: Entity, at: (f32, f32), velocity: Vec2) -> Entity {
+        world
+            .spawn()
+            .insert(Bullet(owner))
+            .insert(Coordinates(at))
+            .insert(ShotRange(16))
+            .insert(Velocity(velocity))
+            .insert(Sound::default())
+            .id()
+    }
+
+    fn bullet_stage() -> SystemStage {
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(bullet.system());
+        stage
+    }
+
+    fn lives_lost(world: &World) -> usize {
+        sent::<LifeLost>(world).len()
+    }
+
+    fn ricochet_hits_player(self_damage_after_ricochet: bool) -> usize {
+        let (mut world, player) = level_world(BulletConfig {
+            self_damage_after_ricochet,
+            ..Default::default()
+        });
+        let bullet = spawn_bullet(&mut world, player, (5.5, 5.5), Vec2::new(-8., 0.));
+        world.entity_mut(bullet).insert(Ricocheted);
+        bullet_stage().run(&mut world);
+        lives_lost(&world)
+    }
+
+    #[test]
+    fn ricocheted_player_bullet_only_hurts_with_self_damage() {
+        assert_eq!(ricochet_hits_player(true), 1);
+        assert_eq!(ricochet_hits_player(false), 0);
+    }
+
+    #[test]
+    fn near_miss_whizzes_without_costing_a_life() {
+        let (mut world, _) = level_world(BulletConfig {
+            near_miss_cue: true,
+            ..Default::default()
+        });
+        let shooter = world.spawn().id();
+        let bullet = spawn_bullet(&mut world, shooter, (7.5, 5.5), Vec2::new(8., 0.));
+        let mut stage = bullet_stage();
+        stage.run(&mut world);
+        *world.get_mut::<Coordinates>(bullet).unwrap() = Coordinates((7.9, 5.5));
+        stage.run(&mut world);
+        let whiz = world
+            .get_resource::<Assets<Buffer>>()
+            .unwrap()
+            .get_handle(world.get_resource::<Sfx>().unwrap().bullet_whiz);
+        let whizzes = world
+            .query::<(&Sound, &Parent)>()
+            .iter(&world)
+            .filter(|(sound, _)| sound.buffer == whiz)
+            .count();
+        assert_eq!(whizzes, 1);
+        assert_eq!(lives_lost(&world), 0);
+    }
+
+    #[test]
+    fn near_miss_sends_robot_perpendicular_to_the_bullet() {
+        let (mut world, player) = level_world(BulletConfig {
+            robots_dodge: true,
+            ..Default::default()
+        });
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((3., 3.5)))
+            .insert(Health(1))
+            .insert(MaxSpeed(2.))
+            .id();
+        spawn_bullet(&mut world, player, (3., 2.), Vec2::new(8., 0.));
+        bullet_stage().run(&mut world);
+        let Destination(target) = *world.get::<Destination>(robot).unwrap();
+        assert_eq!(target, (3, 6));
+        assert!(world.get::<Dodging>(robot).is_some());
+        assert_eq!(**world.get::<Health>(robot).unwrap(), 1);
+    }
+}
@@ -0,0 +1,321 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use big_brain::prelude::Actor;
+use blackout::{
+    bevy_input_actionmap::InputMap,
+    bevy_openal::{Buffer, Sound, SoundState},
+    core::{Coordinates, Player, PointLike},
+    localization::Localization,
+    log::Log,
+    speech::SpeechQueue,
+};
+
+use crate::{
+    game::{AppState, Sfx, TOGGLE_DANGER_METER, TOGGLE_PURSUIT_TREND},
+    robot::{PursuePlayer, Robot},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct DangerMeterConfig {
+    pub enabled: bool,
+    pub range: f32,
+}
+
+impl Default for DangerMeterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            range: 24.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct DangerMeter;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PursuitTrendConfig {
+    pub enabled: bool,
+    pub interval: f32,
+    pub threshold: f32,
+}
+
+impl Default for PursuitTrendConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 3.,
+            threshold: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PursuitTrend {
+    Approaching,
+    Retreating,
+}
+
+pub fn pursuit_trend(change: f32, threshold: f32) -> Option<PursuitTrend> {
+    if change <= -threshold {
+        Some(PursuitTrend::Approaching)
+    } else if change >= threshold {
+        Some(PursuitTrend::Retreating)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct PursuitTrendState {
+    nearest: Option<(Entity, f32)>,
+    change: f32,
+    timer: Timer,
+}
+
+impl PursuitTrendState {
+    fn update(
+        &mut self,
+        nearest: Option<(Entity, f32)>,
+        delta: Duration,
+        config: &PursuitTrendConfig,
+    ) -> Option<PursuitTrend> {
+        match (self.nearest, nearest) {
+            (Some((previous_entity, previous)), Some((entity, distance)))
+                if previous_entity == entity =>
+            {
+                self.change += distance - previous;
+            }
+            _ => {
+                self.change = 0.;
+                self.timer = Timer::from_seconds(config.interval, false);
+            }
+        }
+        self.nearest = nearest;
+        let mut trend = None;
+        if nearest.is_some() {
+            self.timer.tick(delta);
+            if self.timer.finished() {
+                trend = pursuit_trend(self.change, config.threshold);
+                self.change = 0.;
+                self.timer.reset();
+            }
+        }
+        trend
+    }
+}
+
+fn danger_pitch(distance: f32, range: f32) -> f32 {
+    let closeness = (1. - distance / range).max(0.).min(1.);
+    0.5 + closeness * 1.5
+}
+
+fn toggle_danger_meter(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<DangerMeterConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if input.just_active(TOGGLE_DANGER_METER) {
+        config.enabled = !config.enabled;
+        let message = if config.enabled {
+            "danger_meter.on"
+        } else {
+            "danger_meter.off"
+        };
+        speech.interrupt(localization.get(message));
+    }
+}
+
+fn danger_meter(
+    mut commands: Commands,
+    config: Res<DangerMeterConfig>,
+    buffers: Res<Assets<Buffer>>,
+    sfx: Res<Sfx>,
+    player: Query<(Entity, &Player, &Coordinates)>,
+    pursuers: Query<&Actor, With<PursuePlayer>>,
+    robots: Query<&Coordinates, With<Robot>>,
+    mut meter: Query<(Entity, &DangerMeter, &mut Sound)>,
+) {
+    if let Ok((player_entity, _, player_coordinates)) = player.single() {
+        let mut nearest: Option<f32> = None;
+        if config.enabled {
+            for Actor(actor) in pursuers.iter() {
+                if let Ok(robot_coordinates) = robots.get(*actor) {
+                    let distance = robot_coordinates.distance(player_coordinates);
+                    if distance <= config.range && nearest.map_or(true, |v| distance < v) {
+                        nearest = Some(distance);
+                    }
+                }
+            }
+        }
+        match (nearest, meter.single_mut()) {
+            (Some(distance), Ok((_, _, mut sound))) => {
+                sound.pitch = danger_pitch(distance, config.range);
+            }
+            (Some(distance), Err(_)) => {
+                let meter = commands
+                    .spawn()
+                    .insert(DangerMeter)
+                    .insert(Sound {
+                        buffer: buffers.get_handle(sfx.shockwave),
+                        state: SoundState::Playing,
+                        looping: true,
+                        gain: 0.3,
+                        pitch: danger_pitch(distance, config.range),
+                        ..Default::default()
+                    })
+                    .insert(Transform::default())
+                    .insert(GlobalTransform::default())
+                    .id();
+                commands.entity(player_entity).push_children(&[meter]);
+            }
+            (None, Ok((entity, _, _))) => {
+                commands.entity(entity).despawn_recursive();
+            }
+            (None, Err(_)) => {}
+        }
+    }
+}
+
+fn toggle_pursuit_trend(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<PursuitTrendConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if input.just_active(TOGGLE_PURSUIT_TREND) {
+        config.enabled = !config.enabled;
+        let message = if config.enabled {
+            "pursuit_trend.on"
+        } else {
+            "pursuit_trend.off"
+        };
+        speech.interrupt(localization.get(message));
+    }
+}
+
+fn speak_pursuit_trend(
+    time: Res<Time>,
+    config: Res<PursuitTrendConfig>,
+    player: Query<(&Player, &Coordinates)>,
+    pursuers: Query<&Actor, With<PursuePlayer>>,
+    robots: Query<&Coordinates, With<Robot>>,
+    mut state: Local<PursuitTrendState>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+) {
+    if !config.enabled {
+        *state = Default::default();
+        return;
+    }
+    if let Ok((_, player_coordinates)) = player.single() {
+        let mut nearest: Option<(Entity, f32)> = None;
+        for Actor(actor) in pursuers.iter() {
+            if let Ok(robot_coordinates) = robots.get(*actor) {
+                let distance = robot_coordinates.distance(player_coordinates);
+                if nearest.map_or(true, |(_, v)| distance < v) {
+                    nearest = Some((*actor, distance));
+                }
+            }
+        }
+        if let Some(trend) = state.update(nearest, time.delta(), &config) {
+            let message = match trend {
+                PursuitTrend::Approaching => "pursuit_trend.approaching",
+                PursuitTrend::Retreating => "pursuit_trend.retreating",
+            };
+            if let Ok(mut log) = log.single_mut() {
+                log.push(localization.get(message));
+            }
+        }
+    }
+}
+
+pub struct DangerPlugin;
+
+impl Plugin for DangerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DangerMeterConfig>()
+            .init_resource::<PursuitTrendConfig>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(toggle_danger_meter.system())
+                    .with_system(danger_meter.system())
+                    .with_system(toggle_pursuit_trend.system())
+                    .with_system(speak_pursuit_trend.system()),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{robot::RobotType, testing::test_world};
+
+    fn trend_over(distances: &[f32]) -> Option<PursuitTrend> {
+        let config = PursuitTrendConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let robot = World::default().spawn().id();
+        let mut state = PursuitTrendState::default();
+        let mut trend = None;
+        for distance in distances {
+            trend = state.update(Some((robot, *distance)), Duration::from_secs(1), &config);
+        }
+        trend
+    }
+
+    #[test]
+    fn robot_moving_toward_player_is_approaching() {
+        assert_eq!(trend_over(&[10., 9., 8.]), Some(PursuitTrend::Approaching));
+    }
+
+    #[test]
+    fn robot_moving_away_from_player_is_retreating() {
+        assert_eq!(trend_over(&[8., 9., 10.]), Some(PursuitTrend::Retreating));
+    }
+
+    #[test]
+    fn meter_pitch_follows_the_nearest_pursuer() {
+        let mut world = test_world();
+        world.insert_resource(DangerMeterConfig {
+            enabled: true,
+            range: 20.,
+        });
+        world.insert_resource(Sfx::default());
+        world.spawn().insert(Player).insert(Coordinates((0., 0.)));
+        let pursuer = |world: &mut World, x: f32| {
+            let robot = world
+                .spawn()
+                .insert(Robot(RobotType::Dumbass))
+                .insert(Coordinates((x, 0.)))
+                .id();
+            world.spawn().insert(Actor(robot)).insert(PursuePlayer);
+            robot
+        };
+        let nearest = pursuer(&mut world, 10.);
+        pursuer(&mut world, 15.);
+        world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((2., 0.)));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(danger_meter.system());
+        let pitch = |world: &mut World| {
+            world
+                .query_filtered::<&Sound, With<DangerMeter>>()
+                .iter(world)
+                .map(|sound| sound.pitch)
+                .collect::<Vec<f32>>()
+        };
+        stage.run(&mut world);
+        assert_eq!(pitch(&mut world), vec![danger_pitch(10., 20.)]);
+        *world.get_mut::<Coordinates>(nearest).unwrap() = Coordinates((5., 0.));
+        stage.run(&mut world);
+        assert_eq!(pitch(&mut world), vec![danger_pitch(5., 20.)]);
+        assert!(danger_pitch(5., 20.) > danger_pitch(10., 20.));
+        assert_eq!(danger_pitch(25., 20.), 0.5);
+    }
+}
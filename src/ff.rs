@@ -4,7 +4,46 @@ use blackout::gilrs::{
     GamepadId, Gilrs,
 };
 
-use crate::player::Shoot;
+use crate::{
+    level::WallCollision,
+    player::{DamageIndicated, KillConfirmed, LifeLost, Shoot},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct FfEffect {
+    pub enabled: bool,
+    pub magnitude: u16,
+    pub duration: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FfConfig {
+    pub shoot: FfEffect,
+    pub life_lost: FfEffect,
+    pub wall_collision: FfEffect,
+}
+
+impl Default for FfConfig {
+    fn default() -> Self {
+        Self {
+            shoot: FfEffect {
+                enabled: true,
+                magnitude: 60_000,
+                duration: 50,
+            },
+            life_lost: FfEffect {
+                enabled: true,
+                magnitude: 65_535,
+                duration: 150,
+            },
+            wall_collision: FfEffect {
+                enabled: true,
+                magnitude: 25_000,
+                duration: 400,
+            },
+        }
+    }
+}
 
 fn setup(mut commands: Commands, gilrs: NonSend<Gilrs>) {
     let mut support_ff = Vec::new();
@@ -17,27 +56,97 @@ fn setup(mut commands: Commands, gilrs: NonSend<Gilrs>) {
     commands.insert_resource(support_ff);
 }
 
+// Each call builds its own `Effect`, so events landing on the same frame layer rather than cancel.
+fn play_effect(gilrs: &mut Gilrs, gamepads: &[GamepadId], kind: BaseEffectType, duration: u32) {
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind,
+            scheduling: Replay {
+                play_for: Ticks::from_ms(duration),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .gamepads(gamepads)
+        .finish(gilrs)
+        .unwrap();
+    effect.play().unwrap();
+}
+
 fn generate_ff(world: &mut World) {
     let world = world.cell();
     let mut gilrs = world.get_non_send_mut::<Gilrs>().unwrap();
     let support_ff = world.get_resource::<Vec<GamepadId>>().unwrap();
+    let config = *world.get_resource::<FfConfig>().unwrap();
     if !support_ff.is_empty() {
         if let Some(events) = world.get_resource::<Events<Shoot>>() {
             let mut reader = events.get_reader();
             for _ in reader.iter(&events) {
-                let effect = EffectBuilder::new()
-                    .add_effect(BaseEffect {
-                        kind: BaseEffectType::Strong { magnitude: 60_000 },
-                        scheduling: Replay {
-                            play_for: Ticks::from_ms(50),
-                            ..Default::default()
+                if config.shoot.enabled {
+                    play_effect(
+                        &mut gilrs,
+                        &*support_ff,
+                        BaseEffectType::Strong {
+                            magnitude: config.shoot.magnitude,
+                        },
+                        config.shoot.duration,
+                    );
+                }
+            }
+        }
+        if let Some(events) = world.get_resource::<Events<KillConfirmed>>() {
+            let mut reader = events.get_reader();
+            for KillConfirmed(strength) in reader.iter(&events) {
+                play_effect(
+                    &mut gilrs,
+                    &*support_ff,
+                    BaseEffectType::Weak {
+                        magnitude: (20_000. + strength * 45_000.) as u16,
+                    },
+                    (100. + strength * 150.) as u32,
+                );
+            }
+        }
+        if let Some(events) = world.get_resource::<Events<LifeLost>>() {
+            let mut reader = events.get_reader();
+            for _ in reader.iter(&events) {
+                if config.life_lost.enabled {
+                    play_effect(
+                        &mut gilrs,
+                        &*support_ff,
+                        BaseEffectType::Strong {
+                            magnitude: config.life_lost.magnitude,
+                        },
+                        config.life_lost.duration,
+                    );
+                }
+            }
+        }
+        if let Some(events) = world.get_resource::<Events<DamageIndicated>>() {
+            let mut reader = events.get_reader();
+            for DamageIndicated(bearing) in reader.iter(&events) {
+                // Most pads put the heavy motor on the left and the light one on the right.
+                let kind = if bearing.sin() < 0. {
+                    BaseEffectType::Weak { magnitude: 50_000 }
+                } else {
+                    BaseEffectType::Strong { magnitude: 50_000 }
+                };
+                play_effect(&mut gilrs, &*support_ff, kind, 200);
+            }
+        }
+        if let Some(events) = world.get_resource::<Events<WallCollision>>() {
+            let mut reader = events.get_reader();
+            for _ in reader.iter(&events) {
+                if config.wall_collision.enabled {
+                    play_effect(
+                        &mut gilrs,
+                        &*support_ff,
+                        BaseEffectType::Weak {
+                            magnitude: config.wall_collision.magnitude,
                         },
-                        ..Default::default()
-                    })
-                    .gamepads(&*support_ff)
-                    .finish(&mut gilrs)
-                    .unwrap();
-                effect.play().unwrap();
+                        config.wall_collision.duration,
+                    );
+                }
             }
         }
     }
@@ -47,7 +156,8 @@ pub struct ForceFeedbackPlugin;
 
 impl Plugin for ForceFeedbackPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_startup_system(setup.system())
+        app.init_resource::<FfConfig>()
+            .add_startup_system(setup.system())
             .add_system_to_stage(CoreStage::PostUpdate, generate_ff.exclusive_system());
     }
 }
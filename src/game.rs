@@ -1,4 +1,4 @@
-use std::error::Error;
+use std::{collections::HashMap, error::Error};
 
 use bevy::{
     asset::{HandleId, LoadState},
@@ -6,24 +6,266 @@ use bevy::{
 };
 use blackout::{
     bevy_input_actionmap::{GamepadAxisDirection, InputMap},
-    bevy_openal::{efx, Buffers, Context, GlobalEffects},
-    core::Player,
+    bevy_openal::{efx, Buffers, Context, GlobalEffects, Sound, SoundState},
+    bevy_tts::Tts,
+    core::{Area, Paused, Player},
+    derive_more::{Deref, DerefMut},
     error::error_handler,
-    log::Log,
-    map::{Map, MapConfig},
+    exploration::{self, Exploring},
+    localization::Localization,
+    log::{self, Log, LogPriority},
+    map::{AreaChanged, Areas, Map, MapConfig},
     navigation,
     navigation::NavigationConfig,
+    rand::prelude::*,
+    speech::{self, SpeechQueue},
 };
 
-use crate::player::Score;
+use crate::{level::Level, player::Score, robot::RobotType};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum AppState {
     Loading,
+    NewGame,
     InGame,
     LevelUp,
     BetweenLives,
     GameOver,
+    Paused,
+    LoadingSave,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+impl Difficulty {
+    pub fn shot_range_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    pub fn shot_speed_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    pub fn starting_lives(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    pub fn robot_count_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    pub fn max_speed_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.15,
+        }
+    }
+
+    pub fn spread_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    fn localization_key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+
+    pub fn ambience_gain_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    pub fn ambience_density_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 2.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GameMode {
+    Standard,
+    Survival,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Standard
+    }
+}
+
+impl GameMode {
+    pub fn next(&self) -> Self {
+        match self {
+            GameMode::Standard => GameMode::Survival,
+            GameMode::Survival => GameMode::Standard,
+        }
+    }
+
+    fn localization_key(&self) -> &'static str {
+        match self {
+            GameMode::Standard => "game_mode.standard",
+            GameMode::Survival => "game_mode.survival",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AmbienceConfig {
+    pub scale_with_difficulty: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AssistConfig {
+    pub robots_on_radar: bool,
+    // Freeze the game while the player explores, resuming once they move.
+    pub pause_while_exploring: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DifficultyCurve {
+    pub enabled: bool,
+    pub speed_per_level: f32,
+    pub max_speed_multiplier: f32,
+    pub shot_interval_per_level: f32,
+    pub min_shot_interval_multiplier: f32,
+    pub accuracy_per_level: f32,
+    pub min_spread_multiplier: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed_per_level: 0.05,
+            max_speed_multiplier: 1.5,
+            shot_interval_per_level: 0.05,
+            min_shot_interval_multiplier: 0.5,
+            accuracy_per_level: 0.05,
+            min_spread_multiplier: 0.5,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    fn steps(&self, level: u32) -> f32 {
+        if self.enabled {
+            level.saturating_sub(1) as f32
+        } else {
+            0.
+        }
+    }
+
+    pub fn speed_multiplier(&self, level: u32) -> f32 {
+        (1. + self.speed_per_level * self.steps(level)).min(self.max_speed_multiplier)
+    }
+
+    pub fn shot_interval_multiplier(&self, level: u32) -> f32 {
+        (1. - self.shot_interval_per_level * self.steps(level))
+            .max(self.min_shot_interval_multiplier)
+    }
+
+    pub fn spread_multiplier(&self, level: u32) -> f32 {
+        (1. - self.accuracy_per_level * self.steps(level)).max(self.min_spread_multiplier)
+    }
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct GameRng(pub StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Volumes {
+    pub master: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self { master: 1. }
+    }
+}
+
+const VOLUME_STEP: f32 = 0.1;
+
+// Speech rate in the backend's own units. `None` leaves the backend at its normal rate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpeechConfig {
+    pub rate: Option<f32>,
+}
+
+// Fraction of the backend's rate range moved per keypress.
+const SPEECH_RATE_STEP: f32 = 0.05;
+
+#[derive(Clone, Debug)]
+pub struct Narration {
+    pub enabled: bool,
+    pub intro: Option<String>,
+    pub levels: Vec<String>,
+    pub outro: Option<String>,
+}
+
+impl Default for Narration {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intro: Some("The robots have overrun the complex. Find the exits, and take out as many of them as you can on the way.".into()),
+            levels: vec![],
+            outro: Some("The robots have won this round.".into()),
+        }
+    }
 }
 
 // This asset-handling/loading code needs some cleanup.
@@ -36,6 +278,7 @@ pub struct AssetHandles {
 #[derive(Clone, Debug)]
 pub struct Sprites {
     pub badass: HandleId,
+    pub bomber: HandleId,
     pub bullet: HandleId,
     pub dumbass: HandleId,
     pub jackass: HandleId,
@@ -46,6 +289,7 @@ impl Default for Sprites {
     fn default() -> Self {
         Self {
             badass: "gfx/red.png".into(),
+            bomber: "gfx/green.png".into(),
             bullet: "gfx/silver.png".into(),
             dumbass: "gfx/yellow.png".into(),
             jackass: "gfx/fuchsia.png".into(),
@@ -53,32 +297,69 @@ impl Default for Sprites {
         }
     }
 }
+#[derive(Clone, Debug, Default)]
+pub struct VoiceSet {
+    pub investigate: Vec<HandleId>,
+    pub taunts: Vec<HandleId>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Sfx {
     pub ambiences: Vec<HandleId>,
+    pub bomb_beep: HandleId,
     pub bonus_clear: HandleId,
     pub bonus: HandleId,
     pub bullet: HandleId,
     pub bullet_wall: HandleId,
+    pub bullet_whiz: HandleId,
+    pub checkpoint_beacon: HandleId,
+    pub damage_indicator: HandleId,
     pub drone: HandleId,
+    pub empty_click: HandleId,
     pub exit: HandleId,
     pub exit_correct: HandleId,
+    pub kill_confirmed: HandleId,
     pub investigate: Vec<HandleId>,
+    pub invulnerable: HandleId,
     pub level_exit: HandleId,
     pub life_lost: HandleId,
+    pub melee: HandleId,
     pub player_footstep: HandleId,
     pub player_shoot: HandleId,
+    pub reload: HandleId,
     pub robot_badass: HandleId,
+    pub robot_bomber: HandleId,
     pub robot_dumbass: HandleId,
     pub robot_explode: HandleId,
+    pub robot_damaged: Vec<HandleId>,
     pub robot_footstep: HandleId,
+    pub robot_hit: HandleId,
     pub robot_jackass: HandleId,
     pub robot_shoot: HandleId,
+    pub scan_tone: HandleId,
     pub shockwave: HandleId,
+    pub sprint: HandleId,
     pub taunts: Vec<HandleId>,
+    pub voices: HashMap<RobotType, VoiceSet>,
     pub wall_power_up: HandleId,
 }
 
+impl Sfx {
+    pub fn investigate_for(&self, robot_type: &RobotType) -> &Vec<HandleId> {
+        match self.voices.get(robot_type) {
+            Some(voices) if !voices.investigate.is_empty() => &voices.investigate,
+            _ => &self.investigate,
+        }
+    }
+
+    pub fn taunts_for(&self, robot_type: &RobotType) -> &Vec<HandleId> {
+        match self.voices.get(robot_type) {
+            Some(voices) if !voices.taunts.is_empty() => &voices.taunts,
+            _ => &self.taunts,
+        }
+    }
+}
+
 impl Default for Sfx {
     fn default() -> Self {
         Self {
@@ -90,13 +371,19 @@ impl Default for Sfx {
                 "sfx/ambience5.flac".into(),
                 "sfx/ambience6.flac".into(),
             ],
+            bomb_beep: "sfx/bonus.flac".into(),
             bonus_clear: "sfx/bonus_clear.flac".into(),
             bonus: "sfx/bonus.flac".into(),
             bullet: "sfx/bullet.flac".into(),
             bullet_wall: "sfx/bullet_wall.flac".into(),
+            bullet_whiz: "sfx/bullet.flac".into(),
+            checkpoint_beacon: "sfx/bonus_clear.flac".into(),
+            damage_indicator: "sfx/robot_shoot.flac".into(),
             drone: "sfx/drone.flac".into(),
+            empty_click: "sfx/bullet_wall.flac".into(),
             exit: "sfx/exit.flac".into(),
             exit_correct: "sfx/exit_correct.flac".into(),
+            kill_confirmed: "sfx/bonus_clear.flac".into(),
             investigate: vec![
                 "sfx/investigate1.flac".into(),
                 "sfx/investigate2.flac".into(),
@@ -106,17 +393,25 @@ impl Default for Sfx {
                 "sfx/investigate6.flac".into(),
                 "sfx/investigate7.flac".into(),
             ],
+            invulnerable: "sfx/drone.flac".into(),
             level_exit: "sfx/level_exit.flac".into(),
             life_lost: "sfx/life_lost.flac".into(),
+            melee: "sfx/bullet_wall.flac".into(),
             player_footstep: "sfx/player_footstep.flac".into(),
             player_shoot: "sfx/player_shoot.flac".into(),
+            reload: "sfx/wall_power_up.flac".into(),
             robot_badass: "sfx/robot_badass.flac".into(),
+            robot_bomber: "sfx/robot_dumbass.flac".into(),
             robot_dumbass: "sfx/robot_dumbass.flac".into(),
             robot_explode: "sfx/robot_explode.flac".into(),
+            robot_damaged: vec!["sfx/taunt2.flac".into(), "sfx/taunt5.flac".into()],
             robot_footstep: "sfx/robot_footstep.flac".into(),
+            robot_hit: "sfx/bullet_wall.flac".into(),
             robot_jackass: "sfx/robot_jackass.flac".into(),
             robot_shoot: "sfx/robot_shoot.flac".into(),
+            scan_tone: "sfx/bonus.flac".into(),
             shockwave: "sfx/shockwave.flac".into(),
+            sprint: "sfx/wall_power_up.flac".into(),
             taunts: vec![
                 "sfx/taunt1.flac".into(),
                 "sfx/taunt2.flac".into(),
@@ -127,21 +422,261 @@ impl Default for Sfx {
                 "sfx/taunt7.flac".into(),
                 "sfx/taunt8.flac".into(),
             ],
+            voices: {
+                let mut voices = HashMap::new();
+                voices.insert(
+                    RobotType::Dumbass,
+                    VoiceSet {
+                        investigate: vec![
+                            "sfx/investigate1.flac".into(),
+                            "sfx/investigate2.flac".into(),
+                            "sfx/investigate3.flac".into(),
+                        ],
+                        taunts: vec![
+                            "sfx/taunt1.flac".into(),
+                            "sfx/taunt2.flac".into(),
+                            "sfx/taunt3.flac".into(),
+                        ],
+                    },
+                );
+                voices.insert(
+                    RobotType::Jackass,
+                    VoiceSet {
+                        investigate: vec![
+                            "sfx/investigate4.flac".into(),
+                            "sfx/investigate5.flac".into(),
+                        ],
+                        taunts: vec![
+                            "sfx/taunt4.flac".into(),
+                            "sfx/taunt5.flac".into(),
+                            "sfx/taunt6.flac".into(),
+                        ],
+                    },
+                );
+                voices.insert(
+                    RobotType::Badass,
+                    VoiceSet {
+                        investigate: vec![
+                            "sfx/investigate6.flac".into(),
+                            "sfx/investigate7.flac".into(),
+                        ],
+                        taunts: vec!["sfx/taunt7.flac".into(), "sfx/taunt8.flac".into()],
+                    },
+                );
+                voices
+            },
             wall_power_up: "sfx/wall_power_up.flac".into(),
         }
     }
 }
 
+pub const ENGLISH: &[(&str, &str)] = &[
+    ("bullet.ouch", "Ouch!"),
+    ("checkpoint_beacon.off", "Checkpoint beacon off."),
+    ("checkpoint_beacon.on", "Checkpoint beacon on."),
+    ("danger_meter.off", "Danger meter off."),
+    ("danger_meter.on", "Danger meter on."),
+    ("difficulty.easy", "Easy"),
+    ("difficulty.hard", "Hard"),
+    ("difficulty.normal", "Normal"),
+    ("exit.name", "Exit"),
+    ("game.difficulty", "Difficulty: {}"),
+    ("game.mode", "Mode: {}"),
+    (
+        "game.new",
+        "Press Tab to change difficulty, G to change mode, or Enter to start.",
+    ),
+    (
+        "game.over",
+        "Game over. Your final score is {}. Press Tab to change difficulty, G to change mode, or Enter to play again.",
+    ),
+    ("game.paused", "Paused"),
+    ("game.resumed", "Resumed"),
+    ("game.speech_rate", "Speech rate {}%"),
+    ("game.volume", "Volume {}%"),
+    ("game_mode.standard", "Standard"),
+    ("game_mode.survival", "Survival"),
+    (
+        "level.complete.one",
+        "Congratulations! Your score is {}, and you left {} robot behind. You've earned an extra life! Press Enter to continue to level {}.",
+    ),
+    (
+        "level.complete.other",
+        "Congratulations! Your score is {}, and you left {} robots behind. You've earned an extra life! Press Enter to continue to level {}.",
+    ),
+    ("level.irate_robot", "You ran into a very irate {}."),
+    (
+        "level.kills_required.one",
+        "{} more robot must be destroyed before you can leave.",
+    ),
+    (
+        "level.kills_required.other",
+        "{} more robots must be destroyed before you can leave.",
+    ),
+    ("level.no_exit", "No exit found."),
+    ("level.start", "Level {}."),
+    (
+        "level.wave.one",
+        "Wave {} incoming with {} robot! {} bonus points.",
+    ),
+    (
+        "level.wave.other",
+        "Wave {} incoming with {} robots! {} bonus points.",
+    ),
+    ("level.wall", "Wall! Wall! You ran into a wall!"),
+    ("orientation.exit", "Exit"),
+    ("orientation.here", "{}: here"),
+    ("orientation.location", "{}: {}"),
+    ("orientation.start", "Start"),
+    ("player.ammo.one", "{} round of {}."),
+    ("player.ammo.other", "{} rounds of {}."),
+    ("player.ammo.unlimited", "Unlimited ammo."),
+    ("player.coordinates", "({}, {})"),
+    ("player.coordinates.area", "({}, {}), {}"),
+    ("player.efficiency", "{} fired, {}, {}% hit ratio."),
+    ("player.kills.one", "{} kill"),
+    ("player.kills.other", "{} kills"),
+    ("player.level", "Level {}"),
+    ("player.lives.one", "{} life left."),
+    ("player.lives.other", "{} lives left."),
+    ("player.name", "You"),
+    ("player.reloading", "Reloading."),
+    ("player.safe_zone.enter", "Safe zone."),
+    ("player.safe_zone.leave", "Leaving safe zone."),
+    ("player.score.one", "{} point."),
+    ("player.score.other", "{} points."),
+    ("player.shots.one", "{} shot"),
+    ("player.shots.other", "{} shots"),
+    ("player.sprint.off", "Walking."),
+    ("player.sprint.on", "Sprinting."),
+    ("pursuit_trend.approaching", "Closing in."),
+    ("pursuit_trend.off", "Pursuit cues off."),
+    ("pursuit_trend.on", "Pursuit cues on."),
+    ("pursuit_trend.retreating", "Falling back."),
+    ("robot.blast", "You're caught in the blast of {}!"),
+    ("robot.description", "{}: {}, {}, {}"),
+    ("robot.evaded", "{} evaded!"),
+    (
+        "robot.grace.one",
+        "Robots are holding their fire for {} second.",
+    ),
+    (
+        "robot.grace.other",
+        "Robots are holding their fire for {} seconds.",
+    ),
+    ("robot.grace.over", "Robots are opening fire!"),
+    ("robot.has_not_seen_you", "hasn't seen you"),
+    ("robot.killed.defeated", "{} is defeated!"),
+    ("robot.killed.melee", "{} is smashed to pieces!"),
+    ("robot.killed.no_more", "{} is no more!"),
+    ("robot.killed.obliterated", "{} is obliterated!"),
+    ("robot.killed.self_destruct", "{} self-destructs!"),
+    ("robot.killed.shockwave", "{} is taken out by an exploding {}!"),
+    ("robot.killed.stage_left", "{} exits stage left!"),
+    ("robot.killed.toast", "{} is toast!"),
+    (
+        "robot.killed.warranty",
+        "{} just suffered a warranty-voiding event!",
+    ),
+    ("robot.name", "{} {}"),
+    ("robot.nearest", "{}: {}"),
+    ("robot.none_focused", "No robot focused."),
+    ("robot.none_nearby", "No robots in sight."),
+    ("robot.sees_you", "sees you"),
+    ("robot_type.badass", "Badass"),
+    ("robot_type.bomber", "Bomber"),
+    ("robot_type.dumbass", "Dumbass"),
+    ("robot_type.jackass", "Jackass"),
+    ("robots.remaining.one", "{} robot remaining."),
+    ("robots.remaining.other", "{} robots remaining."),
+    ("save.auto.off", "Auto-save off"),
+    ("save.auto.one", "Auto-save every level"),
+    ("save.auto.other", "Auto-save every {} levels"),
+    ("save.loaded", "Game loaded."),
+    ("save.none", "No saved game."),
+    ("save.saved", "Game saved."),
+    ("scan.no_area", "Not in a room."),
+    ("score.depleted", "Out of points. Game over."),
+    ("score.milestone", "{} points!"),
+    ("streak.double", "Double kill!"),
+    ("streak.off", "Kill streak callouts off."),
+    ("streak.on", "Kill streak callouts on."),
+    ("streak.quad", "Quad kill!"),
+    ("streak.rampage", "Rampage!"),
+    ("streak.triple", "Triple kill!"),
+];
+
+pub const DESCRIBE_ROBOT: &str = "DESCRIBE_ROBOT";
+pub const SCAN_AREA: &str = "SCAN_AREA";
+pub const SPEAK_AMMO: &str = "SPEAK_AMMO";
 pub const SPEAK_COORDINATES: &str = "SPEAK_COORDINATES";
 pub const SPEAK_DIRECTION: &str = "SPEAK_DIRECTION";
+pub const SPEAK_EFFICIENCY: &str = "SPEAK_EFFICIENCY";
 pub const SPEAK_HEALTH: &str = "SPEAK_HEALTH";
 pub const SPEAK_LEVEL: &str = "SPEAK_LEVEL";
+pub const SPEAK_NEAREST_ROBOT: &str = "SPEAK_NEAREST_ROBOT";
+pub const SPEAK_ORIENTATION: &str = "SPEAK_ORIENTATION";
 pub const SPEAK_ROBOT_COUNT: &str = "SPEAK_ROBOT_COUNT";
 pub const SPEAK_SCORE: &str = "SPEAK_SCORE";
+pub const SPEECH_RATE_DOWN: &str = "SPEECH_RATE_DOWN";
+pub const SPEECH_RATE_UP: &str = "SPEECH_RATE_UP";
 pub const SNAP_LEFT: &str = "SNAP_LEFT";
 pub const SNAP_RIGHT: &str = "SNAP_RIGHT";
 pub const SHOOT: &str = "SHOOT";
 pub const CONTINUE: &str = "CONTINUE";
+pub const CYCLE_AUTO_SAVE: &str = "CYCLE_AUTO_SAVE";
+pub const CYCLE_DIFFICULTY: &str = "CYCLE_DIFFICULTY";
+pub const CYCLE_GAME_MODE: &str = "CYCLE_GAME_MODE";
+pub const FACE_EXIT: &str = "FACE_EXIT";
+pub const LOAD: &str = "LOAD";
+// Deliberately left unbound until melee has its own sound; see `MeleeConfig`.
+pub const MELEE: &str = "MELEE";
+pub const PAUSE: &str = "PAUSE";
+pub const RELOAD: &str = "RELOAD";
+pub const SAVE: &str = "SAVE";
+pub const TOGGLE_CHECKPOINT_BEACON: &str = "TOGGLE_CHECKPOINT_BEACON";
+pub const TOGGLE_DANGER_METER: &str = "TOGGLE_DANGER_METER";
+pub const TOGGLE_KILL_STREAKS: &str = "TOGGLE_KILL_STREAKS";
+pub const TOGGLE_PURSUIT_TREND: &str = "TOGGLE_PURSUIT_TREND";
+pub const VOLUME_DOWN: &str = "VOLUME_DOWN";
+pub const VOLUME_UP: &str = "VOLUME_UP";
+
+// Give an entity carrying an `Area` its own reverb, used while the player is inside it.
+#[derive(Clone, Copy, Debug)]
+pub struct ReverbZone(pub efx::EaxReverbProperties);
+
+// Assigns `ReverbZone`s to each level's areas by size. Off by default, leaving the generic preset.
+#[derive(Clone, Copy, Debug)]
+pub struct ReverbZoneConfig {
+    pub enabled: bool,
+    // Areas with at least this many tiles get a cavernous preset.
+    pub large_area: usize,
+    // Areas with at most this many tiles get a dry one.
+    pub small_area: usize,
+}
+
+impl Default for ReverbZoneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            large_area: 200,
+            small_area: 24,
+        }
+    }
+}
+
+fn set_reverb(
+    context: &Context,
+    global_effects: &mut GlobalEffects,
+    preset: &efx::EaxReverbProperties,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(slot) = global_effects.get_mut(0) {
+        let mut reverb = context.new_effect::<efx::EaxReverbEffect>()?;
+        reverb.set_preset(preset)?;
+        slot.set_effect(&reverb)?;
+    }
+    Ok(())
+}
 
 fn setup(
     asset_server: Res<AssetServer>,
@@ -149,7 +684,9 @@ fn setup(
     mut input: ResMut<InputMap<String>>,
     context: ResMut<Context>,
     mut global_effects: ResMut<GlobalEffects>,
+    mut localization: ResMut<Localization>,
 ) -> Result<(), Box<dyn Error>> {
+    localization.add_fallbacks(ENGLISH);
     handles.gfx = asset_server.load_folder("gfx")?;
     handles.sfx = asset_server.load_folder("sfx")?;
     let mut slot = context.new_aux_effect_slot()?;
@@ -216,14 +753,23 @@ fn setup(
             navigation::ACTION_ROTATE_RIGHT,
             GamepadButtonType::DPadRight,
         )
+        .bind(DESCRIBE_ROBOT, KeyCode::I)
+        .bind(SCAN_AREA, KeyCode::A)
+        .bind(SPEAK_AMMO, KeyCode::W)
         .bind(SPEAK_COORDINATES, KeyCode::C)
         .bind(SPEAK_COORDINATES, GamepadButtonType::LeftThumb)
         .bind(SPEAK_DIRECTION, KeyCode::D)
         .bind(SPEAK_DIRECTION, GamepadButtonType::RightThumb)
+        .bind(navigation::ACTION_SPEAK_HEADING, KeyCode::V)
+        .bind(SPEAK_EFFICIENCY, KeyCode::E)
         .bind(SPEAK_HEALTH, KeyCode::H)
         .bind(SPEAK_LEVEL, KeyCode::L)
+        .bind(SPEAK_NEAREST_ROBOT, KeyCode::N)
+        .bind(SPEAK_ORIENTATION, KeyCode::O)
         .bind(SPEAK_ROBOT_COUNT, KeyCode::R)
         .bind(SPEAK_SCORE, KeyCode::S)
+        .bind(SPEECH_RATE_DOWN, KeyCode::Minus)
+        .bind(SPEECH_RATE_UP, KeyCode::Equals)
         .bind(SNAP_LEFT, vec![KeyCode::LControl, KeyCode::Left])
         .bind(SNAP_LEFT, vec![KeyCode::RControl, KeyCode::Left])
         .bind(SNAP_LEFT, GamepadButtonType::LeftTrigger)
@@ -234,7 +780,45 @@ fn setup(
         .bind(SHOOT, GamepadButtonType::LeftTrigger2)
         .bind(SHOOT, GamepadButtonType::RightTrigger2)
         .bind(CONTINUE, KeyCode::Return)
-        .bind(CONTINUE, GamepadButtonType::South);
+        .bind(CONTINUE, GamepadButtonType::South)
+        .bind(CYCLE_DIFFICULTY, KeyCode::Tab)
+        .bind(CYCLE_GAME_MODE, KeyCode::G)
+        .bind(FACE_EXIT, KeyCode::X)
+        .bind(RELOAD, KeyCode::Q)
+        .bind(RELOAD, GamepadButtonType::East)
+        .bind(PAUSE, KeyCode::P)
+        .bind(PAUSE, GamepadButtonType::Start)
+        .bind(log::ACTION_REVIEW_LOG_PREV, KeyCode::Comma)
+        .bind(log::ACTION_REVIEW_LOG_NEXT, KeyCode::Period)
+        .bind(log::ACTION_REVIEW_LOG_LAST, KeyCode::Slash)
+        .bind(SAVE, KeyCode::F5)
+        .bind(CYCLE_AUTO_SAVE, KeyCode::F6)
+        .bind(LOAD, KeyCode::F9)
+        .bind(speech::ACTION_SPEAK_QUEUE_STATUS, KeyCode::F12)
+        .bind(TOGGLE_CHECKPOINT_BEACON, KeyCode::B)
+        .bind(TOGGLE_DANGER_METER, KeyCode::M)
+        .bind(TOGGLE_KILL_STREAKS, KeyCode::K)
+        .bind(TOGGLE_PURSUIT_TREND, KeyCode::T)
+        .bind(exploration::ACTION_EXPLORE_FORWARD, KeyCode::Numpad8)
+        .bind(exploration::ACTION_EXPLORE_BACKWARD, KeyCode::Numpad2)
+        .bind(exploration::ACTION_EXPLORE_LEFT, KeyCode::Numpad4)
+        .bind(exploration::ACTION_EXPLORE_RIGHT, KeyCode::Numpad6)
+        .bind(exploration::ACTION_EXPLORE_FOCUS_PREV, KeyCode::Numpad7)
+        .bind(exploration::ACTION_EXPLORE_FOCUS_NEXT, KeyCode::Numpad9)
+        .bind(
+            exploration::ACTION_EXPLORE_SELECT_PREV_TYPE,
+            KeyCode::Numpad1,
+        )
+        .bind(
+            exploration::ACTION_EXPLORE_SELECT_NEXT_TYPE,
+            KeyCode::Numpad3,
+        )
+        .bind(
+            exploration::ACTION_NAVIGATE_TO_EXPLORED,
+            KeyCode::NumpadEnter,
+        )
+        .bind(VOLUME_DOWN, KeyCode::PageDown)
+        .bind(VOLUME_UP, KeyCode::PageUp);
     Ok(())
 }
 
@@ -254,7 +838,7 @@ fn load(
     if gfx_loaded && sfx_loaded && buffers_created == handles.sfx.len() {
         let tiles = asset_server.get_handle("sfx/tiles.png");
         materials.add(ColorMaterial::texture(tiles));
-        state.overwrite_replace(AppState::InGame)?;
+        state.overwrite_replace(AppState::NewGame)?;
     }
     Ok(())
 }
@@ -265,12 +849,93 @@ pub enum Reset {
     NewLevel,
 }
 
+fn assign_reverb_zones(
+    mut commands: Commands,
+    config: Res<ReverbZoneConfig>,
+    map: Query<(Entity, &Areas), Added<Areas>>,
+) {
+    if !config.enabled {
+        return;
+    }
+    for (map_entity, areas) in map.iter() {
+        for area in areas.iter() {
+            let size = area.rect.width() * area.rect.height();
+            let preset = if size >= config.large_area {
+                efx::REVERB_PRESET_CAVE
+            } else if size <= config.small_area {
+                efx::REVERB_PRESET_CARPETEDHALLWAY
+            } else {
+                continue;
+            };
+            let zone = commands
+                .spawn()
+                .insert(area.clone())
+                .insert(ReverbZone(preset))
+                .id();
+            commands.entity(map_entity).push_children(&[zone]);
+        }
+    }
+}
+
+fn reverb_zones(
+    context: Res<Context>,
+    mut global_effects: ResMut<GlobalEffects>,
+    mut events: EventReader<AreaChanged>,
+    player: Query<&Player>,
+    zones: Query<(Entity, &Area, &ReverbZone)>,
+    mut current: Local<Option<Entity>>,
+) -> Result<(), Box<dyn Error>> {
+    for AreaChanged { entity, to, .. } in events.iter() {
+        if player.get(*entity).is_err() {
+            continue;
+        }
+        let zone = zones.iter().find(|(_, area, _)| *area == to);
+        let zone_entity = zone.map(|(entity, _, _)| entity);
+        if zone_entity == *current {
+            continue;
+        }
+        *current = zone_entity;
+        let preset = zone
+            .map(|(_, _, ReverbZone(preset))| *preset)
+            .unwrap_or(efx::REVERB_PRESET_GENERIC);
+        set_reverb(&context, &mut global_effects, &preset)?;
+    }
+    Ok(())
+}
+
 fn send_new_game_event(mut events: EventWriter<Reset>) {
     events.send(Reset::NewGame);
 }
 
+fn narrate(
+    narration: Res<Narration>,
+    mut events: EventReader<Reset>,
+    level: Query<&Level, (With<Player>, Changed<Level>)>,
+    mut log: Query<&mut Log>,
+) {
+    if !narration.enabled {
+        return;
+    }
+    if let Ok(mut log) = log.single_mut() {
+        for event in events.iter() {
+            if let Reset::NewGame = event {
+                if let Some(intro) = &narration.intro {
+                    log.push(intro.clone());
+                }
+            }
+        }
+        if let Ok(level) = level.single() {
+            if let Some(line) = narration.levels.get((**level as usize).saturating_sub(1)) {
+                log.push(line.clone());
+            }
+        }
+    }
+}
+
 fn game_over_enter(
     mut commands: Commands,
+    narration: Res<Narration>,
+    localization: Res<Localization>,
     map: Query<(Entity, &Map)>,
     score: Query<&Score>,
     mut log: Query<&mut Log>,
@@ -280,21 +945,83 @@ fn game_over_enter(
     }
     if let Ok(score) = score.single() {
         if let Ok(mut log) = log.single_mut() {
-            log.push(format!(
-                "Game over. Your final score is {}. Press Enter to play again.",
-                **score
-            ));
+            if narration.enabled {
+                if let Some(outro) = &narration.outro {
+                    log.push(outro.clone());
+                }
+            }
+            log.push_with_priority(
+                localization.format("game.over", &[&**score]),
+                LogPriority::Urgent,
+            );
         }
     }
 }
 
+fn cycle_game_options(
+    input: &InputMap<String>,
+    difficulty: &mut Difficulty,
+    mode: &mut GameMode,
+    speech: &mut SpeechQueue,
+    localization: &Localization,
+) {
+    if input.just_active(CYCLE_DIFFICULTY) {
+        *difficulty = difficulty.next();
+        let name = localization.get(difficulty.localization_key());
+        speech.interrupt(localization.format("game.difficulty", &[&name]));
+    }
+    if input.just_active(CYCLE_GAME_MODE) {
+        *mode = mode.next();
+        let name = localization.get(mode.localization_key());
+        speech.interrupt(localization.format("game.mode", &[&name]));
+    }
+}
+
+fn new_game_enter(localization: Res<Localization>, mut log: Query<&mut Log>) {
+    if let Ok(mut log) = log.single_mut() {
+        log.push_with_priority(localization.get("game.new"), LogPriority::Urgent);
+    }
+}
+
+fn new_game_update(
+    input: Res<InputMap<String>>,
+    mut state: ResMut<State<AppState>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    cycle_game_options(
+        &input,
+        &mut difficulty,
+        &mut mode,
+        &mut speech,
+        &localization,
+    );
+    if input.just_active(CONTINUE) {
+        state.overwrite_replace(AppState::InGame)?;
+    }
+    Ok(())
+}
+
 fn game_over_update(
     mut commands: Commands,
     input: Res<InputMap<String>>,
     mut state: ResMut<State<AppState>>,
     player: Query<(Entity, &Player)>,
     mut events: EventWriter<Reset>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
 ) -> Result<(), Box<dyn Error>> {
+    cycle_game_options(
+        &input,
+        &mut difficulty,
+        &mut mode,
+        &mut speech,
+        &localization,
+    );
     if input.just_active(CONTINUE) {
         for (entity, _) in player.iter() {
             commands.entity(entity).despawn_recursive();
@@ -305,6 +1032,186 @@ fn game_over_update(
     Ok(())
 }
 
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+struct PausedSounds(Vec<Entity>);
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+struct PausedForExploration(bool);
+
+fn pause(
+    input: Res<InputMap<String>>,
+    mut state: ResMut<State<AppState>>,
+) -> Result<(), Box<dyn Error>> {
+    if input.just_active(PAUSE) {
+        state.push(AppState::Paused)?;
+    }
+    Ok(())
+}
+
+fn pause_for_exploration(
+    assist: Res<AssistConfig>,
+    player: Query<&Player, Added<Exploring>>,
+    mut paused: ResMut<PausedForExploration>,
+    mut state: ResMut<State<AppState>>,
+) -> Result<(), Box<dyn Error>> {
+    if assist.pause_while_exploring && player.iter().next().is_some() {
+        **paused = true;
+        state.push(AppState::Paused)?;
+    }
+    Ok(())
+}
+
+fn mark_paused(mut paused: ResMut<Paused>) {
+    **paused = true;
+}
+
+fn mark_resumed(mut paused: ResMut<Paused>) {
+    **paused = false;
+}
+
+fn pause_enter(
+    mut sounds: Query<(Entity, &mut Sound)>,
+    mut paused_sounds: ResMut<PausedSounds>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    paused_sounds.clear();
+    for (entity, mut sound) in sounds.iter_mut() {
+        if sound.state == SoundState::Playing {
+            sound.state = SoundState::Paused;
+            paused_sounds.push(entity);
+        }
+    }
+    tts.speak(localization.get("game.paused"), true)?;
+    Ok(())
+}
+
+fn pause_update(
+    input: Res<InputMap<String>>,
+    mut state: ResMut<State<AppState>>,
+    mut paused_for_exploration: ResMut<PausedForExploration>,
+) -> Result<(), Box<dyn Error>> {
+    let moved = [
+        navigation::ACTION_FORWARD,
+        navigation::ACTION_BACKWARD,
+        navigation::ACTION_LEFT,
+        navigation::ACTION_RIGHT,
+        navigation::ACTION_ROTATE_LEFT,
+        navigation::ACTION_ROTATE_RIGHT,
+    ]
+    .iter()
+    .any(|action| input.just_active(*action));
+    if input.just_active(PAUSE) || (**paused_for_exploration && moved) {
+        **paused_for_exploration = false;
+        state.pop()?;
+    }
+    Ok(())
+}
+
+fn pause_exit(
+    mut sounds: Query<&mut Sound>,
+    mut paused_sounds: ResMut<PausedSounds>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    for entity in paused_sounds.drain(..) {
+        if let Ok(mut sound) = sounds.get_mut(entity) {
+            if sound.state == SoundState::Paused {
+                sound.play();
+            }
+        }
+    }
+    tts.speak(localization.get("game.resumed"), true)?;
+    Ok(())
+}
+
+fn adjust_master_volume(
+    input: Res<InputMap<String>>,
+    mut volumes: ResMut<Volumes>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    let mut master = volumes.master;
+    if input.just_active(VOLUME_DOWN) {
+        master -= VOLUME_STEP;
+    }
+    if input.just_active(VOLUME_UP) {
+        master += VOLUME_STEP;
+    }
+    let master = (master.clamp(0., 1.) * 10.).round() / 10.;
+    if (master - volumes.master).abs() > f32::EPSILON {
+        volumes.master = master;
+        tts.speak(
+            localization.format("game.volume", &[&((master * 100.) as u32)]),
+            true,
+        )?;
+    }
+    Ok(())
+}
+
+// Whatever the master volume drives; the OpenAL context in the game.
+trait ListenerGain {
+    fn set_listener_gain(&self, gain: f32) -> Result<(), Box<dyn Error>>;
+}
+
+impl ListenerGain for Context {
+    fn set_listener_gain(&self, gain: f32) -> Result<(), Box<dyn Error>> {
+        self.set_gain(gain)?;
+        Ok(())
+    }
+}
+
+fn apply_master_volume<L: ListenerGain + Component>(
+    volumes: Res<Volumes>,
+    listener: Res<L>,
+) -> Result<(), Box<dyn Error>> {
+    if volumes.is_changed() {
+        listener.set_listener_gain(volumes.master)?;
+    }
+    Ok(())
+}
+
+fn adjust_speech_rate(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<SpeechConfig>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    let current = config.rate.unwrap_or_else(|| tts.normal_rate());
+    let step = (tts.max_rate() - tts.min_rate()) * SPEECH_RATE_STEP;
+    let mut rate = current;
+    if input.just_active(SPEECH_RATE_DOWN) {
+        rate -= step;
+    }
+    if input.just_active(SPEECH_RATE_UP) {
+        rate += step;
+    }
+    let rate = rate.clamp(tts.min_rate(), tts.max_rate());
+    if (rate - current).abs() > f32::EPSILON {
+        config.rate = Some(rate);
+        tts.set_rate(rate)?;
+        // Backends disagree on units, so speak the rate relative to normal.
+        let percent = (rate / tts.normal_rate() * 100.).round() as u32;
+        tts.speak(localization.format("game.speech_rate", &[&percent]), true)?;
+    }
+    Ok(())
+}
+
+fn apply_speech_rate(
+    config: Res<SpeechConfig>,
+    mut tts: ResMut<Tts>,
+) -> Result<(), Box<dyn Error>> {
+    if config.is_changed() {
+        if let Some(rate) = config.rate {
+            let rate = rate.clamp(tts.min_rate(), tts.max_rate());
+            if (tts.get_rate()? - rate).abs() > f32::EPSILON {
+                tts.set_rate(rate)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -322,6 +1229,7 @@ impl Plugin for GamePlugin {
             .insert_resource(NavigationConfig {
                 movement_states: vec![AppState::InGame],
                 movement_control_states: vec![AppState::InGame],
+                ..Default::default()
             })
             .insert_resource(MapConfig {
                 speak_area_descriptions: false,
@@ -334,8 +1242,9 @@ impl Plugin for GamePlugin {
             .add_plugin(blackout::bevy_input_actionmap::ActionPlugin::<String>::default())
             .add_plugin(blackout::log::LogPlugin)
             .add_plugin(blackout::map::MapPlugin)
+            .add_plugin(blackout::exploration::ExplorationPlugin)
             .add_plugin(blackout::navigation::NavigationPlugin::<AppState>::default())
-            .add_plugin(blackout::pathfinding::PathfindingPlugin)
+            .add_plugin(blackout::pathfinding::PathfindingPlugin::<AppState>::default())
             .add_plugin(blackout::sound::SoundPlugin)
             .add_plugin(blackout::visibility::VisibilityPlugin)
             .add_plugin(crate::ff::ForceFeedbackPlugin)
@@ -345,19 +1254,75 @@ impl Plugin for GamePlugin {
             .add_plugin(crate::bullet::BulletPlugin)
             .add_plugin(crate::level::LevelPlugin)
             .add_plugin(crate::bonus::BonusPlugin)
+            .add_plugin(crate::danger::DangerPlugin)
+            .add_plugin(crate::scan::ScanPlugin)
+            .add_plugin(crate::save::SavePlugin)
             .add_event::<Reset>()
             .add_state(AppState::Loading)
+            .init_resource::<AmbienceConfig>()
             .init_resource::<AssetHandles>()
+            .init_resource::<AssistConfig>()
+            .init_resource::<Difficulty>()
+            .init_resource::<DifficultyCurve>()
+            .init_resource::<GameMode>()
+            .init_resource::<GameRng>()
+            .init_resource::<Narration>()
+            .init_resource::<PausedForExploration>()
+            .init_resource::<PausedSounds>()
             .init_resource::<Sfx>()
+            .init_resource::<ReverbZoneConfig>()
+            .init_resource::<SpeechConfig>()
             .init_resource::<Sprites>()
+            .init_resource::<Volumes>()
             .add_startup_system(setup.system().chain(error_handler.system()))
+            .add_system(adjust_master_volume.system().chain(error_handler.system()))
+            .add_system(
+                apply_master_volume::<Context>
+                    .system()
+                    .chain(error_handler.system()),
+            )
+            .add_system(adjust_speech_rate.system().chain(error_handler.system()))
+            .add_system(apply_speech_rate.system().chain(error_handler.system()))
+            .add_system(narrate.system())
+            .add_system(assign_reverb_zones.system())
+            // Zones are spawned by commands during the update, so look them up afterward.
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                reverb_zones.system().chain(error_handler.system()),
+            )
             .add_system_set(
                 SystemSet::on_update(AppState::Loading)
                     .with_system(load.system().chain(error_handler.system())),
             )
+            .add_system_set(
+                SystemSet::on_enter(AppState::NewGame).with_system(new_game_enter.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::NewGame)
+                    .with_system(new_game_update.system().chain(error_handler.system())),
+            )
             .add_system_set(
                 SystemSet::on_enter(AppState::InGame).with_system(send_new_game_event.system()),
             )
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(pause.system().chain(error_handler.system()))
+                    .with_system(pause_for_exploration.system().chain(error_handler.system())),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Paused)
+                    .with_system(mark_paused.system())
+                    .with_system(pause_enter.system().chain(error_handler.system())),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Paused)
+                    .with_system(pause_update.system().chain(error_handler.system())),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Paused)
+                    .with_system(mark_resumed.system())
+                    .with_system(pause_exit.system().chain(error_handler.system())),
+            )
             .add_system_set(
                 SystemSet::on_enter(AppState::GameOver).with_system(game_over_enter.system()),
             )
@@ -367,3 +1332,112 @@ impl Plugin for GamePlugin {
             );
     }
 }
+
+// Just enough of an app for systems that look up sound buffers.
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+    use blackout::pathfinding::Destination;
+
+    use super::*;
+    use crate::robot::thinkers_running;
+
+    fn retarget(mut destinations: Query<&mut Destination>) {
+        for mut destination in destinations.iter_mut() {
+            destination.0 .0 += 1;
+        }
+    }
+
+    #[test]
+    fn exploring_with_assist_freezes_robot_destinations() {
+        let mut world = World::default();
+        world.insert_resource(AssistConfig {
+            pause_while_exploring: true,
+            ..Default::default()
+        });
+        world.insert_resource(PausedForExploration::default());
+        world.insert_resource(Paused::default());
+        world.insert_resource(State::new(AppState::InGame));
+        let robot = world.spawn().insert(Destination((1, 1))).id();
+        world.spawn().insert(Player).insert(Exploring((2., 2.)));
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system_set(State::<AppState>::get_driver())
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(pause_for_exploration.system().chain(error_handler.system())),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(mark_paused.system()))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(thinkers_running.system())
+                    .with_system(retarget.system()),
+            );
+        for _ in 0..3 {
+            stage.run(&mut world);
+        }
+        assert_eq!(
+            *world.get_resource::<State<AppState>>().unwrap().current(),
+            AppState::Paused
+        );
+        let frozen = *world.get::<Destination>(robot).unwrap();
+        stage.run(&mut world);
+        assert_eq!(*world.get::<Destination>(robot).unwrap(), frozen);
+    }
+
+    #[derive(Default)]
+    struct RecordedGain(std::sync::Mutex<Vec<f32>>);
+
+    impl ListenerGain for RecordedGain {
+        fn set_listener_gain(&self, gain: f32) -> Result<(), Box<dyn Error>> {
+            self.0.lock().unwrap().push(gain);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn changing_master_volume_updates_listener_gain() {
+        let mut world = World::default();
+        world.insert_resource(Volumes::default());
+        world.insert_resource(RecordedGain::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(
+            apply_master_volume::<RecordedGain>
+                .system()
+                .chain(error_handler.system()),
+        );
+        stage.run(&mut world);
+        world.get_resource_mut::<Volumes>().unwrap().master = 0.4;
+        stage.run(&mut world);
+        stage.run(&mut world);
+        let gains = world.get_resource::<RecordedGain>().unwrap();
+        assert_eq!(*gains.0.lock().unwrap(), vec![1., 0.4]);
+    }
+
+    #[test]
+    fn new_game_queues_the_intro_line() {
+        let mut world = World::default();
+        let narration = Narration {
+            enabled: true,
+            ..Default::default()
+        };
+        let intro = narration.intro.clone().unwrap();
+        world.insert_resource(narration);
+        world.insert_resource(Events::<Reset>::default());
+        let log = world.spawn().insert(Log::default()).id();
+        world
+            .get_resource_mut::<Events<Reset>>()
+            .unwrap()
+            .send(Reset::NewGame);
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(narrate.system());
+        stage.run(&mut world);
+        let messages: Vec<String> = world
+            .get::<Log>(log)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.message.clone())
+            .collect();
+        assert_eq!(messages, vec![intro]);
+    }
+}
@@ -1,15 +1,17 @@
-use std::{error::Error, f32::consts::PI};
+use std::{error::Error, f32::consts::PI, time::Duration};
 
 use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
 use blackout::{
     bevy_input_actionmap::InputMap,
     bevy_openal::{Buffer, Sound, SoundState},
+    bevy_tts::Tts,
     core::{Area, Coordinates, Player, PointLike},
     crossbeam_channel::{unbounded, Receiver, Sender},
     derive_more::{Deref, DerefMut},
     error::error_handler,
-    log::Log,
-    map::{Areas, Exit, GridBuilder, Map, MapBundle},
+    localization::Localization,
+    log::{Log, LogPriority},
+    map::{AreaChanged, Areas, CaveBuilder, Exit, GridBuilder, Map, MapBundle, MapConfig},
     mapgen,
     mapgen::{MapBuilder, TileType},
     navigation::{Collision, MonitorsCollisions, MotionBlocked},
@@ -18,16 +20,26 @@ use blackout::{
     sound::SoundIcon,
     visibility::{Viewshed, VisibilityBlocked},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    game::{AppState, Reset, Sfx, CONTINUE},
-    player::{LifeLost, Lives, Score},
-    robot::{Robot, RobotCommands, RobotType},
+    game::{
+        AmbienceConfig, AppState, Difficulty, DifficultyCurve, GameMode, Reset, Sfx, CONTINUE,
+        FACE_EXIT,
+    },
+    player::{DamageSource, LifeLost, Lives, Score},
+    robot::{BomberConfig, PatrolRoute, Robot, RobotCommands, RobotType},
 };
 
-#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut, Deserialize, Serialize)]
 pub struct Level(u32);
 
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct LevelSeed(pub u64);
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct LevelSeedOverride(pub Option<u64>);
+
 #[derive(Clone, Copy, Debug, Default)]
 struct LevelExit;
 
@@ -36,6 +48,39 @@ struct NextExit;
 
 pub struct WallCollision(pub Coordinates);
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WallPenaltyMode {
+    LifeLoss,
+    // Points deducted per sustained bump.
+    ScoreLoss(u32),
+    SoundOnly,
+}
+
+impl Default for WallPenaltyMode {
+    fn default() -> Self {
+        WallPenaltyMode::LifeLoss
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deref, DerefMut)]
+pub struct CollisionCueCooldown(pub f32);
+
+impl Default for CollisionCueCooldown {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+fn cue_ready(last: &mut Option<f64>, now: f64, cooldown: f32) -> bool {
+    if let Some(last) = last {
+        if now - *last < cooldown as f64 {
+            return false;
+        }
+    }
+    *last = Some(now);
+    true
+}
+
 #[derive(Clone, Debug, Deref, DerefMut)]
 struct WallCollisionTimer(Timer);
 
@@ -45,31 +90,201 @@ impl Default for WallCollisionTimer {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapDimensions {
+    pub width_in_rooms: u32,
+    pub height_in_rooms: u32,
+    pub room_width: u32,
+    pub room_height: u32,
+}
+
+impl MapDimensions {
+    pub fn for_level(level: u32) -> Self {
+        Self {
+            width_in_rooms: 5 + (level / 2),
+            height_in_rooms: 5 + (level / 2),
+            room_width: 16,
+            room_height: 16,
+        }
+    }
+
+    pub fn tile_width(&self) -> usize {
+        (self.width_in_rooms * (self.room_width * 2)) as usize
+    }
+
+    pub fn tile_height(&self) -> usize {
+        (self.height_in_rooms * (self.room_height * 2)) as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RobotDensityConfig {
+    pub enabled: bool,
+    pub robots_per_hundred_tiles: f32,
+}
+
+impl Default for RobotDensityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            robots_per_hundred_tiles: 0.15,
+        }
+    }
+}
+
+impl RobotDensityConfig {
+    pub fn robot_count(&self, walkable_tiles: usize, level_scale: f32) -> u32 {
+        let count = walkable_tiles as f32 / 100. * self.robots_per_hundred_tiles * level_scale;
+        count.round().max(1.) as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClearToExitRule {
+    pub required_fraction: Option<f32>,
+}
+
+impl ClearToExitRule {
+    pub fn kills_remaining(&self, spawned: u32, remaining: u32) -> u32 {
+        if let Some(fraction) = self.required_fraction {
+            let required = (spawned as f32 * fraction.clamp(0., 1.)).ceil() as u32;
+            let killed = spawned.saturating_sub(remaining);
+            required.saturating_sub(killed)
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExitBeaconConfig {
+    pub enabled: bool,
+    pub max_gain: f32,
+    pub min_interval: Option<f32>,
+    pub ramp_distance: f32,
+}
+
+impl Default for ExitBeaconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_gain: 4.,
+            min_interval: Some(1.5),
+            ramp_distance: 40.,
+        }
+    }
+}
+
+impl ExitBeaconConfig {
+    // 0 at or beyond `ramp_distance`, rising to 1 when standing on the exit.
+    fn proximity(&self, distance: f32) -> f32 {
+        if self.ramp_distance <= 0. {
+            return 0.;
+        }
+        (1. - distance / self.ramp_distance).clamp(0., 1.)
+    }
+
+    pub fn gain(&self, base: f32, distance: f32) -> f32 {
+        if self.enabled {
+            base + (self.max_gain - base).max(0.) * self.proximity(distance)
+        } else {
+            base
+        }
+    }
+
+    pub fn interval(&self, base: f32, distance: f32) -> f32 {
+        match self.min_interval {
+            Some(min_interval) if self.enabled => {
+                base - (base - min_interval).max(0.) * self.proximity(distance)
+            }
+            _ => base,
+        }
+    }
+}
+
+const LEVEL_EXIT_GAIN: f32 = 2.;
+
+#[derive(Clone, Copy, Debug, Deref, DerefMut)]
+struct BaseInterval(f32);
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct SpawnedRobots(pub u32);
+
+#[derive(Clone, Copy, Debug)]
+pub struct CaveConfig {
+    // Build every this many levels as an open cave rather than a grid of rooms.
+    pub every: Option<u32>,
+    pub fill_percent: f32,
+    pub smoothing_passes: u32,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        Self {
+            every: None,
+            fill_percent: 0.45,
+            smoothing_passes: 4,
+        }
+    }
+}
+
+impl CaveConfig {
+    pub fn applies_to(&self, level: u32) -> bool {
+        match self.every {
+            Some(every) if every > 0 => level % every == 0,
+            _ => false,
+        }
+    }
+}
+
 fn setup_level(
     mut commands: Commands,
     mut level: Query<&mut Level>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+    config: Res<MapConfig>,
+    mut level_seed: ResMut<LevelSeed>,
+    mut seed_override: ResMut<LevelSeedOverride>,
+    difficulty: Res<Difficulty>,
+    ambience: Res<AmbienceConfig>,
+    caves: Res<CaveConfig>,
 ) {
+    let gain_multiplier = if ambience.scale_with_difficulty {
+        difficulty.ambience_gain_multiplier()
+    } else {
+        1.
+    };
     if let Ok(mut level) = level.single_mut() {
         **level += 1;
-        let map_dimension = 5 + (**level / 2);
-        let room_dimension = 16;
-        let tile_dimension = (map_dimension * (room_dimension * 2)) as usize;
-        let map = MapBuilder::new(tile_dimension, tile_dimension)
-            .with(GridBuilder::new(
-                map_dimension,
-                map_dimension,
-                room_dimension,
-                room_dimension,
-            ))
+        let dimensions = MapDimensions::for_level(**level);
+        let seed = seed_override.take().unwrap_or_else(|| {
+            config
+                .seed
+                .map(|seed| seed.wrapping_add(**level as u64))
+                .unwrap_or_else(random)
+        });
+        **level_seed = seed;
+        let mut builder = MapBuilder::new(dimensions.tile_width(), dimensions.tile_height());
+        if caves.applies_to(**level) {
+            builder.with(CaveBuilder::new(caves.fill_percent, caves.smoothing_passes));
+        } else {
+            builder.with(GridBuilder::new(
+                dimensions.width_in_rooms,
+                dimensions.height_in_rooms,
+                dimensions.room_width,
+                dimensions.room_height,
+                Some(seed),
+            ));
+        }
+        builder
             .with(mapgen::filter::AreaStartingPosition::new(
                 mapgen::XStart::LEFT,
                 mapgen::YStart::TOP,
             ))
-            .with(mapgen::filter::DistantExit::new())
-            .build();
+            .with(mapgen::filter::DistantExit::new());
+        let map = builder.build_with_rng(&mut StdRng::seed_from_u64(seed));
         let map = Map::new(map);
         commands
             .spawn()
@@ -81,13 +296,13 @@ fn setup_level(
                 parent.spawn().insert(Sound {
                     buffer: buffers.get_handle(sfx.drone),
                     state: SoundState::Playing,
-                    gain: 0.2,
+                    gain: 0.2 * gain_multiplier,
                     looping: true,
                     ..Default::default()
                 });
             });
         if let Ok(mut log) = log.single_mut() {
-            log.push(format!("Level {}.", **level));
+            log.push(localization.format("level.start", &[&**level]));
         }
     }
 }
@@ -97,11 +312,24 @@ fn spawn_ambience(
     sfx: Res<Sfx>,
     buffers: Res<Assets<Buffer>>,
     map: Query<(Entity, &Map, &Areas), Added<Areas>>,
+    difficulty: Res<Difficulty>,
+    config: Res<AmbienceConfig>,
 ) {
     if let Ok((entity, _, areas)) = map.single() {
         let mut contains_ambience: Vec<Area> = vec![];
         let mut rng = thread_rng();
-        for handle in &sfx.ambiences {
+        let (gain_multiplier, density_multiplier) = if config.scale_with_difficulty {
+            (
+                difficulty.ambience_gain_multiplier(),
+                difficulty.ambience_density_multiplier(),
+            )
+        } else {
+            (1., 1.)
+        };
+        let count = ((sfx.ambiences.len() as f32 * density_multiplier).round() as usize)
+            .max(1)
+            .min(areas.len());
+        for handle in sfx.ambiences.iter().cycle().take(count) {
             loop {
                 let area_index = rng.gen_range(0..areas.len());
                 let area = &areas[area_index];
@@ -113,7 +341,7 @@ fn spawn_ambience(
                     buffer: buffers.get_handle(*handle),
                     state: SoundState::Playing,
                     looping: true,
-                    gain: 0.4,
+                    gain: 0.4 * gain_multiplier,
                     ..Default::default()
                 };
                 let x = (rng.gen_range(area.rect.x1..area.rect.x2)) as f32;
@@ -131,121 +359,319 @@ fn spawn_ambience(
     }
 }
 
+const PATROL_NEIGHBORS: usize = 2;
+
+fn random_tile(area: &Area, rng: &mut impl Rng) -> (i32, i32) {
+    (
+        rng.gen_range(area.rect.x1..area.rect.x2) as i32,
+        rng.gen_range(area.rect.y1..area.rect.y2) as i32,
+    )
+}
+
+// A waypoint in the robot's own area followed by one in each of the nearest neighboring areas.
+fn patrol_route(area: &Area, areas: &[Area], rng: &mut impl Rng) -> PatrolRoute {
+    let center = area.center();
+    let mut neighbors = areas.iter().filter(|a| *a != area).collect::<Vec<&Area>>();
+    neighbors.sort_by(|a, b| {
+        let a = a.center().distance(&center);
+        let b = b.center().distance(&center);
+        a.partial_cmp(&b).unwrap()
+    });
+    let mut waypoints = vec![random_tile(area, rng)];
+    for neighbor in neighbors.iter().take(PATROL_NEIGHBORS) {
+        waypoints.push(random_tile(neighbor, rng));
+    }
+    PatrolRoute(waypoints)
+}
+
+// Running counts used to number robots of each type, carried across survival waves.
+#[derive(Clone, Copy, Debug, Default)]
+struct RobotCounts {
+    dumbass: u32,
+    jackass: u32,
+    badass: u32,
+    bomber: u32,
+}
+
+// Spawns a wave of robots sized for `level`, keeping clear of the area containing `avoid`.
+fn spawn_wave(
+    commands: &mut Commands,
+    rng: &mut StdRng,
+    map_entity: Entity,
+    map: &Map,
+    areas: &[Area],
+    avoid: &dyn PointLike,
+    level: u32,
+    difficulty: &Difficulty,
+    curve: &DifficultyCurve,
+    density: &RobotDensityConfig,
+    bombers: &BomberConfig,
+    counts: &mut RobotCounts,
+    localization: &Localization,
+) -> u32 {
+    let base_robots = 20;
+    let extra_robots = (level - 1) * 10;
+    let mut total_robots = base_robots + extra_robots;
+    let mut robot_types = vec![RobotType::Dumbass; base_robots as usize];
+    match level {
+        2 => {
+            for _ in 0..5 {
+                robot_types.push(RobotType::Dumbass);
+            }
+            for _ in 5..10 {
+                robot_types.push(RobotType::Jackass);
+            }
+        }
+        v if v > 2 => {
+            for _ in 0..(extra_robots as f32 * 0.3) as u32 {
+                robot_types.push(RobotType::Dumbass);
+            }
+            for _ in 0..(extra_robots as f32 * 0.5) as u32 {
+                robot_types.push(RobotType::Jackass);
+            }
+            for _ in 0..(extra_robots as f32 * 0.2) as u32 {
+                robot_types.push(RobotType::Badass);
+            }
+            if bombers.enabled {
+                for _ in 0..(extra_robots as f32 * 0.1) as u32 {
+                    robot_types.push(RobotType::Bomber);
+                }
+            }
+        }
+        _ => {}
+    };
+    let unscaled_robots = total_robots;
+    total_robots =
+        ((total_robots as f32 * difficulty.robot_count_multiplier()).round() as u32).max(1);
+    // Density goes last so the map's capacity has the final say over the difficulty's count.
+    if density.enabled {
+        let walkable_tiles = map
+            .base
+            .tiles
+            .iter()
+            .filter(|t| **t == TileType::Floor)
+            .count();
+        let level_scale = total_robots as f32 / base_robots as f32;
+        total_robots = density.robot_count(walkable_tiles, level_scale);
+    }
+    if total_robots != unscaled_robots {
+        robot_types.shuffle(rng);
+        robot_types = robot_types
+            .iter()
+            .cycle()
+            .take(total_robots as usize)
+            .cloned()
+            .collect();
+    }
+    robot_types.shuffle(rng);
+    let avoided_area = areas.iter().find(|a| a.contains(avoid));
+    let areas = areas
+        .iter()
+        .cloned()
+        .filter(|a| Some(a) != avoided_area)
+        .collect::<Vec<Area>>();
+    if !areas.is_empty() {
+        let mut spawned_robots = 0;
+        let mut candidate_areas = areas.clone();
+        candidate_areas.shuffle(rng);
+        let mut all_robot_coords: Vec<(usize, usize)> = vec![];
+        while spawned_robots < total_robots {
+            let area = candidate_areas[0].clone();
+            candidate_areas.remove(0);
+            if candidate_areas.is_empty() {
+                candidate_areas = areas.clone();
+                candidate_areas.shuffle(rng);
+            }
+            let mut robot_coords = (
+                rng.gen_range(area.rect.x1..area.rect.x2),
+                rng.gen_range(area.rect.y1..area.rect.y2),
+            );
+            while all_robot_coords.contains(&robot_coords) {
+                robot_coords = (
+                    rng.gen_range(area.rect.x1..area.rect.x2),
+                    rng.gen_range(area.rect.y1..area.rect.y2),
+                );
+            }
+            all_robot_coords.push(robot_coords);
+            if let Some(robot_type) = robot_types.pop() {
+                let name;
+                match robot_type {
+                    RobotType::Dumbass => {
+                        counts.dumbass += 1;
+                        name = Name::new(localization.format(
+                            "robot.name",
+                            &[&localization.get("robot_type.dumbass"), &counts.dumbass],
+                        ));
+                    }
+                    RobotType::Jackass => {
+                        counts.jackass += 1;
+                        name = Name::new(localization.format(
+                            "robot.name",
+                            &[&localization.get("robot_type.jackass"), &counts.jackass],
+                        ));
+                    }
+                    RobotType::Badass => {
+                        counts.badass += 1;
+                        name = Name::new(localization.format(
+                            "robot.name",
+                            &[&localization.get("robot_type.badass"), &counts.badass],
+                        ));
+                    }
+                    RobotType::Bomber => {
+                        counts.bomber += 1;
+                        name = Name::new(localization.format(
+                            "robot.name",
+                            &[&localization.get("robot_type.bomber"), &counts.bomber],
+                        ));
+                    }
+                };
+                let coordinates: Coordinates = robot_coords.into();
+                let route = patrol_route(&area, &areas, rng);
+                let entity_id = commands
+                    .spawn()
+                    .insert_robot(&robot_type, difficulty, curve, level)
+                    .insert(name)
+                    .insert(coordinates)
+                    .insert(route)
+                    .id();
+                commands.entity(map_entity).push_children(&[entity_id]);
+            }
+            spawned_robots += 1;
+        }
+    }
+    total_robots
+}
+
 fn spawn_robots(
     mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    curve: Res<DifficultyCurve>,
+    density: Res<RobotDensityConfig>,
+    bombers: Res<BomberConfig>,
+    mut spawned: ResMut<SpawnedRobots>,
+    level_seed: Res<LevelSeed>,
     level: Query<&Level>,
     map: Query<(Entity, &Map, &Areas), Added<Areas>>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
 ) {
     if let Ok(level) = level.single() {
         if let Ok((entity, map, areas)) = map.single() {
-            let base_robots = 20;
-            let extra_robots = (**level - 1) * 10;
-            let total_robots = base_robots + extra_robots;
-            let mut robot_types = vec![RobotType::Dumbass; base_robots as usize];
-            match **level {
-                2 => {
-                    for _ in 0..5 {
-                        robot_types.push(RobotType::Dumbass);
-                    }
-                    for _ in 5..10 {
-                        robot_types.push(RobotType::Jackass);
-                    }
-                }
-                v if v > 2 => {
-                    for _ in 0..(extra_robots as f32 * 0.3) as u32 {
-                        robot_types.push(RobotType::Dumbass);
-                    }
-                    for _ in 0..(extra_robots as f32 * 0.5) as u32 {
-                        robot_types.push(RobotType::Jackass);
-                    }
-                    for _ in 0..(extra_robots as f32 * 0.2) as u32 {
-                        robot_types.push(RobotType::Badass);
-                    }
-                }
-                _ => {}
-            };
             if let Some(start) = map.start() {
-                let mut rng = thread_rng();
-                robot_types.shuffle(&mut rng);
-                let starting_area = areas.iter().find(|a| a.contains(&start)).unwrap();
-                let areas = areas
-                    .iter()
-                    .cloned()
-                    .filter(|a| a != starting_area)
-                    .collect::<Vec<Area>>();
-                let mut spawned_robots = 0;
-                let mut candidate_areas = areas.clone();
-                candidate_areas.shuffle(&mut rng);
-                let mut all_robot_coords: Vec<(usize, usize)> = vec![];
-                let mut dumbass_count = 0;
-                let mut jackass_count = 0;
-                let mut badass_count = 0;
-                while spawned_robots < total_robots {
-                    let area = candidate_areas[0].clone();
-                    candidate_areas.remove(0);
-                    if candidate_areas.is_empty() {
-                        candidate_areas = areas.clone();
-                        candidate_areas.shuffle(&mut rng);
-                    }
-                    let mut robot_coords = (
-                        rng.gen_range(area.rect.x1..area.rect.x2),
-                        rng.gen_range(area.rect.y1..area.rect.y2),
+                let mut rng = StdRng::seed_from_u64(**level_seed);
+                let mut counts = RobotCounts::default();
+                let total_robots = spawn_wave(
+                    &mut commands,
+                    &mut rng,
+                    entity,
+                    map,
+                    areas,
+                    &start,
+                    **level,
+                    &difficulty,
+                    &curve,
+                    &density,
+                    &bombers,
+                    &mut counts,
+                    &localization,
+                );
+                commands.entity(entity).insert(counts).insert(Wave(1));
+                **spawned = total_robots;
+                if let Ok(mut log) = log.single_mut() {
+                    log.push(localization.format_plural(
+                        "robots.remaining",
+                        total_robots as usize,
+                        &[&total_robots],
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// Points awarded per survival wave reached, multiplied by the wave number.
+const WAVE_BONUS: u32 = 250;
+
+// The current survival wave, kept on the map so it resets along with it.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct Wave(pub u32);
+
+// Sent when a survival player reaches the exit and should face another wave.
+pub struct NextWave;
+
+fn next_wave(
+    mut commands: Commands,
+    mut events: EventReader<NextWave>,
+    difficulty: Res<Difficulty>,
+    curve: Res<DifficultyCurve>,
+    density: Res<RobotDensityConfig>,
+    bombers: Res<BomberConfig>,
+    mut spawned: ResMut<SpawnedRobots>,
+    level_seed: Res<LevelSeed>,
+    level: Query<&Level>,
+    mut map: Query<(Entity, &Map, &Areas, &mut RobotCounts, &mut Wave)>,
+    mut player: Query<(&Player, &Coordinates, &mut Score)>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+) {
+    for _ in events.iter() {
+        if let Ok(level) = level.single() {
+            if let Ok((entity, map, areas, mut counts, mut wave)) = map.single_mut() {
+                if let Ok((_, coordinates, mut score)) = player.single_mut() {
+                    **wave += 1;
+                    let mut rng = StdRng::seed_from_u64(level_seed.wrapping_add(**wave as u64));
+                    // Each wave is built as though it were a later level.
+                    let total_robots = spawn_wave(
+                        &mut commands,
+                        &mut rng,
+                        entity,
+                        map,
+                        areas,
+                        coordinates,
+                        **level + **wave - 1,
+                        &difficulty,
+                        &curve,
+                        &density,
+                        &bombers,
+                        &mut counts,
+                        &localization,
                     );
-                    while all_robot_coords.contains(&robot_coords) {
-                        robot_coords = (
-                            rng.gen_range(area.rect.x1..area.rect.x2),
-                            rng.gen_range(area.rect.y1..area.rect.y2),
+                    **spawned += total_robots;
+                    let bonus = WAVE_BONUS * **wave;
+                    **score += bonus;
+                    if let Ok(mut log) = log.single_mut() {
+                        log.push_with_priority(
+                            localization.format_plural(
+                                "level.wave",
+                                total_robots as usize,
+                                &[&**wave, &total_robots, &bonus],
+                            ),
+                            LogPriority::Urgent,
                         );
                     }
-                    all_robot_coords.push(robot_coords);
-                    if let Some(robot_type) = robot_types.pop() {
-                        let name;
-                        match robot_type {
-                            RobotType::Dumbass => {
-                                dumbass_count += 1;
-                                name = Name::new(format!("Dumbass {}", dumbass_count));
-                            }
-                            RobotType::Jackass => {
-                                jackass_count += 1;
-                                name = Name::new(format!("Jackass {}", jackass_count));
-                            }
-                            RobotType::Badass => {
-                                badass_count += 1;
-                                name = Name::new(format!("Badass {}", badass_count));
-                            }
-                        };
-                        let coordinates: Coordinates = robot_coords.into();
-                        let entity_id = commands
-                            .spawn()
-                            .insert_robot(&robot_type)
-                            .insert(name)
-                            .insert(coordinates)
-                            .id();
-                        commands.entity(entity).push_children(&[entity_id]);
-                    }
-                    spawned_robots += 1;
                 }
             }
-            if let Ok(mut log) = log.single_mut() {
-                let robot_or_robots = if total_robots == 1 { "robot" } else { "robots" };
-                log.push(format!("{} {} remaining.", total_robots, robot_or_robots));
-            }
         }
     }
 }
 
 fn position_player_at_start(
-    mut player: Query<(&Player, &mut Coordinates, &mut Transform)>,
+    mut player: Query<(&Player, Entity, &mut Coordinates, &mut Transform)>,
     map: Query<(&Map, &Areas), Added<Areas>>,
+    mut area_changed: EventWriter<AreaChanged>,
 ) {
     if let Ok((map, areas)) = map.single() {
         if let Some(start) = map.start() {
-            if let Ok((_, mut coordinates, mut transform)) = player.single_mut() {
+            if let Ok((_, entity, mut coordinates, mut transform)) = player.single_mut() {
                 for area in areas.iter() {
                     if area.contains(&start) {
                         *coordinates = area.center().into();
                         transform.rotation = Quat::from_rotation_z(PI / 2.);
+                        area_changed.send(AreaChanged {
+                            entity,
+                            from: None,
+                            to: area.clone(),
+                        });
                     }
                 }
             }
@@ -263,9 +689,14 @@ fn spawn_level_exit(
             if let Some(exit_area) = areas.iter().find(|a| a.contains(&exit)) {
                 let sound = SoundIcon {
                     sound: sfx.level_exit,
-                    gain: 2.,
+                    gain: LEVEL_EXIT_GAIN,
                     ..Default::default()
                 };
+                let base_interval = sound
+                    .interval
+                    .as_ref()
+                    .map(|v| v.duration().as_secs_f32())
+                    .unwrap_or_default();
                 let center = exit_area.center();
                 let center = (center.0 as f32, center.1 as f32);
                 let exit_entity = commands
@@ -275,6 +706,7 @@ fn spawn_level_exit(
                     .insert(Transform::default())
                     .insert(MonitorsCollisions)
                     .insert(LevelExit)
+                    .insert(BaseInterval(base_interval))
                     .id();
                 commands.entity(entity).push_children(&[exit_entity]);
             }
@@ -282,15 +714,35 @@ fn spawn_level_exit(
     }
 }
 
+fn exit_beacon(
+    config: Res<ExitBeaconConfig>,
+    player: Query<(&Player, &Coordinates)>,
+    mut exits: Query<(&LevelExit, &Coordinates, &BaseInterval, &mut SoundIcon)>,
+) {
+    if let Ok((_, player_coordinates)) = player.single() {
+        for (_, coordinates, base_interval, mut icon) in exits.iter_mut() {
+            let distance = player_coordinates.distance(coordinates);
+            icon.gain = config.gain(LEVEL_EXIT_GAIN, distance);
+            if let Some(ref mut interval) = icon.interval {
+                let seconds = config.interval(**base_interval, distance);
+                interval.set_duration(Duration::from_secs_f32(seconds));
+            }
+        }
+    }
+}
+
 fn exit_post_processor(
     mut commands: Commands,
     sfx: Res<Sfx>,
+    localization: Res<Localization>,
     mut map: Query<(&mut Map, &mut MotionBlocked, &mut VisibilityBlocked)>,
     exits: Query<(Entity, &Exit, &Coordinates), Added<Exit>>,
 ) {
     if let Ok((mut map, mut motion_blocked, mut visibility_blocked)) = map.single_mut() {
         for (entity, _, coordinates) in exits.iter() {
-            commands.entity(entity).insert(Name::new("Exit"));
+            commands
+                .entity(entity)
+                .insert(Name::new(localization.get("exit.name")));
             commands.entity(entity).insert(SoundIcon {
                 sound: sfx.exit,
                 gain: 0.4,
@@ -320,19 +772,14 @@ enum NextExitMsg {
 
 fn highlight_next_exit(
     mut commands: Commands,
-    mut cache: Local<Option<Area>>,
-    mut events: EventReader<Reset>,
-    player: Query<(&Player, &Coordinates)>,
-    map: Query<(&Areas, &Map)>,
+    mut events: EventReader<AreaChanged>,
+    map: Query<&Map>,
     exits: Query<(Entity, &Exit, &Coordinates)>,
     next_exit: Query<(Entity, &NextExit, &Coordinates)>,
     pool: Res<AsyncComputeTaskPool>,
     mut sender: Local<Option<Sender<NextExitMsg>>>,
     mut receiver: Local<Option<Receiver<NextExitMsg>>>,
 ) {
-    for _ in events.iter() {
-        *cache = None;
-    }
     if sender.is_none() {
         let (tx, rx) = unbounded();
         *sender = Some(tx);
@@ -364,43 +811,75 @@ fn highlight_next_exit(
             }
         }
     }
-    if let Ok((_, coordinates)) = player.single() {
-        if let Ok((areas, map)) = map.single() {
-            if let Some(current_area) = areas.iter().find(|a| a.contains(coordinates)) {
-                let recalculate;
-                if let Some(cached_area) = &*cache {
-                    if current_area == cached_area {
-                        return;
-                    } else {
-                        *cache = Some(current_area.clone());
-                        recalculate = true;
-                    }
-                } else {
-                    *cache = Some(current_area.clone());
-                    recalculate = true;
-                }
-                if recalculate {
-                    let start = current_area.center();
-                    let map_clone = map.clone();
-                    if let Some(sender) = sender.clone() {
-                        pool.spawn(async move {
-                            if let Some(destination) = map_clone.exit() {
-                                if let Some(result) = find_path(&start, &destination, &map_clone) {
-                                    let path = result.0;
-                                    sender.send(NextExitMsg::Path(path)).unwrap();
-                                } else {
-                                    sender.send(NextExitMsg::NoPath).unwrap();
-                                }
-                            }
-                        })
-                        .detach();
+    for AreaChanged {
+        to: current_area, ..
+    } in events.iter()
+    {
+        if let Ok(map) = map.single() {
+            let start = current_area.center();
+            let map_clone = map.clone();
+            if let Some(sender) = sender.clone() {
+                pool.spawn(async move {
+                    if let Some(destination) = map_clone.exit() {
+                        if let Some(result) = find_path(&start, &destination, &map_clone) {
+                            let path = result.0;
+                            sender.send(NextExitMsg::Path(path)).unwrap();
+                        } else {
+                            sender.send(NextExitMsg::NoPath).unwrap();
+                        }
                     }
-                }
+                })
+                .detach();
             }
         }
     }
 }
 
+fn face_exit(
+    input: Res<InputMap<String>>,
+    mut player: Query<(&Player, &Coordinates, &mut Transform)>,
+    next_exit: Query<(&NextExit, &Coordinates)>,
+    exits: Query<(&Exit, &Coordinates)>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !input.just_active(FACE_EXIT) {
+        return Ok(());
+    }
+    if let Ok((_, coordinates, mut transform)) = player.single_mut() {
+        let next_exit = next_exit.single().map(|(_, c)| *c).ok();
+        let exits = exits.iter().map(|(_, c)| *c);
+        if !face_nearest_exit(coordinates, &mut transform, next_exit, exits) {
+            tts.speak(localization.get("level.no_exit"), true)?;
+        }
+    }
+    Ok(())
+}
+
+// Prefers the exit the player is heading for, then the closest one.
+fn face_nearest_exit(
+    coordinates: &Coordinates,
+    transform: &mut Transform,
+    next_exit: Option<Coordinates>,
+    exits: impl Iterator<Item = Coordinates>,
+) -> bool {
+    let exit = next_exit.or_else(|| {
+        exits.min_by(|a, b| {
+            coordinates
+                .distance(a)
+                .partial_cmp(&coordinates.distance(b))
+                .unwrap()
+        })
+    });
+    if let Some(exit) = exit {
+        let bearing = coordinates.bearing(&exit);
+        transform.rotation = Quat::from_rotation_z(bearing);
+        true
+    } else {
+        false
+    }
+}
+
 fn next_exit_added(
     sfx: Res<Sfx>,
     mut next_exit: Query<(&NextExit, &mut SoundIcon), Added<NextExit>>,
@@ -424,6 +903,9 @@ fn next_exit_removed(
 
 fn collision(
     mut commands: Commands,
+    time: Res<Time>,
+    cooldown: Res<CollisionCueCooldown>,
+    mut last_cue: Local<Option<f64>>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
     mut collisions: EventReader<Collision>,
@@ -431,6 +913,7 @@ fn collision(
     state: Res<State<AppState>>,
     robots: Query<(&Robot, &Name)>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     map: Query<(Entity, &Map)>,
     mut life_lost: EventWriter<LifeLost>,
     mut wall_collisions: EventWriter<WallCollision>,
@@ -450,6 +933,9 @@ fn collision(
                             commands
                                 .entity(player_entity)
                                 .insert(WallCollisionTimer::default());
+                            if !cue_ready(&mut last_cue, time.seconds_since_startup(), **cooldown) {
+                                continue;
+                            }
                             let buffer = buffers.get_handle(sfx.wall_power_up);
                             let sound_id = commands
                                 .spawn()
@@ -466,7 +952,13 @@ fn collision(
                         for entity in &map.entities[event.coordinates.to_index(map.width())] {
                             if let Ok((_, name)) = robots.get(*entity) {
                                 life_lost.send(LifeLost);
-                                log.push(format!("You ran into a very irate {}.", **name));
+                                commands
+                                    .entity(player_entity)
+                                    .insert(DamageSource(event.coordinates.into()));
+                                log.push_with_priority(
+                                    localization.format("level.irate_robot", &[&**name]),
+                                    LogPriority::Urgent,
+                                );
                             }
                         }
                     }
@@ -479,19 +971,33 @@ fn collision(
 fn wall_collide(
     mut commands: Commands,
     time: Res<Time>,
-    mut player: Query<(Entity, &mut WallCollisionTimer, &Lives)>,
+    cooldown: Res<CollisionCueCooldown>,
+    mut last_message: Local<Option<f64>>,
+    penalty: Res<WallPenaltyMode>,
+    mut player: Query<(Entity, &mut WallCollisionTimer, &Lives, &mut Score)>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     mut life_lost: EventWriter<LifeLost>,
 ) {
-    for (entity, mut timer, lives) in player.iter_mut() {
+    for (entity, mut timer, lives, mut score) in player.iter_mut() {
         timer.tick(time.delta());
         if timer.finished() {
             commands.entity(entity).remove::<WallCollisionTimer>();
-            if **lives > 0 {
-                life_lost.send(LifeLost);
+            match *penalty {
+                WallPenaltyMode::LifeLoss => {
+                    if **lives > 0 {
+                        life_lost.send(LifeLost);
+                    }
+                }
+                WallPenaltyMode::ScoreLoss(penalty) => {
+                    **score = score.saturating_sub(penalty);
+                }
+                WallPenaltyMode::SoundOnly => {}
             }
-            if let Ok(mut log) = log.single_mut() {
-                log.push("Wall! Wall! You ran into a wall!");
+            if cue_ready(&mut last_message, time.seconds_since_startup(), **cooldown) {
+                if let Ok(mut log) = log.single_mut() {
+                    log.push(localization.get("level.wall"));
+                }
             }
         }
     }
@@ -511,10 +1017,33 @@ fn level_up(
     exit: Query<(&LevelExit, &Coordinates)>,
     mut state: ResMut<State<AppState>>,
     robot_coordinates: Query<(&Robot, &Coordinates)>,
+    rule: Res<ClearToExitRule>,
+    spawned: Res<SpawnedRobots>,
+    localization: Res<Localization>,
+    mut log: Query<&mut Log>,
+    mut announced: Local<bool>,
+    mode: Res<GameMode>,
+    mut next_wave: EventWriter<NextWave>,
+    mut wave_started: Local<bool>,
 ) -> Result<(), Box<dyn Error>> {
     for (_, player_coordinates, viewshed) in player.iter() {
         for (_, exit_coordinates) in exit.iter() {
             if player_coordinates.distance(exit_coordinates) < 5. {
+                let remaining = robot_coordinates.iter().len() as u32;
+                let kills_remaining = rule.kills_remaining(**spawned, remaining);
+                if kills_remaining > 0 {
+                    if !*announced {
+                        *announced = true;
+                        if let Ok(mut log) = log.single_mut() {
+                            log.push(localization.format_plural(
+                                "level.kills_required",
+                                kills_remaining as usize,
+                                &[&kills_remaining],
+                            ));
+                        }
+                    }
+                    continue;
+                }
                 let mut can_advance = true;
                 for (_, robot_coordinates) in robot_coordinates.iter() {
                     if viewshed.is_visible(robot_coordinates) {
@@ -523,8 +1052,20 @@ fn level_up(
                     }
                 }
                 if can_advance {
-                    state.push(AppState::LevelUp)?;
+                    match *mode {
+                        GameMode::Standard => state.push(AppState::LevelUp)?,
+                        // Only one wave per visit, so lingering at the exit doesn't flood the map.
+                        GameMode::Survival => {
+                            if !*wave_started {
+                                *wave_started = true;
+                                next_wave.send(NextWave);
+                            }
+                        }
+                    }
                 }
+            } else {
+                *announced = false;
+                *wave_started = false;
             }
         }
     }
@@ -535,6 +1076,7 @@ fn level_up_enter(
     level: Query<&Level>,
     mut lives: Query<&mut Lives>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     robots: Query<&Robot>,
     score: Query<&Score>,
 ) {
@@ -545,14 +1087,11 @@ fn level_up_enter(
         if let Ok(mut log) = log.single_mut() {
             if let Ok(score) = score.single() {
                 let robot_count = robots.iter().len();
-                let robot_or_robots = if robot_count == 1 { "robot" } else { "robots" };
-                log.push(format!(
-                "Congratulations! Your score is {}, and you left {} {} behind. You've earned an extra life! Press Enter to continue to level {}.",
-                **score,
-                robot_count,
-                robot_or_robots,
-                **level + 1,
-            ));
+                log.push(localization.format_plural(
+                    "level.complete",
+                    robot_count,
+                    &[&**score, &robot_count, &(**level + 1)],
+                ));
             }
         }
     }
@@ -575,17 +1114,35 @@ fn level_up_update(
     Ok(())
 }
 
+pub const POSITION_PLAYER_AT_START_LABEL: &str = "POSITION_PLAYER_AT_START";
+
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut AppBuilder) {
         const HIGHLIGHT_NEXT_EXIT_LABEL: &str = "HIGHLIGHT_NEXT_EXIT";
-        app.add_event::<WallCollision>()
+        app.add_event::<NextWave>()
+            .add_event::<WallCollision>()
+            .init_resource::<ClearToExitRule>()
+            .init_resource::<CollisionCueCooldown>()
+            .init_resource::<ExitBeaconConfig>()
+            .init_resource::<LevelSeed>()
+            .init_resource::<LevelSeedOverride>()
+            .init_resource::<CaveConfig>()
+            .init_resource::<SpawnedRobots>()
+            .init_resource::<RobotDensityConfig>()
+            .init_resource::<WallPenaltyMode>()
             .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup_level.system()))
             .add_system(spawn_ambience.system())
             .add_system(spawn_robots.system())
-            .add_system(position_player_at_start.system())
+            .add_system(next_wave.system())
+            .add_system(
+                position_player_at_start
+                    .system()
+                    .label(POSITION_PLAYER_AT_START_LABEL),
+            )
             .add_system(spawn_level_exit.system())
+            .add_system(exit_beacon.system())
             .add_system(
                 exit_post_processor
                     .system()
@@ -606,7 +1163,8 @@ impl Plugin for LevelPlugin {
             )
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
-                    .with_system(level_up.system().chain(error_handler.system())),
+                    .with_system(level_up.system().chain(error_handler.system()))
+                    .with_system(face_exit.system().chain(error_handler.system())),
             )
             .add_system_set(
                 SystemSet::on_enter(AppState::LevelUp).with_system(level_up_enter.system()),
@@ -617,3 +1175,263 @@ impl Plugin for LevelPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::Events;
+    use blackout::mapgen::geometry::Rect;
+
+    use super::*;
+    use crate::testing::{open_map, sent, spawn_level, test_world};
+
+    #[test]
+    fn facing_the_exit_turns_toward_it() {
+        let player = Coordinates((2., 2.));
+        let exits = vec![Coordinates((10., 2.)), Coordinates((2., 5.))];
+        let mut transform = Transform::default();
+        assert!(face_nearest_exit(
+            &player,
+            &mut transform,
+            None,
+            exits.clone().into_iter()
+        ));
+        assert!((transform.local_x() - Vec3::Y).length() < 0.001);
+        assert!(face_nearest_exit(
+            &player,
+            &mut transform,
+            Some(exits[0]),
+            exits.clone().into_iter()
+        ));
+        assert!((transform.local_x() - Vec3::X).length() < 0.001);
+        assert!(!face_nearest_exit(
+            &player,
+            &mut transform,
+            None,
+            std::iter::empty()
+        ));
+    }
+
+    fn ambience_for(difficulty: Difficulty) -> (usize, f32) {
+        let mut world = test_world();
+        world.insert_resource(Sfx::default());
+        world.insert_resource(difficulty);
+        world.insert_resource(AmbienceConfig {
+            scale_with_difficulty: true,
+        });
+        let areas = (0..12)
+            .map(|i| Area {
+                rect: Rect::new(i * 3, 0, 2, 2),
+                description: None,
+                light: 1.,
+            })
+            .collect();
+        world
+            .spawn()
+            .insert(Map::new(mapgen::Map::new(36, 3)))
+            .insert(Areas(areas));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(spawn_ambience.system());
+        stage.run(&mut world);
+        let gains = world
+            .query::<&Sound>()
+            .iter(&world)
+            .map(|sound| sound.gain)
+            .collect::<Vec<_>>();
+        (gains.len(), gains[0])
+    }
+
+    #[test]
+    fn hard_ambience_is_denser_and_louder_than_easy() {
+        let (easy_count, easy_gain) = ambience_for(Difficulty::Easy);
+        let (hard_count, hard_gain) = ambience_for(Difficulty::Hard);
+        assert!(hard_count > easy_count);
+        assert!(hard_gain > easy_gain);
+    }
+
+    #[test]
+    fn exit_icon_gets_louder_as_the_player_approaches() {
+        let mut world = World::default();
+        world.insert_resource(ExitBeaconConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((30., 0.)))
+            .id();
+        let exit = world
+            .spawn()
+            .insert(LevelExit)
+            .insert(Coordinates((0., 0.)))
+            .insert(BaseInterval(3.))
+            .insert(SoundIcon::default())
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(exit_beacon.system());
+        let mut gains = vec![];
+        for x in [30., 15., 5.].iter() {
+            *world.get_mut::<Coordinates>(player).unwrap() = Coordinates((*x, 0.));
+            stage.run(&mut world);
+            gains.push(world.get::<SoundIcon>(exit).unwrap().gain);
+        }
+        assert!(gains[0] < gains[1]);
+        assert!(gains[1] < gains[2]);
+    }
+
+    fn sustained_wall_contact(penalty: WallPenaltyMode) -> (usize, usize) {
+        let mut world = test_world();
+        world.insert_resource(penalty);
+        world.insert_resource(CollisionCueCooldown::default());
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Localization::default());
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(Events::<Collision>::default());
+        world.insert_resource(Events::<WallCollision>::default());
+        world.insert_resource(Events::<LifeLost>::default());
+        let mut map = open_map(5, 5);
+        map.base.tiles[(3, 2).to_index(5)] = TileType::Wall;
+        let level = spawn_level(&mut world, map);
+        world.spawn().insert(Log::default());
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((2., 2.)))
+            .insert(Lives(3))
+            .insert(Score(100))
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(collision.system())
+            .add_system(wall_collide.system());
+        for _ in 0..3 {
+            world
+                .get_resource_mut::<Events<Collision>>()
+                .unwrap()
+                .send(Collision {
+                    entity: player,
+                    coordinates: (3., 2.),
+                    index: (3, 2).to_index(5),
+                });
+            stage.run(&mut world);
+            let mut timer = world.get_mut::<WallCollisionTimer>(player).unwrap();
+            let duration = timer.duration();
+            timer.tick(duration);
+            stage.run(&mut world);
+        }
+        let cues = world
+            .query_filtered::<&Parent, With<Sound>>()
+            .iter(&world)
+            .filter(|parent| ***parent == level)
+            .count();
+        (cues, sent::<LifeLost>(&world).len())
+    }
+
+    #[test]
+    fn sound_only_walls_cue_without_costing_lives() {
+        assert_eq!(sustained_wall_contact(WallPenaltyMode::SoundOnly), (1, 0));
+        assert_eq!(sustained_wall_contact(WallPenaltyMode::LifeLoss), (1, 3));
+    }
+
+    #[test]
+    fn full_clear_rule_holds_the_exit_while_robots_live() {
+        let mut world = World::default();
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(ClearToExitRule {
+            required_fraction: Some(1.),
+        });
+        world.insert_resource(SpawnedRobots(2));
+        world.insert_resource(Localization::default());
+        world.insert_resource(GameMode::Standard);
+        world.insert_resource(Events::<NextWave>::default());
+        let log = world.spawn().insert(Log::default()).id();
+        world
+            .spawn()
+            .insert(LevelExit)
+            .insert(Coordinates((4., 1.)));
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((40., 40.)))
+            .id();
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((2., 1.)))
+            .insert(Viewshed::default())
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system_set(State::<AppState>::get_driver())
+            .add_system(level_up.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        let current = |world: &World| {
+            world
+                .get_resource::<State<AppState>>()
+                .unwrap()
+                .current()
+                .clone()
+        };
+        assert_eq!(current(&world), AppState::InGame);
+        assert_eq!(world.get::<Log>(log).unwrap().len(), 1);
+        world.despawn(robot);
+        world.get_mut::<Coordinates>(player).unwrap().0 .0 = 3.;
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(current(&world), AppState::LevelUp);
+    }
+
+    #[test]
+    fn sustained_contact_cues_once_per_interval() {
+        let mut last = None;
+        // Contact every frame at 60 FPS for three seconds with a one-second cooldown.
+        let cues = (0..180)
+            .filter(|frame| cue_ready(&mut last, *frame as f64 / 60., 1.))
+            .count();
+        assert_eq!(cues, 3);
+    }
+
+    #[test]
+    fn robot_count_is_proportional_to_walkable_area() {
+        let config = RobotDensityConfig {
+            enabled: true,
+            robots_per_hundred_tiles: 2.,
+        };
+        assert_eq!(config.robot_count(500, 1.), 10);
+        assert_eq!(config.robot_count(1000, 1.), 20);
+        assert_eq!(config.robot_count(1000, 1.5), 30);
+        assert_eq!(config.robot_count(10, 1.), 1);
+    }
+
+    #[test]
+    fn rectangular_grid_places_rectangular_rooms() {
+        let dimensions = MapDimensions {
+            width_in_rooms: 3,
+            height_in_rooms: 2,
+            room_width: 8,
+            room_height: 4,
+        };
+        let mut builder = MapBuilder::new(dimensions.tile_width(), dimensions.tile_height());
+        builder.with(GridBuilder::new(
+            dimensions.width_in_rooms,
+            dimensions.height_in_rooms,
+            dimensions.room_width,
+            dimensions.room_height,
+            Some(1),
+        ));
+        let map = builder.build_with_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!((map.width, map.height), (48, 16));
+        assert_eq!(map.rooms.len(), 6);
+        for room in &map.rooms {
+            assert!(room.x2 - room.x1 > room.y2 - room.y1);
+            assert!(room.x2 < map.width && room.y2 < map.height);
+        }
+        let mut columns = map.rooms.iter().map(|room| room.x1).collect::<Vec<_>>();
+        columns.sort_unstable();
+        columns.dedup();
+        let mut rows = map.rooms.iter().map(|room| room.y1).collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        assert_eq!((columns.len(), rows.len()), (3, 2));
+    }
+}
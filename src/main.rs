@@ -5,11 +5,16 @@ use bevy::prelude::*;
 
 mod bonus;
 mod bullet;
+mod danger;
 mod ff;
 mod game;
 mod level;
 mod player;
 mod robot;
+mod save;
+mod scan;
+#[cfg(test)]
+mod testing;
 mod tilemap;
 
 fn main() {
@@ -5,31 +5,54 @@ use blackout::{
     bevy_input_actionmap::InputMap,
     bevy_openal::{Buffer, Listener, Sound, SoundState},
     bevy_tts::Tts,
-    core::{Angle, Area, Coordinates, MovementDirection, Player, PointLike},
+    core::{Angle, Area, CoordinateConfig, Coordinates, MovementDirection, Player, PointLike},
     derive_more::{Deref, DerefMut},
     error::error_handler,
     exploration::Mappable,
-    log::Log,
-    map::{Areas, Map},
-    navigation::{BlocksMotion, MaxSpeed, RotationSpeed, Speed, Velocity},
-    sound::{Footstep, FootstepBundle},
+    localization::Localization,
+    log::{Log, LogPriority},
+    map::{AreaChanged, Areas, Map},
+    navigation::{BlocksMotion, MaxSpeed, RotationSpeed, Speed, Sprinting, Velocity},
+    sound::{spawn_sound_at, Footstep, FootstepBundle, Heard, SoundIcon, SoundParams},
+    speech::SpeechQueue,
     visibility::{BlocksVisibility, Viewshed},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    bonus::BonusTimes,
-    bullet::{Bullet, BulletCommands, ShotRange, ShotSpeed, ShotTimer},
+    bonus::{AwardBonus, BonusTimes},
+    bullet::{Bullet, BulletCommands, BulletConfig, Ricochet, ShotRange, ShotSpeed, ShotTimer},
     game::{
-        AppState, Reset, Sfx, Sprites, SHOOT, SNAP_LEFT, SNAP_RIGHT, SPEAK_COORDINATES,
-        SPEAK_DIRECTION, SPEAK_HEALTH, SPEAK_LEVEL, SPEAK_ROBOT_COUNT, SPEAK_SCORE,
+        AppState, Difficulty, Reset, Sfx, Sprites, MELEE, RELOAD, SHOOT, SNAP_LEFT, SNAP_RIGHT,
+        SPEAK_AMMO, SPEAK_COORDINATES, SPEAK_DIRECTION, SPEAK_EFFICIENCY, SPEAK_HEALTH,
+        SPEAK_LEVEL, SPEAK_ORIENTATION, SPEAK_ROBOT_COUNT, SPEAK_SCORE, TOGGLE_CHECKPOINT_BEACON,
     },
     level::Level,
-    robot::{Robot, RobotKilled, RobotType},
+    robot::{CauseOfDeath, Robot, RobotKilled, RobotType},
 };
 
 #[derive(Clone, Debug, Deref, DerefMut)]
 struct BetweenLivesTimer(Timer);
 
+const MELEE_RANGE: f32 = 1.5;
+
+// Off until melee has its own sound rather than borrowing the bullet impact.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MeleeConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+struct MeleeTimer(Timer);
+
+impl Default for MeleeTimer {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(1., false);
+        timer.set_elapsed(timer.duration());
+        MeleeTimer(timer)
+    }
+}
+
 impl Default for BetweenLivesTimer {
     fn default() -> Self {
         BetweenLivesTimer(Timer::from_seconds(5., false))
@@ -37,11 +60,125 @@ impl Default for BetweenLivesTimer {
 }
 
 #[derive(Clone, Copy, Debug, Default)]
-struct Checkpoint(Coordinates, Quat);
+pub struct Checkpoint(pub Coordinates, pub Quat);
 
+#[derive(Clone, Copy, Debug, Default)]
+struct CheckpointBeacon;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CheckpointBeaconConfig {
+    pub enabled: bool,
+    pub gain: f32,
+}
+
+impl Default for CheckpointBeaconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gain: 0.1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SafeZonePolicy {
+    Disabled,
+    NoFiring,
+    FreeFiring,
+}
+
+impl Default for SafeZonePolicy {
+    fn default() -> Self {
+        SafeZonePolicy::Disabled
+    }
+}
+
+fn is_safe_zone(area: &Area, map: &Map) -> bool {
+    map.start().map_or(false, |start| area.contains(&start))
+}
+
+// The shot to fire under `policy`, or `None` if the safe zone forbids firing.
+fn safe_zone_shot(policy: SafeZonePolicy, in_safe_zone: bool) -> Option<Shoot> {
+    match policy {
+        SafeZonePolicy::NoFiring if in_safe_zone => None,
+        _ => Some(Shoot {
+            costless: in_safe_zone,
+        }),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct LifeLost;
 
+// Where the last damage to the player came from.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageSource(pub Coordinates);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DamageIndicatorConfig {
+    pub sound: bool,
+    pub rumble: bool,
+}
+
+// Bearing of the damage relative to the player's facing, counter-clockwise in radians.
+#[derive(Clone, Copy, Debug)]
+pub struct DamageIndicated(pub f32);
+
+pub fn damage_bearing(player: &Coordinates, facing: f32, source: &dyn PointLike) -> f32 {
+    let bearing = (source.y() - player.y()).atan2(source.x() - player.x()) - facing;
+    bearing.sin().atan2(bearing.cos())
+}
+
+// Minimum seconds between lost lives, so simultaneous hits only cost one.
 #[derive(Clone, Copy, Debug, Deref, DerefMut)]
+pub struct LifeLossCooldown(pub f32);
+
+impl Default for LifeLossCooldown {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KillConfirmationConfig {
+    pub chime: bool,
+    pub rumble: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KillConfirmed(pub f32);
+
+pub fn kill_confirmation_strength(robot_type: &RobotType) -> f32 {
+    (robot_type.points() as f32 / RobotType::Badass.points() as f32).clamp(0.1, 1.)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Magazine {
+    pub current: u32,
+    pub capacity: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MagazineConfig {
+    pub enabled: bool,
+    pub capacity: u32,
+    pub reload_duration: f32,
+}
+
+impl Default for MagazineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 12,
+            reload_duration: 1.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+struct Reloading(Timer);
+
+#[derive(Clone, Copy, Debug, Deref, DerefMut, Deserialize, Serialize)]
 pub struct Lives(pub u32);
 
 impl Default for Lives {
@@ -50,11 +187,105 @@ impl Default for Lives {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut, Deserialize, Serialize)]
 pub struct Score(pub u32);
 
+#[derive(Clone, Debug, Default)]
+pub struct ScoreCalloutConfig {
+    pub milestones: Vec<u32>,
+    pub interval: Option<f32>,
+}
+
+pub fn reached_milestone(milestones: &[u32], score: u32) -> Option<u32> {
+    milestones.iter().copied().filter(|m| *m <= score).max()
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScoreFloorRule {
+    Floor(u32),
+    SuddenDeath,
+}
+
+impl Default for ScoreFloorRule {
+    fn default() -> Self {
+        ScoreFloorRule::Floor(0)
+    }
+}
+
+impl ScoreFloorRule {
+    // Returns the new score, or `None` if the deduction ends the run.
+    pub fn deduct(&self, score: u32, amount: u32) -> Option<u32> {
+        match self {
+            ScoreFloorRule::Floor(floor) => {
+                if score <= *floor {
+                    Some(score)
+                } else {
+                    Some(score.saturating_sub(amount).max(*floor))
+                }
+            }
+            ScoreFloorRule::SuddenDeath => {
+                let score = score.saturating_sub(amount);
+                if score == 0 {
+                    None
+                } else {
+                    Some(score)
+                }
+            }
+        }
+    }
+}
+
+// Grace period after respawning during which the player can't lose a life.
+#[derive(Clone, Copy, Debug)]
+pub struct RespawnGraceConfig {
+    pub enabled: bool,
+    pub duration: f32,
+    pub gain: f32,
+}
+
+impl Default for RespawnGraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: 3.,
+            gain: 0.3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct Invulnerable(pub Timer);
+
+#[derive(Clone, Copy, Debug, Default)]
+struct InvulnerableCue;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SprintCueConfig {
+    pub sound: bool,
+    pub speak: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Shoot {
+    // Shots fired in a free-firing safe zone don't count against the score.
+    pub costless: bool,
+}
+
 #[derive(Clone, Copy, Debug, Default)]
-pub struct Shoot;
+pub struct ShotStats {
+    pub shots: u32,
+    pub kills: u32,
+}
+
+impl ShotStats {
+    pub fn hit_ratio(&self) -> f32 {
+        if self.shots == 0 {
+            0.
+        } else {
+            self.kills as f32 / self.shots as f32
+        }
+    }
+}
 
 #[derive(Bundle)]
 struct PlayerBundle {
@@ -118,6 +349,9 @@ fn spawn_player(
     sfx: Res<Sfx>,
     asset_server: Res<AssetServer>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    magazine: Res<MagazineConfig>,
+    difficulty: Res<Difficulty>,
+    localization: Res<Localization>,
 ) {
     let sprite_handle = asset_server.get_handle(sprites.player);
     commands
@@ -128,6 +362,12 @@ fn spawn_player(
             ..Default::default()
         })
         .insert_bundle(PlayerBundle::default())
+        .insert(Name::new(localization.get("player.name")))
+        .insert(Lives(difficulty.starting_lives()))
+        .insert(Magazine {
+            current: magazine.capacity,
+            capacity: magazine.capacity,
+        })
         .with_children(|parent| {
             parent.spawn().insert_bundle(FootstepBundle {
                 footstep: Footstep {
@@ -139,18 +379,90 @@ fn spawn_player(
         });
 }
 
+fn orientation_token(
+    localization: &Localization,
+    label: &str,
+    from: &Coordinates,
+    to: &dyn PointLike,
+) -> String {
+    let label = localization.get(label);
+    let location = localization.distance_and_direction(from, to);
+    if location.is_empty() {
+        localization.format("orientation.here", &[&label])
+    } else {
+        localization.format("orientation.location", &[&label, &location])
+    }
+}
+
+fn spoken_coordinates(
+    localization: &Localization,
+    config: &CoordinateConfig,
+    coordinates: &Coordinates,
+    map: &Map,
+    areas: Option<&Areas>,
+) -> String {
+    let (x, y) = config.spoken(coordinates, map.height());
+    let area = if config.include_area {
+        areas.and_then(|areas| {
+            areas
+                .iter()
+                .find(|a| a.contains(coordinates))
+                .and_then(|a| a.description.clone())
+        })
+    } else {
+        None
+    };
+    if let Some(area) = area {
+        localization.format("player.coordinates.area", &[&x, &y, &area])
+    } else {
+        localization.format("player.coordinates", &[&x, &y])
+    }
+}
+
+fn efficiency_readout(localization: &Localization, shot_stats: &ShotStats) -> String {
+    let shots = localization.format_plural(
+        "player.shots",
+        shot_stats.shots as usize,
+        &[&shot_stats.shots],
+    );
+    let kills = localization.format_plural(
+        "player.kills",
+        shot_stats.kills as usize,
+        &[&shot_stats.kills],
+    );
+    localization.format(
+        "player.efficiency",
+        &[
+            &shots,
+            &kills,
+            &((shot_stats.hit_ratio() * 100.).round() as u32),
+        ],
+    )
+}
+
 fn speak_info(
     input: Res<InputMap<String>>,
     mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
     player: Query<(&Player, &Coordinates, &Transform, &Lives, &Level, &Score)>,
     robots: Query<&Robot>,
+    map: Query<&Map>,
+    areas: Query<&Areas>,
+    shot_stats: Res<ShotStats>,
+    coordinate_config: Res<CoordinateConfig>,
 ) -> Result<(), Box<dyn Error>> {
     if input.just_active(SPEAK_COORDINATES) {
         if let Ok((_, coordinates, _, _, _, _)) = player.single() {
-            tts.speak(
-                format!("({}, {})", coordinates.x_i32(), coordinates.y_i32()),
-                true,
-            )?;
+            if let Ok(map) = map.single() {
+                let message = spoken_coordinates(
+                    &localization,
+                    &coordinate_config,
+                    coordinates,
+                    map,
+                    areas.single().ok(),
+                );
+                tts.speak(message, true)?;
+            }
         }
     }
     if input.just_active(SPEAK_DIRECTION) {
@@ -158,31 +470,63 @@ fn speak_info(
             let forward = transform.local_x();
             let yaw = Angle::Radians(forward.y.atan2(forward.x));
             let direction: MovementDirection = yaw.into();
-            tts.speak(format!("{}", direction), true)?;
+            tts.speak(localization.direction(direction), true)?;
         }
     }
+    if input.just_active(SPEAK_EFFICIENCY) {
+        tts.speak(efficiency_readout(&localization, &shot_stats), true)?;
+    }
     if input.just_active(SPEAK_HEALTH) {
         if let Ok((_, _, _, lives, _, _)) = player.single() {
-            let life_or_lives = if **lives != 1 { "lives" } else { "life" };
-            tts.speak(format!("{} {} left.", **lives, life_or_lives), true)?;
+            tts.speak(
+                localization.format_plural("player.lives", **lives as usize, &[&**lives]),
+                true,
+            )?;
         }
     }
     if input.just_active(SPEAK_LEVEL) {
         if let Ok((_, _, _, _, level, _)) = player.single() {
-            tts.speak(format!("Level {}", **level), true)?;
+            tts.speak(localization.format("player.level", &[&**level]), true)?;
+        }
+    }
+    if input.just_active(SPEAK_ORIENTATION) {
+        if let Ok((_, coordinates, _, _, _, _)) = player.single() {
+            if let Ok(map) = map.single() {
+                let mut tokens: Vec<String> = vec![];
+                if let Some(start) = map.start() {
+                    tokens.push(orientation_token(
+                        &localization,
+                        "orientation.start",
+                        coordinates,
+                        &start,
+                    ));
+                }
+                if let Some(exit) = map.exit() {
+                    tokens.push(orientation_token(
+                        &localization,
+                        "orientation.exit",
+                        coordinates,
+                        &exit,
+                    ));
+                }
+                if !tokens.is_empty() {
+                    tts.speak(tokens.join(". "), true)?;
+                }
+            }
         }
     }
     if input.just_active(SPEAK_SCORE) {
         if let Ok((_, _, _, _, _, score)) = player.single() {
-            let point_or_points = if **score == 1 { "point" } else { "points" };
-            tts.speak(format!("{} {}.", **score, point_or_points), true)?;
+            tts.speak(
+                localization.format_plural("player.score", **score as usize, &[&**score]),
+                true,
+            )?;
         }
     }
     if input.just_active(SPEAK_ROBOT_COUNT) {
         let robot_count = robots.iter().len();
-        let robot_or_robots = if robot_count == 1 { "robot" } else { "robots" };
         tts.speak(
-            format!("{} {} remaining.", robot_count, robot_or_robots),
+            localization.format_plural("robots.remaining", robot_count, &[&robot_count]),
             true,
         )?;
     }
@@ -226,6 +570,8 @@ fn snap(input: Res<InputMap<String>>, mut transform: Query<(&Player, &mut Transf
     }
 }
 
+const GUNSHOT_LOUDNESS: f32 = 20.;
+
 fn shoot(
     mut commands: Commands,
     time: Res<Time>,
@@ -240,17 +586,63 @@ fn shoot(
         &ShotSpeed,
     )>,
     mut shoot: EventWriter<Shoot>,
-    level: Query<(Entity, &Map)>,
+    mut heard: EventWriter<Heard>,
+    level: Query<(Entity, &Map, &Areas)>,
     sfx: Res<Sfx>,
     buffers: Res<Assets<Buffer>>,
+    safe_zone_policy: Res<SafeZonePolicy>,
+    magazine_config: Res<MagazineConfig>,
+    mut magazine: Query<(&mut Magazine, Option<&Reloading>), With<Player>>,
+    bullet_config: Res<BulletConfig>,
 ) {
     if let Ok((_, player_entity, coordinates, transform, mut timer, shot_range, shot_speed)) =
         player.single_mut()
     {
         timer.tick(time.delta());
         if input.active(SHOOT) && timer.finished() {
-            shoot.send(Shoot);
-            if let Ok((level_entity, _)) = level.single() {
+            let in_safe_zone = *safe_zone_policy != SafeZonePolicy::Disabled
+                && level.single().map_or(false, |(_, map, areas)| {
+                    areas
+                        .iter()
+                        .find(|a| a.contains(coordinates))
+                        .map_or(false, |a| is_safe_zone(a, map))
+                });
+            let shot = if let Some(shot) = safe_zone_shot(*safe_zone_policy, in_safe_zone) {
+                shot
+            } else {
+                return;
+            };
+            if magazine_config.enabled {
+                if let Ok((mut magazine, reloading)) = magazine.single_mut() {
+                    if reloading.is_some() {
+                        return;
+                    }
+                    if magazine.current == 0 {
+                        if let Ok((level_entity, _, _)) = level.single() {
+                            let click = commands
+                                .spawn()
+                                .insert(Sound {
+                                    buffer: buffers.get_handle(sfx.empty_click),
+                                    state: SoundState::Playing,
+                                    gain: 0.5,
+                                    pitch: 2.,
+                                    ..Default::default()
+                                })
+                                .id();
+                            commands.entity(level_entity).push_children(&[click]);
+                        }
+                        timer.reset();
+                        return;
+                    }
+                    magazine.current -= 1;
+                }
+            }
+            shoot.send(shot);
+            heard.send(Heard {
+                coordinates: *coordinates,
+                loudness: GUNSHOT_LOUDNESS,
+            });
+            if let Ok((level_entity, _, _)) = level.single() {
                 let shot_sound = commands
                     .spawn()
                     .insert(Sound {
@@ -271,6 +663,11 @@ fn shoot(
                         shot_range,
                     )
                     .id();
+                if bullet_config.player_ricochets > 0 {
+                    commands
+                        .entity(bullet)
+                        .insert(Ricochet(bullet_config.player_ricochets));
+                }
                 commands
                     .entity(level_entity)
                     .push_children(&[shot_sound, bullet]);
@@ -280,30 +677,282 @@ fn shoot(
     }
 }
 
+fn reload(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<InputMap<String>>,
+    config: Res<MagazineConfig>,
+    mut player: Query<(Entity, &mut Magazine, Option<&mut Reloading>), With<Player>>,
+    level: Query<(Entity, &Map)>,
+    sfx: Res<Sfx>,
+    buffers: Res<Assets<Buffer>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !config.enabled {
+        return Ok(());
+    }
+    if let Ok((entity, mut magazine, reloading)) = player.single_mut() {
+        if let Some(mut reloading) = reloading {
+            reloading.tick(time.delta());
+            if reloading.finished() {
+                magazine.capacity = config.capacity;
+                magazine.current = magazine.capacity;
+                commands.entity(entity).remove::<Reloading>();
+                tts.speak(
+                    localization.format_plural(
+                        "player.ammo",
+                        magazine.current as usize,
+                        &[&magazine.current, &magazine.capacity],
+                    ),
+                    true,
+                )?;
+            }
+        } else if input.just_active(RELOAD) && magazine.current < config.capacity {
+            commands
+                .entity(entity)
+                .insert(Reloading(Timer::from_seconds(
+                    config.reload_duration,
+                    false,
+                )));
+            if let Ok((level_entity, _)) = level.single() {
+                let sound = commands
+                    .spawn()
+                    .insert(Sound {
+                        buffer: buffers.get_handle(sfx.reload),
+                        state: SoundState::Playing,
+                        gain: 0.5,
+                        ..Default::default()
+                    })
+                    .id();
+                commands.entity(level_entity).push_children(&[sound]);
+            }
+            tts.speak(localization.get("player.reloading"), true)?;
+        }
+    }
+    Ok(())
+}
+
+fn speak_ammo(
+    input: Res<InputMap<String>>,
+    config: Res<MagazineConfig>,
+    player: Query<(&Magazine, Option<&Reloading>), With<Player>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !input.just_active(SPEAK_AMMO) {
+        return Ok(());
+    }
+    if !config.enabled {
+        tts.speak(localization.get("player.ammo.unlimited"), true)?;
+    } else if let Ok((magazine, reloading)) = player.single() {
+        if reloading.is_some() {
+            tts.speak(localization.get("player.reloading"), true)?;
+        } else {
+            tts.speak(
+                localization.format_plural(
+                    "player.ammo",
+                    magazine.current as usize,
+                    &[&magazine.current, &magazine.capacity],
+                ),
+                true,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+const DAMAGE_INDICATOR_DISTANCE: f32 = 2.;
+
+fn damage_indicator(
+    mut commands: Commands,
+    config: Res<DamageIndicatorConfig>,
+    player: Query<(&Player, &Coordinates, &Transform, &DamageSource), Changed<DamageSource>>,
+    level: Query<(Entity, &Map)>,
+    sfx: Res<Sfx>,
+    buffers: Res<Assets<Buffer>>,
+    mut indicated: EventWriter<DamageIndicated>,
+) {
+    if let Ok((_, coordinates, transform, DamageSource(source))) = player.single() {
+        let forward = transform.local_x();
+        let bearing = damage_bearing(coordinates, forward.y.atan2(forward.x), source);
+        if config.sound {
+            if let Ok((level_entity, _)) = level.single() {
+                // Place the sting a short way toward the source so it pans from that side.
+                let angle = (source.y() - coordinates.y()).atan2(source.x() - coordinates.x());
+                let position = (
+                    coordinates.x() + angle.cos() * DAMAGE_INDICATOR_DISTANCE,
+                    coordinates.y() + angle.sin() * DAMAGE_INDICATOR_DISTANCE,
+                );
+                spawn_sound_at(
+                    &mut commands,
+                    level_entity,
+                    buffers.get_handle(sfx.damage_indicator),
+                    &position,
+                    SoundParams {
+                        pitch: 1.3,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+        if config.rumble {
+            indicated.send(DamageIndicated(bearing));
+        }
+    }
+}
+
+fn sprint_cues(
+    mut commands: Commands,
+    config: Res<SprintCueConfig>,
+    player: Query<(Entity, Option<&Sprinting>), With<Player>>,
+    mut was_sprinting: Local<bool>,
+    sfx: Res<Sfx>,
+    buffers: Res<Assets<Buffer>>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if let Ok((entity, sprinting)) = player.single() {
+        let is_sprinting = sprinting.is_some();
+        if is_sprinting == *was_sprinting {
+            return;
+        }
+        *was_sprinting = is_sprinting;
+        if config.sound {
+            let sound = commands
+                .spawn()
+                .insert(Sound {
+                    buffer: buffers.get_handle(sfx.sprint),
+                    state: SoundState::Playing,
+                    gain: 0.3,
+                    pitch: if is_sprinting { 1.2 } else { 0.8 },
+                    ..Default::default()
+                })
+                .insert(Transform::default())
+                .insert(GlobalTransform::default())
+                .id();
+            commands.entity(entity).push_children(&[sound]);
+        }
+        if config.speak {
+            let message = if is_sprinting {
+                "player.sprint.on"
+            } else {
+                "player.sprint.off"
+            };
+            speech.push(localization.get(message));
+        }
+    }
+}
+
+fn safe_zone_announcement(
+    safe_zone_policy: Res<SafeZonePolicy>,
+    localization: Res<Localization>,
+    mut events: EventReader<AreaChanged>,
+    map: Query<&Map>,
+    mut log: Query<&mut Log>,
+) {
+    if *safe_zone_policy == SafeZonePolicy::Disabled {
+        return;
+    }
+    if let (Ok(map), Ok(mut log)) = (map.single(), log.single_mut()) {
+        for AreaChanged { from, to, .. } in events.iter() {
+            let was_safe = from.as_ref().map_or(false, |a| is_safe_zone(a, map));
+            let is_safe = is_safe_zone(to, map);
+            if is_safe && !was_safe {
+                log.push_with_priority(
+                    localization.get("player.safe_zone.enter"),
+                    LogPriority::Low,
+                );
+            } else if was_safe && !is_safe {
+                log.push_with_priority(
+                    localization.get("player.safe_zone.leave"),
+                    LogPriority::Low,
+                );
+            }
+        }
+    }
+}
+
 fn checkpoint(
     mut player: Query<(&Player, &Coordinates, &Transform, &mut Checkpoint)>,
-    mut events: EventReader<Reset>,
-    mut cache: Local<Option<Area>>,
-    areas: Query<&Areas>,
+    mut events: EventReader<AreaChanged>,
+    mut current: Local<Option<Area>>,
+    mut checkpoint_area: Local<Option<Area>>,
 ) {
-    for _ in events.iter() {
-        *cache = None;
-    }
     if let Ok((_, coordinates, transform, mut checkpoint)) = player.single_mut() {
-        if let Ok(areas) = areas.single() {
-            if let Some(cached_area) = &*cache {
-                if checkpoint.0.distance(&coordinates) > 5. {
-                    if let Some(current_area) = areas.iter().find(|a| a.contains(coordinates)) {
-                        if cached_area != current_area {
-                            *cache = Some(current_area.clone());
-                            *checkpoint = Checkpoint(*coordinates, transform.rotation);
-                        }
-                    }
-                }
-            } else if let Some(current_area) = areas.iter().find(|a| a.contains(coordinates)) {
-                *cache = Some(current_area.clone());
+        for event in events.iter() {
+            if event.from.is_none() {
+                *checkpoint_area = Some(event.to.clone());
                 *checkpoint = Checkpoint(*coordinates, transform.rotation);
             }
+            *current = Some(event.to.clone());
+        }
+        if *current != *checkpoint_area && checkpoint.0.distance(&coordinates) > 5. {
+            *checkpoint_area = current.clone();
+            *checkpoint = Checkpoint(*coordinates, transform.rotation);
+        }
+    }
+}
+
+fn toggle_checkpoint_beacon(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<CheckpointBeaconConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if input.just_active(TOGGLE_CHECKPOINT_BEACON) {
+        config.enabled = !config.enabled;
+        let message = if config.enabled {
+            "checkpoint_beacon.on"
+        } else {
+            "checkpoint_beacon.off"
+        };
+        speech.interrupt(localization.get(message));
+    }
+}
+
+fn checkpoint_beacon(
+    mut commands: Commands,
+    config: Res<CheckpointBeaconConfig>,
+    sfx: Res<Sfx>,
+    player: Query<&Checkpoint, With<Player>>,
+    moved: Query<&Checkpoint, (With<Player>, Changed<Checkpoint>)>,
+    mut beacon: Query<(Entity, &mut Coordinates), With<CheckpointBeacon>>,
+    map: Query<(Entity, &Map)>,
+    mut life_lost: EventReader<LifeLost>,
+    mut resets: EventReader<Reset>,
+    mut stale: Local<bool>,
+) {
+    // After a death or reset the old checkpoint is meaningless until a new one is set.
+    if life_lost.iter().count() > 0 || resets.iter().count() > 0 {
+        *stale = true;
+    } else if moved.single().is_ok() {
+        *stale = false;
+    }
+    if !config.enabled || *stale {
+        for (entity, _) in beacon.iter_mut() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    if let Ok(checkpoint) = player.single() {
+        if let Ok((_, mut coordinates)) = beacon.single_mut() {
+            if *coordinates != checkpoint.0 {
+                *coordinates = checkpoint.0;
+            }
+        } else if let Ok((map_entity, _)) = map.single() {
+            let beacon = commands
+                .spawn()
+                .insert(CheckpointBeacon)
+                .insert(checkpoint.0)
+                .insert(Transform::default())
+                .insert(SoundIcon {
+                    sound: sfx.checkpoint_beacon,
+                    gain: config.gain,
+                    ..Default::default()
+                })
+                .id();
+            commands.entity(map_entity).push_children(&[beacon]);
         }
     }
 }
@@ -314,10 +963,20 @@ fn life_loss(
     mut state: ResMut<State<AppState>>,
     asset_server: Res<AssetServer>,
     sfx: Res<Sfx>,
-    mut player: Query<(&Player, &mut Lives)>,
+    mut player: Query<(&Player, &mut Lives), Without<Invulnerable>>,
     map: Query<(Entity, &Map)>,
+    time: Res<Time>,
+    cooldown: Res<LifeLossCooldown>,
+    mut last_loss: Local<Option<f64>>,
 ) -> Result<(), Box<dyn Error>> {
     for _ in events.iter() {
+        let now = time.seconds_since_startup();
+        if let Some(last_loss) = *last_loss {
+            if now - last_loss < **cooldown as f64 {
+                continue;
+            }
+        }
+        *last_loss = Some(now);
         for (_, mut lives) in player.iter_mut() {
             **lives -= 1;
             let buffer = asset_server.get_handle(sfx.life_lost);
@@ -358,59 +1017,128 @@ fn despawn_player_bullets(
 }
 
 fn tick_between_lives_timer(
+    mut commands: Commands,
     time: Res<Time>,
     mut timer: ResMut<BetweenLivesTimer>,
     mut state: ResMut<State<AppState>>,
-    mut player: Query<(
-        &Player,
-        &Lives,
-        &Checkpoint,
-        &mut Coordinates,
-        &mut Transform,
-    )>,
+    grace: Res<RespawnGraceConfig>,
+    mut player: Query<
+        (
+            Entity,
+            &Lives,
+            &Checkpoint,
+            &mut Coordinates,
+            &mut Transform,
+        ),
+        With<Player>,
+    >,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
 ) -> Result<(), Box<dyn Error>> {
     timer.tick(time.delta());
     if timer.finished() {
         state.pop()?;
-        if let Ok((_, lives, checkpoint, mut coordinates, mut transform)) = player.single_mut() {
+        if let Ok((entity, lives, checkpoint, mut coordinates, mut transform)) = player.single_mut()
+        {
             if **lives == 0 {
                 state.overwrite_replace(AppState::GameOver)?;
             } else {
-                let life_or_lives = if **lives > 1 { "lives" } else { "life" };
                 if let Ok(mut log) = log.single_mut() {
-                    log.push(format!("{} {} left.", **lives, life_or_lives));
+                    log.push_with_priority(
+                        localization.format_plural("player.lives", **lives as usize, &[&**lives]),
+                        LogPriority::Urgent,
+                    );
                 }
                 **coordinates = *checkpoint.0;
                 transform.rotation = checkpoint.1;
+                if grace.enabled {
+                    commands
+                        .entity(entity)
+                        .insert(Invulnerable(Timer::from_seconds(grace.duration, false)));
+                }
             }
         }
     }
     Ok(())
 }
 
+fn invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<RespawnGraceConfig>,
+    buffers: Res<Assets<Buffer>>,
+    sfx: Res<Sfx>,
+    mut player: Query<(Entity, &mut Invulnerable), With<Player>>,
+    added: Query<Entity, (With<Player>, Added<Invulnerable>)>,
+    cues: Query<Entity, With<InvulnerableCue>>,
+) {
+    for entity in added.iter() {
+        let cue = spawn_sound_at(
+            &mut commands,
+            entity,
+            buffers.get_handle(sfx.invulnerable),
+            &(0., 0.),
+            SoundParams {
+                gain: config.gain,
+                pitch: 1.5,
+                looping: true,
+                ..Default::default()
+            },
+        );
+        commands.entity(cue).insert(InvulnerableCue);
+    }
+    for (entity, mut invulnerable) in player.iter_mut() {
+        invulnerable.tick(time.delta());
+        if invulnerable.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            for cue in cues.iter() {
+                commands.entity(cue).despawn_recursive();
+            }
+        }
+    }
+}
+
 fn score(
     mut score: Query<&mut Score>,
     mut shot: EventReader<Shoot>,
     mut shots_fired: Local<u8>,
     mut robot_kills: EventReader<RobotKilled>,
     active_bonuses: Query<&BonusTimes>,
-) {
+    rule: Res<ScoreFloorRule>,
+    mut state: ResMut<State<AppState>>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
     const SHOTS_PER_POINT: u8 = 5;
     if let Ok(mut score) = score.single_mut() {
-        for _ in shot.iter() {
+        for Shoot { costless } in shot.iter() {
+            if *costless {
+                continue;
+            }
             *shots_fired += 1;
             if **score > 0 && *shots_fired > SHOTS_PER_POINT {
-                **score -= 1;
                 *shots_fired = 0;
+                if let Some(new_score) = rule.deduct(**score, 1) {
+                    **score = new_score;
+                } else {
+                    **score = 0;
+                    if *state.current() == AppState::InGame {
+                        if let Ok(mut log) = log.single_mut() {
+                            log.push_with_priority(
+                                localization.get("score.depleted"),
+                                LogPriority::Urgent,
+                            );
+                        }
+                        state.overwrite_replace(AppState::GameOver)?;
+                    }
+                }
             }
         }
-        for RobotKilled(_, robot_type, _, _, _) in robot_kills.iter() {
-            let mut points: f32 = match robot_type {
-                RobotType::Dumbass => 10.,
-                RobotType::Jackass => 50.,
-                RobotType::Badass => 100.,
-            };
+        for RobotKilled(_, robot_type, _, _, cause) in robot_kills.iter() {
+            if !cause.player_caused() {
+                continue;
+            }
+            let mut points = robot_type.points() as f32;
             if let Ok(active_bonuses) = active_bonuses.single() {
                 if !active_bonuses.is_empty() {
                     for _ in &active_bonuses[1..] {
@@ -421,6 +1149,150 @@ fn score(
             **score += points as u32;
         }
     }
+    Ok(())
+}
+
+fn melee(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<InputMap<String>>,
+    config: Res<MeleeConfig>,
+    mut timer: ResMut<MeleeTimer>,
+    player: Query<(&Player, Entity, &Coordinates)>,
+    robots: Query<(&Robot, Entity, &Coordinates)>,
+    level: Query<&Map>,
+    mut robot_killed: EventWriter<RobotKilled>,
+    mut bonus: EventWriter<AwardBonus>,
+    sfx: Res<Sfx>,
+    buffers: Res<Assets<Buffer>>,
+) {
+    if !config.enabled {
+        return;
+    }
+    timer.tick(time.delta());
+    if !input.just_active(MELEE) || !timer.finished() {
+        return;
+    }
+    timer.reset();
+    if let (Ok((_, player_entity, coordinates)), Ok(map)) = (player.single(), level.single()) {
+        spawn_sound_at(
+            &mut commands,
+            player_entity,
+            buffers.get_handle(sfx.melee),
+            &(0., 0.),
+            SoundParams {
+                pitch: 0.6,
+                ..Default::default()
+            },
+        );
+        for (Robot(robot_type), entity, robot_coordinates) in robots.iter() {
+            if coordinates.distance(robot_coordinates) <= MELEE_RANGE {
+                robot_killed.send(RobotKilled(
+                    entity,
+                    *robot_type,
+                    *robot_coordinates,
+                    robot_coordinates.to_index(map.width()),
+                    CauseOfDeath::Melee,
+                ));
+                bonus.send(AwardBonus);
+            }
+        }
+    }
+}
+
+fn shot_stats(
+    mut stats: ResMut<ShotStats>,
+    mut resets: EventReader<Reset>,
+    mut shot: EventReader<Shoot>,
+    mut robot_kills: EventReader<RobotKilled>,
+    player: Query<Entity, With<Player>>,
+) {
+    for _ in resets.iter() {
+        *stats = ShotStats::default();
+    }
+    for _ in shot.iter() {
+        stats.shots += 1;
+    }
+    for RobotKilled(_, _, _, _, cause) in robot_kills.iter() {
+        if let CauseOfDeath::Bullet(owner) = cause {
+            if player.get(*owner).is_ok() {
+                stats.kills += 1;
+            }
+        }
+    }
+}
+
+fn confirm_kills(
+    mut commands: Commands,
+    config: Res<KillConfirmationConfig>,
+    sfx: Res<Sfx>,
+    buffers: Res<Assets<Buffer>>,
+    mut robot_kills: EventReader<RobotKilled>,
+    mut confirmations: EventWriter<KillConfirmed>,
+    player: Query<Entity, With<Player>>,
+) {
+    for RobotKilled(_, robot_type, _, _, cause) in robot_kills.iter() {
+        let killer = match cause {
+            CauseOfDeath::Bullet(owner) => player.get(*owner).ok(),
+            CauseOfDeath::Melee => player.single().ok(),
+            _ => None,
+        };
+        if let Some(player_entity) = killer {
+            let strength = kill_confirmation_strength(robot_type);
+            if config.rumble {
+                confirmations.send(KillConfirmed(strength));
+            }
+            if config.chime {
+                let chime = commands
+                    .spawn()
+                    .insert(Sound {
+                        buffer: buffers.get_handle(sfx.kill_confirmed),
+                        state: SoundState::Playing,
+                        gain: 0.2 + strength * 0.4,
+                        pitch: 0.8 + strength * 0.4,
+                        ..Default::default()
+                    })
+                    .insert(Transform::default())
+                    .insert(GlobalTransform::default())
+                    .id();
+                commands.entity(player_entity).push_children(&[chime]);
+            }
+        }
+    }
+}
+
+fn score_callouts(
+    time: Res<Time>,
+    config: Res<ScoreCalloutConfig>,
+    player: Query<(&Player, &Score)>,
+    mut last_milestone: Local<Option<u32>>,
+    mut timer: Local<Option<Timer>>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if let Ok((_, score)) = player.single() {
+        let milestone = reached_milestone(&config.milestones, **score);
+        if milestone > *last_milestone {
+            if let Some(milestone) = milestone {
+                speech.push(localization.format("score.milestone", &[&milestone]));
+            }
+        }
+        // Also catches the score dropping back down when a new game starts.
+        *last_milestone = milestone;
+        if let Some(interval) = config.interval {
+            let timer = timer.get_or_insert_with(|| Timer::from_seconds(interval, true));
+            timer.tick(time.delta());
+            if timer.just_finished() {
+                speech.push(localization.format_plural(
+                    "player.score",
+                    **score as usize,
+                    &[&**score],
+                ));
+            }
+        } else {
+            *timer = None;
+        }
+    }
 }
 
 pub struct PlayerPlugin;
@@ -428,10 +1300,25 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_resource::<BetweenLivesTimer>()
+            .init_resource::<MagazineConfig>()
+            .init_resource::<MeleeConfig>()
+            .init_resource::<MeleeTimer>()
+            .init_resource::<CheckpointBeaconConfig>()
+            .init_resource::<DamageIndicatorConfig>()
+            .init_resource::<KillConfirmationConfig>()
+            .init_resource::<LifeLossCooldown>()
+            .init_resource::<SafeZonePolicy>()
+            .init_resource::<ScoreCalloutConfig>()
+            .init_resource::<ScoreFloorRule>()
+            .init_resource::<ShotStats>()
+            .init_resource::<RespawnGraceConfig>()
+            .init_resource::<SprintCueConfig>()
+            .add_event::<DamageIndicated>()
+            .add_event::<KillConfirmed>()
             .add_event::<LifeLost>()
             .add_event::<Shoot>()
             .add_system_set(
-                SystemSet::on_exit(AppState::Loading).with_system(spawn_player.system()),
+                SystemSet::on_exit(AppState::NewGame).with_system(spawn_player.system()),
             )
             .add_system_set(
                 SystemSet::on_exit(AppState::GameOver).with_system(spawn_player.system()),
@@ -439,11 +1326,23 @@ impl Plugin for PlayerPlugin {
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
                     .with_system(speak_info.system().chain(error_handler.system()))
+                    .with_system(toggle_checkpoint_beacon.system())
                     .with_system(snap.system())
-                    .with_system(shoot.system()),
+                    .with_system(shoot.system())
+                    .with_system(melee.system())
+                    .with_system(reload.system().chain(error_handler.system()))
+                    .with_system(speak_ammo.system().chain(error_handler.system()))
+                    .with_system(sprint_cues.system())
+                    .with_system(score_callouts.system()),
             )
             .add_system(checkpoint.system())
+            .add_system(safe_zone_announcement.system())
+            .add_system(shot_stats.system())
+            .add_system(confirm_kills.system())
+            .add_system(checkpoint_beacon.system())
+            .add_system(damage_indicator.system())
             .add_system(life_loss.system().chain(error_handler.system()))
+            .add_system(invulnerability.system())
             .add_system_set(
                 SystemSet::on_enter(AppState::BetweenLives)
                     .with_system(reset_between_lives_timer.system())
@@ -456,6 +1355,419 @@ impl Plugin for PlayerPlugin {
                         .chain(error_handler.system()),
                 ),
             )
-            .add_system(score.system());
+            .add_system(score.system().chain(error_handler.system()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::app::Events;
+    use blackout::mapgen;
+
+    use super::*;
+    use crate::{
+        game::ENGLISH,
+        testing::{sent, test_world},
+    };
+
+    #[test]
+    fn orientation_reports_distances_and_exit_direction() {
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        let player = Coordinates((10., 10.));
+        assert_eq!(
+            orientation_token(&localization, "orientation.start", &player, &(10, 0)),
+            "Start: 10 tiles south"
+        );
+        assert_eq!(
+            orientation_token(&localization, "orientation.exit", &player, &(40, 40)),
+            "Exit: 42 tiles northeast"
+        );
+        assert_eq!(
+            orientation_token(&localization, "orientation.start", &player, &(10, 10)),
+            "Start: here"
+        );
+    }
+
+    #[test]
+    fn crossing_a_milestone_calls_it_out_once() {
+        let mut world = World::default();
+        world.insert_resource(Time::default());
+        world.insert_resource(ScoreCalloutConfig {
+            milestones: vec![100, 500],
+            ..Default::default()
+        });
+        world.insert_resource(SpeechQueue::default());
+        world.insert_resource(Localization::default());
+        let player = world.spawn().insert(Player).insert(Score(50)).id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(score_callouts.system());
+        for score in [50, 150, 160, 400].iter() {
+            **world.get_mut::<Score>(player).unwrap() = *score;
+            stage.run(&mut world);
+        }
+        assert_eq!(world.get_resource::<SpeechQueue>().unwrap().pending(), 1);
+    }
+
+    fn spend_shots(rule: ScoreFloorRule, score: u32) -> (u32, AppState) {
+        fire(rule, score, Shoot::default())
+    }
+
+    fn fire(rule: ScoreFloorRule, score: u32, shot: Shoot) -> (u32, AppState) {
+        let mut world = World::default();
+        world.insert_resource(rule);
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(Localization::default());
+        world.insert_resource(Events::<Shoot>::default());
+        world.insert_resource(Events::<RobotKilled>::default());
+        let player = world.spawn().insert(Score(score)).id();
+        for _ in 0..6 {
+            world
+                .get_resource_mut::<Events<Shoot>>()
+                .unwrap()
+                .send(shot);
+        }
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system_set(State::<AppState>::get_driver())
+            .add_system(score.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        stage.run(&mut world);
+        (
+            **world.get::<Score>(player).unwrap(),
+            world
+                .get_resource::<State<AppState>>()
+                .unwrap()
+                .current()
+                .clone(),
+        )
+    }
+
+    #[test]
+    fn badass_kills_confirm_more_strongly_than_dumbass_kills() {
+        let badass = kill_confirmation_strength(&RobotType::Badass);
+        let dumbass = kill_confirmation_strength(&RobotType::Dumbass);
+        assert!(badass > dumbass);
+        assert_eq!(badass, 1.);
+        assert!(dumbass >= 0.1);
+    }
+
+    #[test]
+    fn safe_zone_policy_governs_firing_and_its_cost() {
+        assert!(safe_zone_shot(SafeZonePolicy::NoFiring, true).is_none());
+        assert!(
+            !safe_zone_shot(SafeZonePolicy::NoFiring, false)
+                .unwrap()
+                .costless
+        );
+        let free = safe_zone_shot(SafeZonePolicy::FreeFiring, true).unwrap();
+        assert!(free.costless);
+        assert_eq!(
+            fire(ScoreFloorRule::default(), 10, free),
+            (10, AppState::InGame)
+        );
+        assert_eq!(
+            fire(ScoreFloorRule::default(), 10, Shoot::default()),
+            (9, AppState::InGame)
+        );
+    }
+
+    #[test]
+    fn sudden_death_ends_the_run_at_zero_score() {
+        assert_eq!(
+            spend_shots(ScoreFloorRule::SuddenDeath, 1),
+            (0, AppState::GameOver)
+        );
+    }
+
+    #[test]
+    fn score_floor_only_clamps() {
+        assert_eq!(
+            spend_shots(ScoreFloorRule::Floor(1), 1),
+            (1, AppState::InGame)
+        );
+    }
+
+    #[test]
+    fn verbose_coordinates_name_the_current_area() {
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        let map = Map::new(mapgen::Map::new(20, 20));
+        let areas = Areas(vec![Area {
+            rect: mapgen::geometry::Rect::new(10, 18, 8, 4),
+            description: Some("Armory".into()),
+            light: 1.,
+        }]);
+        let coordinates = Coordinates((14., 19.));
+        let mut config = CoordinateConfig::default();
+        assert_eq!(
+            spoken_coordinates(&localization, &config, &coordinates, &map, Some(&areas)),
+            "(14, 19)"
+        );
+        config.include_area = true;
+        assert_eq!(
+            spoken_coordinates(&localization, &config, &coordinates, &map, Some(&areas)),
+            "(14, 19), Armory"
+        );
+    }
+
+    #[test]
+    fn hits_within_the_cooldown_cost_one_life() {
+        let mut world = test_world();
+        world.insert_resource(Sfx::default());
+        world.insert_resource(LifeLossCooldown::default());
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(Events::<LifeLost>::default());
+        let player = world.spawn().insert(Player).insert(Lives(3)).id();
+        {
+            let mut events = world.get_resource_mut::<Events<LifeLost>>().unwrap();
+            events.send(LifeLost);
+            events.send(LifeLost);
+        }
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(life_loss.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        assert_eq!(**world.get::<Lives>(player).unwrap(), 2);
+    }
+
+    #[test]
+    fn invulnerability_cue_plays_for_the_grace_period_after_respawn() {
+        let mut world = test_world();
+        world.insert_resource(Sfx::default());
+        world.insert_resource(RespawnGraceConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        world.insert_resource(Localization::default());
+        world.insert_resource(State::new(AppState::InGame));
+        let mut timer = BetweenLivesTimer::default();
+        let duration = timer.duration();
+        timer.tick(duration);
+        world.insert_resource(timer);
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Lives(2))
+            .insert(Checkpoint::default())
+            .insert(Coordinates((5., 5.)))
+            .insert(Transform::default())
+            .id();
+        world
+            .get_resource_mut::<State<AppState>>()
+            .unwrap()
+            .push(AppState::BetweenLives)
+            .unwrap();
+        let mut driver = SystemStage::single_threaded();
+        driver.add_system_set(State::<AppState>::get_driver());
+        driver.run(&mut world);
+        let mut respawn = SystemStage::single_threaded();
+        respawn.add_system(
+            tick_between_lives_timer
+                .system()
+                .chain(error_handler.system()),
+        );
+        respawn.run(&mut world);
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(invulnerability.system());
+        let cues = |world: &mut World| {
+            world
+                .query_filtered::<&Sound, With<InvulnerableCue>>()
+                .iter(world)
+                .filter(|sound| sound.looping)
+                .count()
+        };
+        assert_eq!(cues(&mut world), 0);
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), 1);
+        let grace = Duration::from_secs_f32(RespawnGraceConfig::default().duration);
+        world
+            .get_mut::<Invulnerable>(player)
+            .unwrap()
+            .tick(grace - Duration::from_millis(1));
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), 1);
+        world
+            .get_mut::<Invulnerable>(player)
+            .unwrap()
+            .tick(Duration::from_millis(1));
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), 0);
+        assert!(world.get::<Invulnerable>(player).is_none());
+    }
+
+    #[test]
+    fn sprint_cues_fire_only_on_transitions() {
+        let mut world = test_world();
+        world.insert_resource(Sfx::default());
+        world.insert_resource(SprintCueConfig {
+            sound: true,
+            speak: true,
+        });
+        world.insert_resource(SpeechQueue::default());
+        world.insert_resource(Localization::default());
+        let player = world.spawn().insert(Player).id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(sprint_cues.system());
+        let cues = |world: &mut World| {
+            (
+                world.query::<&Sound>().iter(world).count(),
+                world.get_resource::<SpeechQueue>().unwrap().pending(),
+            )
+        };
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), (0, 0));
+        world.entity_mut(player).insert(Sprinting);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), (1, 1));
+        world.entity_mut(player).remove::<Sprinting>();
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(cues(&mut world), (2, 2));
+    }
+
+    #[test]
+    fn starting_lives_follow_the_selected_difficulty() {
+        let mut world = test_world();
+        world.insert_resource(Sprites::default());
+        world.insert_resource(Sfx::default());
+        world.insert_resource(MagazineConfig::default());
+        world.insert_resource(Difficulty::default());
+        world.insert_resource(Localization::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(spawn_player.system());
+        stage.run(&mut world);
+        let lives = world
+            .query_filtered::<(Entity, &Lives), With<Player>>()
+            .iter(&world)
+            .map(|(entity, lives)| (entity, **lives))
+            .collect::<Vec<(Entity, u32)>>();
+        assert_eq!(lives.len(), 1);
+        assert_eq!(lives[0].1, 3);
+        world.despawn(lives[0].0);
+        *world.get_resource_mut::<Difficulty>().unwrap() = Difficulty::Normal.next();
+        stage.run(&mut world);
+        let lives = world
+            .query_filtered::<&Lives, With<Player>>()
+            .iter(&world)
+            .map(|lives| **lives)
+            .collect::<Vec<u32>>();
+        assert_eq!(lives, vec![2]);
+        assert_eq!(
+            *world.get_resource::<Difficulty>().unwrap(),
+            Difficulty::Hard
+        );
+    }
+
+    #[test]
+    fn hit_from_the_east_pans_the_indicator_east() {
+        let mut world = test_world();
+        world.insert_resource(DamageIndicatorConfig {
+            sound: true,
+            rumble: true,
+        });
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Events::<DamageIndicated>::default());
+        let level = world
+            .spawn()
+            .insert(Map::new(mapgen::Map::new(20, 20)))
+            .id();
+        world
+            .spawn()
+            .insert(Player)
+            .insert(Coordinates((10., 10.)))
+            .insert(Transform::from_rotation(Quat::from_rotation_z(PI / 2.)))
+            .insert(DamageSource(Coordinates((15., 10.))));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(damage_indicator.system());
+        stage.run(&mut world);
+        let sounds = world
+            .query_filtered::<(&Transform, &Parent), With<Sound>>()
+            .iter(&world)
+            .map(|(transform, parent)| (transform.translation, **parent))
+            .collect::<Vec<(Vec3, Entity)>>();
+        assert_eq!(sounds, vec![(Vec3::new(12., 10., 0.), level)]);
+        let bearings = sent::<DamageIndicated>(&world)
+            .into_iter()
+            .map(|DamageIndicated(bearing)| bearing)
+            .collect::<Vec<f32>>();
+        assert_eq!(bearings.len(), 1);
+        // Counter-clockwise positive, so a hit on the right is a quarter turn negative.
+        assert!((bearings[0] + PI / 2.).abs() < 0.001);
+    }
+
+    #[test]
+    fn new_checkpoint_moves_the_beacon() {
+        let mut world = World::default();
+        world.insert_resource(CheckpointBeaconConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Events::<LifeLost>::default());
+        world.insert_resource(Events::<Reset>::default());
+        world.spawn().insert(Map::new(mapgen::Map::new(10, 10)));
+        let player = world
+            .spawn()
+            .insert(Player)
+            .insert(Checkpoint(Coordinates((2., 2.)), Quat::default()))
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(checkpoint_beacon.system());
+        let beacons = |world: &mut World| {
+            world
+                .query_filtered::<&Coordinates, With<CheckpointBeacon>>()
+                .iter(world)
+                .copied()
+                .collect::<Vec<Coordinates>>()
+        };
+        stage.run(&mut world);
+        assert_eq!(beacons(&mut world), vec![Coordinates((2., 2.))]);
+        *world.get_mut::<Checkpoint>(player).unwrap() =
+            Checkpoint(Coordinates((7., 5.)), Quat::default());
+        stage.run(&mut world);
+        assert_eq!(beacons(&mut world), vec![Coordinates((7., 5.))]);
+    }
+
+    #[test]
+    fn efficiency_readout_reports_the_hit_ratio() {
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        let mut world = World::default();
+        world.insert_resource(ShotStats::default());
+        world.insert_resource(Events::<Reset>::default());
+        world.insert_resource(Events::<Shoot>::default());
+        world.insert_resource(Events::<RobotKilled>::default());
+        let player = world.spawn().insert(Player).id();
+        let robot = world.spawn().id();
+        for _ in 0..8 {
+            world
+                .get_resource_mut::<Events<Shoot>>()
+                .unwrap()
+                .send(Shoot::default());
+        }
+        {
+            let mut kills = world.get_resource_mut::<Events<RobotKilled>>().unwrap();
+            for owner in [player, player, robot].iter() {
+                kills.send(RobotKilled(
+                    robot,
+                    RobotType::Dumbass,
+                    Coordinates::default(),
+                    0,
+                    CauseOfDeath::Bullet(*owner),
+                ));
+            }
+        }
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(shot_stats.system());
+        stage.run(&mut world);
+        let stats = *world.get_resource::<ShotStats>().unwrap();
+        assert_eq!((stats.shots, stats.kills), (8, 2));
+        assert_eq!(
+            efficiency_readout(&localization, &stats),
+            "8 shots fired, 2 kills, 25% hit ratio."
+        );
     }
 }
@@ -1,33 +1,100 @@
 use std::{
     collections::{HashMap, HashSet},
+    error::Error,
     f32::consts::PI,
+    time::Duration,
 };
 
-use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy::{
+    ecs::{schedule::ShouldRun, system::EntityCommands},
+    prelude::*,
+};
 use big_brain::prelude::*;
 use blackout::{
+    bevy_input_actionmap::InputMap,
     bevy_openal::{Buffer, Sound, SoundState},
-    core::{Coordinates, Player, PointLike},
+    bevy_tts::Tts,
+    core::{Angle, Coordinates, Paused, Player, PointLike},
     derive_more::{Deref, DerefMut},
-    log::Log,
+    error::error_handler,
+    exploration::ExplorationFocused,
+    localization::Localization,
+    log::{Log, LogPriority},
     map::{Areas, Map},
+    mapgen::TileType,
     navigation::{BlocksMotion, MaxSpeed, MotionBlocked, Speed, Velocity},
-    pathfinding::Destination,
+    pathfinding::{find_path, Destination},
     rand::prelude::*,
-    sound::{Footstep, FootstepBundle, SoundIcon, SoundIconBundle},
+    sound::{
+        spawn_sound_at, walls_between, Footstep, FootstepBundle, Heard, OcclusionConfig, SoundIcon,
+        SoundIconBundle, SoundParams,
+    },
     visibility::{BlocksVisibility, Viewshed, VisibilityBlocked},
 };
 
 use crate::{
     bonus::AwardBonus,
     bullet::{Bullet, BulletCommands, ShotRange, ShotSpeed, ShotTimer},
-    game::{AppState, Sfx, Sprites},
+    game::{
+        AppState, AssistConfig, Difficulty, DifficultyCurve, GameRng, Sfx, Sprites, DESCRIBE_ROBOT,
+        SPEAK_NEAREST_ROBOT,
+    },
     level::WallCollision,
+    player::{DamageSource, LifeLost},
 };
 
+#[derive(Clone, Debug)]
+pub struct BombFuse {
+    pub timer: Timer,
+    pub beep: Timer,
+}
+
+impl BombFuse {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, false),
+            beep: Timer::from_seconds(BOMB_BEEP_MAX_INTERVAL, false),
+        }
+    }
+}
+
+const BOMB_BEEP_MAX_INTERVAL: f32 = 0.8;
+const BOMB_BEEP_MIN_INTERVAL: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BomberConfig {
+    pub enabled: bool,
+    pub fuse: f32,
+    pub blast_radius: f32,
+}
+
+impl Default for BomberConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fuse: 4.,
+            blast_radius: 7.5,
+        }
+    }
+}
+
 pub enum CauseOfDeath {
     Bullet(Entity),
-    Shockwave(Name),
+    Melee,
+    // The name of the robot whose explosion caused this one, and whether the
+    // chain started with the player.
+    Shockwave(Name, bool),
+    SelfDestruct,
+}
+
+impl CauseOfDeath {
+    pub fn player_caused(&self) -> bool {
+        match self {
+            CauseOfDeath::Bullet(_) | CauseOfDeath::Melee => true,
+            CauseOfDeath::Shockwave(_, player_caused) => *player_caused,
+            CauseOfDeath::SelfDestruct => false,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -49,7 +116,28 @@ impl ScorerBuilder for CuriousBuilder {
 }
 
 #[derive(Clone, Debug)]
-pub struct DeathTimer(pub Timer, pub Name);
+pub struct DeathTimer(pub Timer, pub Name, pub bool);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Flee;
+
+impl Flee {
+    pub fn build() -> FleeBuilder {
+        FleeBuilder
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FleeBuilder;
+
+impl ActionBuilder for FleeBuilder {
+    fn build(&self, cmd: &mut Commands, action: Entity, _actor: Entity) {
+        cmd.entity(action).insert(Flee);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct Health(pub u32);
 
 #[derive(Clone, Copy, Debug)]
 pub struct Investigate;
@@ -72,6 +160,214 @@ impl ActionBuilder for InvestigateBuilder {
 #[derive(Clone, Copy, Debug, Deref, DerefMut)]
 struct InvestigateCoordinates((i32, i32));
 
+// Scores when the robot is down to `threshold` health or less with the player inside
+// `cornered_range` tiles.
+#[derive(Clone, Copy, Debug)]
+pub struct LowHealth {
+    pub threshold: u32,
+    pub cornered_range: f32,
+}
+
+impl LowHealth {
+    pub fn build(threshold: u32, cornered_range: f32) -> LowHealthBuilder {
+        LowHealthBuilder {
+            threshold,
+            cornered_range,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LowHealthBuilder {
+    threshold: u32,
+    cornered_range: f32,
+}
+
+impl ScorerBuilder for LowHealthBuilder {
+    fn build(&self, cmd: &mut Commands, scorer: Entity, _actor: Entity) {
+        cmd.entity(scorer).insert(LowHealth {
+            threshold: self.threshold,
+            cornered_range: self.cornered_range,
+        });
+    }
+}
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+struct NoEscape(Timer);
+
+#[derive(Clone, Copy, Debug)]
+pub struct PursuitStuckConfig {
+    pub enabled: bool,
+    pub window: f32,
+    pub min_progress: f32,
+    pub give_up_duration: f32,
+}
+
+impl Default for PursuitStuckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 3.,
+            min_progress: 1.,
+            give_up_duration: 5.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RobotAlertConfig {
+    pub enabled: bool,
+    pub radius: f32,
+}
+
+impl Default for RobotAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 20.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DebrisConfig {
+    pub enabled: bool,
+    pub lifetime: f32,
+}
+
+impl Default for DebrisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lifetime: 5.,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+struct Debris(Timer);
+
+#[derive(Clone, Copy, Debug)]
+pub struct FleeConfig {
+    pub enabled: bool,
+    pub no_escape_duration: f32,
+}
+
+impl Default for FleeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            no_escape_duration: 5.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IntroGraceConfig {
+    pub enabled: bool,
+    pub duration: f32,
+}
+
+impl Default for IntroGraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration: 5.,
+        }
+    }
+}
+
+// Present while robots are holding fire at the start of a level.
+#[derive(Clone, Debug, Deref, DerefMut)]
+struct IntroGrace(Timer);
+
+// Badasses only see within this cone, as (width, offset from heading), when set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FieldOfViewConfig {
+    pub badass: Option<(Angle, Angle)>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShotClearanceConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Idle;
+
+impl Idle {
+    pub fn build() -> IdleBuilder {
+        IdleBuilder
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IdleBuilder;
+
+impl ScorerBuilder for IdleBuilder {
+    fn build(&self, cmd: &mut Commands, scorer: Entity, _actor: Entity) {
+        cmd.entity(scorer).insert(Idle);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Patrol;
+
+impl Patrol {
+    pub fn build() -> PatrolBuilder {
+        PatrolBuilder
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PatrolBuilder;
+
+impl ActionBuilder for PatrolBuilder {
+    fn build(&self, cmd: &mut Commands, action: Entity, _actor: Entity) {
+        cmd.entity(action).insert(Patrol);
+    }
+}
+
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub struct PatrolRoute(pub Vec<(i32, i32)>);
+
+// Index of the current waypoint, and whether the route is being walked forward.
+#[derive(Clone, Copy, Debug)]
+struct PatrolProgress(usize, bool);
+
+impl Default for PatrolProgress {
+    fn default() -> Self {
+        Self(0, true)
+    }
+}
+
+const PATROL_SPEED_FACTOR: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug)]
+struct PlayerSighted(Entity, Coordinates, Coordinates);
+
+#[derive(Clone, Copy, Debug, Deref, DerefMut)]
+pub struct PursuitGracePeriod(pub f32);
+
+impl Default for PursuitGracePeriod {
+    fn default() -> Self {
+        Self(3.)
+    }
+}
+
+// Seconds of play since the player was last seen, and where.
+#[derive(Clone, Copy, Debug)]
+pub struct LastSeen(pub f32, pub Coordinates);
+
+#[derive(Clone, Debug)]
+struct PursuitProgress(Timer, Coordinates);
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+pub struct Dodging(pub Timer);
+
+#[derive(Clone, Debug, Deref, DerefMut)]
+struct GaveUpPursuit(Timer);
+
 #[derive(Clone, Copy, Debug)]
 pub struct PursuePlayer;
 
@@ -98,11 +394,34 @@ pub struct RobotKilled(
     pub CauseOfDeath,
 );
 
-#[derive(Clone, Copy, Debug)]
+pub struct RobotDamaged(pub Entity, pub Velocity);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum RobotType {
     Dumbass,
     Jackass,
     Badass,
+    Bomber,
+}
+
+impl RobotType {
+    pub fn points(&self) -> u32 {
+        match self {
+            RobotType::Dumbass => 10,
+            RobotType::Jackass => 50,
+            RobotType::Badass => 100,
+            RobotType::Bomber => 25,
+        }
+    }
+
+    pub fn localization_key(&self) -> &'static str {
+        match self {
+            RobotType::Dumbass => "robot_type.dumbass",
+            RobotType::Jackass => "robot_type.jackass",
+            RobotType::Badass => "robot_type.badass",
+            RobotType::Bomber => "robot_type.bomber",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -146,34 +465,83 @@ pub struct RobotBundle {
     pub shot_range: ShotRange,
     pub shot_speed: ShotSpeed,
     pub shot_accuracy: ShotAccuracy,
+    pub health: Health,
 }
 
 pub trait RobotCommands<'a, 'b> {
-    fn insert_robot(&mut self, robot_type: &RobotType) -> &mut EntityCommands<'a, 'b>;
+    fn insert_robot(
+        &mut self,
+        robot_type: &RobotType,
+        difficulty: &Difficulty,
+        curve: &DifficultyCurve,
+        level: u32,
+    ) -> &mut EntityCommands<'a, 'b>;
 }
 
 impl<'a, 'b> RobotCommands<'a, 'b> for EntityCommands<'a, 'b> {
-    fn insert_robot(&mut self, robot_type: &RobotType) -> &mut Self {
+    fn insert_robot(
+        &mut self,
+        robot_type: &RobotType,
+        difficulty: &Difficulty,
+        curve: &DifficultyCurve,
+        level: u32,
+    ) -> &mut Self {
         let max_speed;
         let visibility_range;
         let shot_accuracy;
+        let shot_range: u32;
+        let shot_speed: u32;
+        let health;
         match robot_type {
             RobotType::Dumbass => {
                 max_speed = MaxSpeed(2.);
                 visibility_range = 12;
                 shot_accuracy = ShotAccuracy(PI / 9.);
+                shot_range = 16;
+                shot_speed = 8;
+                health = Health(1);
             }
             RobotType::Jackass => {
                 max_speed = MaxSpeed(4.);
                 visibility_range = 16;
                 shot_accuracy = ShotAccuracy(PI / 10.);
+                shot_range = 16;
+                shot_speed = 8;
+                health = Health(2);
             }
             RobotType::Badass => {
                 max_speed = MaxSpeed(4.);
                 visibility_range = 24;
                 shot_accuracy = ShotAccuracy(PI / 12.);
+                shot_range = 16;
+                shot_speed = 8;
+                health = Health(3);
+            }
+            RobotType::Bomber => {
+                max_speed = MaxSpeed(6.);
+                visibility_range = 16;
+                shot_accuracy = Default::default();
+                shot_range = 0;
+                shot_speed = 0;
+                health = Health(1);
             }
         };
+        let shot_range = ShotRange((shot_range as f32 * difficulty.shot_range_multiplier()) as u32);
+        let shot_speed = ShotSpeed((shot_speed as f32 * difficulty.shot_speed_multiplier()) as u32);
+        let max_speed = MaxSpeed(
+            *max_speed * curve.speed_multiplier(level) * difficulty.max_speed_multiplier(),
+        );
+        let shot_accuracy = ShotAccuracy(
+            *shot_accuracy * curve.spread_multiplier(level) * difficulty.spread_multiplier(),
+        );
+        let shot_interval = 3. * curve.shot_interval_multiplier(level);
+        // Dumbasses die in one hit, so never get the chance to flee.
+        let flee_scorer = match robot_type {
+            RobotType::Dumbass => LowHealth::build(0, 0.),
+            RobotType::Jackass => LowHealth::build(1, 4.),
+            RobotType::Badass => LowHealth::build(1, 2.),
+            RobotType::Bomber => LowHealth::build(0, 0.),
+        };
         self.insert_bundle(RobotBundle {
             robot: Robot(*robot_type),
             transform: Default::default(),
@@ -187,18 +555,21 @@ impl<'a, 'b> RobotCommands<'a, 'b> for EntityCommands<'a, 'b> {
             },
             blocks_visibility: Default::default(),
             blocks_motion: Default::default(),
-            shot_timer: ShotTimer(Timer::from_seconds(3., false)),
-            shot_range: ShotRange(16),
-            shot_speed: ShotSpeed(8),
+            shot_timer: ShotTimer(Timer::from_seconds(shot_interval, false)),
+            shot_range,
+            shot_speed,
             shot_accuracy,
+            health,
             coordinates: Default::default(),
             name: Default::default(),
         })
         .insert(
             Thinker::build()
                 .picker(FirstToScore { threshold: 0.8 })
+                .when(flee_scorer, Flee::build())
                 .when(SeesPlayer::build(), PursuePlayer::build())
-                .when(Curious::build(), Investigate::build()),
+                .when(Curious::build(), Investigate::build())
+                .when(Idle::build(), Patrol::build()),
         )
         .with_children(|parent| {
             parent
@@ -210,6 +581,17 @@ impl<'a, 'b> RobotCommands<'a, 'b> for EntityCommands<'a, 'b> {
     }
 }
 
+fn field_of_view(
+    config: Res<FieldOfViewConfig>,
+    mut robots: Query<(&Robot, &mut Viewshed), Added<Robot>>,
+) {
+    for (Robot(robot_type), mut viewshed) in robots.iter_mut() {
+        if let RobotType::Badass = robot_type {
+            viewshed.fov = config.badass;
+        }
+    }
+}
+
 fn post_process_robot(
     mut commands: Commands,
     sfx: Res<Sfx>,
@@ -223,6 +605,7 @@ fn post_process_robot(
             RobotType::Dumbass => sprites.dumbass,
             RobotType::Jackass => sprites.jackass,
             RobotType::Badass => sprites.badass,
+            RobotType::Bomber => sprites.bomber,
         });
         commands.entity(entity).insert_bundle(SpriteBundle {
             material: materials.add(sprite_handle.into()),
@@ -251,6 +634,7 @@ fn post_process_robot(
                         RobotType::Dumbass => sfx.robot_dumbass,
                         RobotType::Jackass => sfx.robot_jackass,
                         RobotType::Badass => sfx.robot_badass,
+                        RobotType::Bomber => sfx.robot_bomber,
                     },
                     gain: 0.8,
                     ..Default::default()
@@ -265,14 +649,35 @@ fn post_process_robot(
 }
 
 fn sees_player_scorer(
+    mut commands: Commands,
+    time: Res<Time>,
+    grace_period: Res<PursuitGracePeriod>,
     mut query: Query<(&Actor, &mut Score), With<SeesPlayer>>,
     viewsheds: Query<&Viewshed>,
     player: Query<(&Player, &Coordinates)>,
+    gave_up: Query<&GaveUpPursuit>,
+    mut last_seen: Query<&mut LastSeen>,
 ) {
     if let Ok((_, player_coords)) = player.single() {
         for (Actor(actor), mut score) in query.iter_mut() {
+            if gave_up.get(*actor).is_ok() {
+                score.set(0.);
+                continue;
+            }
             if let Ok(viewshed) = viewsheds.get(*actor) {
                 if viewshed.is_visible(player_coords) {
+                    if let Ok(mut last_seen) = last_seen.get_mut(*actor) {
+                        *last_seen = LastSeen(0., *player_coords);
+                    } else {
+                        commands.entity(*actor).insert(LastSeen(0., *player_coords));
+                    }
+                    score.set(1.);
+                    continue;
+                }
+            }
+            if let Ok(mut last_seen) = last_seen.get_mut(*actor) {
+                last_seen.0 += time.delta_seconds();
+                if last_seen.0 < **grace_period {
                     score.set(1.);
                     continue;
                 }
@@ -282,16 +687,166 @@ fn sees_player_scorer(
     }
 }
 
+fn low_health_scorer(
+    config: Res<FleeConfig>,
+    mut query: Query<(&Actor, &LowHealth, &mut Score)>,
+    robots: Query<(&Health, &Coordinates, &Viewshed)>,
+    player: Query<(&Player, &Coordinates)>,
+    no_escape: Query<&NoEscape>,
+) {
+    for (Actor(actor), low_health, mut score) in query.iter_mut() {
+        score.set(0.);
+        if !config.enabled || no_escape.get(*actor).is_ok() {
+            continue;
+        }
+        if let (Ok((health, coordinates, viewshed)), Ok((_, player_coordinates))) =
+            (robots.get(*actor), player.single())
+        {
+            if **health <= low_health.threshold
+                && viewshed.is_visible(player_coordinates)
+                && coordinates.distance(player_coordinates) <= low_health.cornered_range
+            {
+                score.set(1.);
+            }
+        }
+    }
+}
+
+// Pathfinding every tile in a large area would stall the frame, so only the farthest few are tried.
+const MAX_FLEE_PATHS: usize = 8;
+
+// The reachable tile in the robot's area farthest from the player, provided it is farther than
+// where the robot already stands.
+fn flee_target(
+    start: &Coordinates,
+    player: &Coordinates,
+    map: &Map,
+    motion_blocked: &MotionBlocked,
+    areas: &Areas,
+) -> Option<(i32, i32)> {
+    let area = areas.iter().find(|a| a.contains(start))?;
+    let mut candidates = vec![];
+    for x in area.rect.x1..=area.rect.x2 {
+        for y in area.rect.y1..=area.rect.y2 {
+            let candidate = (x as i32, y as i32);
+            let distance = candidate.distance(player);
+            if !motion_blocked[candidate.to_index(map.width())] && distance > start.distance(player)
+            {
+                candidates.push((candidate, distance));
+            }
+        }
+    }
+    candidates.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+    candidates
+        .into_iter()
+        .map(|(candidate, _)| candidate)
+        .take(MAX_FLEE_PATHS)
+        .find(|candidate| find_path(start, candidate, map).is_some())
+}
+
+fn flee(
+    mut commands: Commands,
+    config: Res<FleeConfig>,
+    mut query: Query<(&Actor, &mut ActionState), With<Flee>>,
+    robots: Query<(&MaxSpeed, &Coordinates)>,
+    player: Query<(&Player, &Coordinates)>,
+    level: Query<(&Map, &MotionBlocked, &Areas)>,
+    destinations: Query<&Destination>,
+) {
+    for (Actor(actor), mut state) in query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                let target = match (robots.get(*actor), player.single(), level.single()) {
+                    (
+                        Ok((_, coordinates)),
+                        Ok((_, player_coordinates)),
+                        Ok((map, motion_blocked, areas)),
+                    ) => flee_target(coordinates, player_coordinates, map, motion_blocked, areas),
+                    _ => None,
+                };
+                if let (Some(target), Ok((max_speed, _))) = (target, robots.get(*actor)) {
+                    commands
+                        .entity(*actor)
+                        .insert(Destination(target))
+                        .insert(Speed(**max_speed));
+                    *state = ActionState::Executing;
+                } else {
+                    // Nowhere safer to go, so stand and fight.
+                    commands.entity(*actor).insert(NoEscape(Timer::from_seconds(
+                        config.no_escape_duration,
+                        false,
+                    )));
+                    *state = ActionState::Failure;
+                }
+            }
+            ActionState::Executing => {
+                if destinations.get(*actor).is_err() {
+                    *state = ActionState::Success;
+                }
+            }
+            ActionState::Cancelled => {
+                commands.entity(*actor).remove::<Destination>();
+                *state = ActionState::Success;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn no_escape(mut commands: Commands, time: Res<Time>, mut robots: Query<(Entity, &mut NoEscape)>) {
+    for (entity, mut timer) in robots.iter_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            commands.entity(entity).remove::<NoEscape>();
+        }
+    }
+}
+
+const PURSUIT_JITTER: i32 = 2;
+
+// Spread pursuers around the target rather than stacking them on one tile. Seeded by entity so
+// each robot keeps a stable offset and doesn't constantly re-path.
+fn pursuit_target(
+    entity: Entity,
+    target: (i32, i32),
+    map: &Map,
+    motion_blocked: &MotionBlocked,
+    areas: &Areas,
+) -> (i32, i32) {
+    if let Some(area) = areas.iter().find(|a| a.contains(&target)) {
+        let mut rng = StdRng::seed_from_u64(entity.to_bits());
+        let x = (target.0 + rng.gen_range(-PURSUIT_JITTER..=PURSUIT_JITTER))
+            .max(area.rect.x1 as i32)
+            .min(area.rect.x2 as i32);
+        let y = (target.1 + rng.gen_range(-PURSUIT_JITTER..=PURSUIT_JITTER))
+            .max(area.rect.y1 as i32)
+            .min(area.rect.y2 as i32);
+        if !motion_blocked[(x, y).to_index(map.width())] {
+            return (x, y);
+        }
+    }
+    target
+}
+
 fn pursue_player(
     mut commands: Commands,
+    time: Res<Time>,
+    config: Res<PursuitStuckConfig>,
     mut query: Query<(&Actor, &mut ActionState), With<PursuePlayer>>,
     player: Query<(&Player, &Coordinates)>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     names: Query<&Name>,
-    robot: Query<&MaxSpeed>,
+    robot: Query<(&MaxSpeed, &Coordinates)>,
+    mut progress: Query<&mut PursuitProgress>,
     children: Query<&Children>,
     mut timers: Query<&mut Timer>,
+    mut sightings: EventWriter<PlayerSighted>,
+    last_seen: Query<&LastSeen>,
+    level: Query<(&Map, &MotionBlocked, &Areas)>,
+    dodging: Query<&Dodging>,
 ) {
+    let pursuers = query.iter_mut().count();
     for (Actor(actor), mut state) in query.iter_mut() {
         match *state {
             ActionState::Requested => {
@@ -301,14 +856,69 @@ fn pursue_player(
                         timer.reset();
                     }
                 }
+                if let Ok((_, robot_coordinates)) = robot.get(*actor) {
+                    commands.entity(*actor).insert(PursuitProgress(
+                        Timer::from_seconds(config.window, false),
+                        *robot_coordinates,
+                    ));
+                    if let Ok((_, player_coordinates)) = player.single() {
+                        sightings.send(PlayerSighted(
+                            *actor,
+                            *robot_coordinates,
+                            *player_coordinates,
+                        ));
+                    }
+                }
                 *state = ActionState::Executing;
             }
             ActionState::Executing => {
                 if let Ok((_, coordinates)) = player.single() {
-                    if let Ok(max_speed) = robot.get(*actor) {
+                    if config.enabled {
+                        if let (Ok((_, robot_coordinates)), Ok(mut progress)) =
+                            (robot.get(*actor), progress.get_mut(*actor))
+                        {
+                            progress.0.tick(time.delta());
+                            if progress.0.finished() {
+                                if robot_coordinates.distance(&progress.1) < config.min_progress {
+                                    let mut entity = commands.entity(*actor);
+                                    entity
+                                        .remove::<Destination>()
+                                        .remove::<PursuitProgress>()
+                                        .insert(GaveUpPursuit(Timer::from_seconds(
+                                            config.give_up_duration,
+                                            false,
+                                        )));
+                                    // Robots without a sighting fall back to patrolling.
+                                    if let Ok(LastSeen(_, seen)) = last_seen.get(*actor) {
+                                        entity.insert(InvestigateCoordinates(seen.i32()));
+                                    }
+                                    *state = ActionState::Failure;
+                                    continue;
+                                }
+                                progress.0.reset();
+                                progress.1 = *robot_coordinates;
+                            }
+                        }
+                    }
+                    // Let a sidestep play out before resuming the chase.
+                    if dodging.get(*actor).is_ok() {
+                        continue;
+                    }
+                    if let Ok((max_speed, _)) = robot.get(*actor) {
+                        let target = last_seen
+                            .get(*actor)
+                            .map(|last_seen| last_seen.1)
+                            .unwrap_or(*coordinates)
+                            .i32();
+                        let target = match level.single() {
+                            Ok((map, motion_blocked, areas)) if pursuers > 1 => {
+                                pursuit_target(*actor, target, map, motion_blocked, areas)
+                            }
+                            _ => target,
+                        };
                         commands
                             .entity(*actor)
-                            .insert(Destination(coordinates.i32()))
+                            .insert(Destination(target))
                             .insert(Speed(**max_speed));
                     }
                 }
@@ -316,7 +926,10 @@ fn pursue_player(
             ActionState::Cancelled => {
                 if let Ok(mut log) = log.single_mut() {
                     if let Ok(name) = names.get(*actor) {
-                        log.push(format!("{} evaded!", **name));
+                        log.push_with_priority(
+                            localization.format("robot.evaded", &[&**name]),
+                            LogPriority::Low,
+                        );
                     }
                 }
                 *state = ActionState::Success;
@@ -326,11 +939,43 @@ fn pursue_player(
     }
 }
 
+// Robot actions choose destinations, so holding them while paused keeps every robot on course.
+pub fn thinkers_running(paused: Res<Paused>) -> ShouldRun {
+    if **paused {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
+fn dodging(mut commands: Commands, time: Res<Time>, mut robots: Query<(Entity, &mut Dodging)>) {
+    for (entity, mut timer) in robots.iter_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            commands.entity(entity).remove::<Dodging>();
+        }
+    }
+}
+
+fn gave_up_pursuit(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut robots: Query<(Entity, &mut GaveUpPursuit)>,
+) {
+    for (entity, mut timer) in robots.iter_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            commands.entity(entity).remove::<GaveUpPursuit>();
+        }
+    }
+}
+
 const VOICE_GAIN: f32 = 1.2;
 const VOICE_REFERENCE_DISTANCE: f32 = 4.;
 
 fn comment_on_investigation(
     mut commands: Commands,
+    state: Res<State<AppState>>,
     query: Query<&Actor, With<Investigate>>,
     time: Res<Time>,
     robots: Query<(&Robot, &Children)>,
@@ -338,18 +983,21 @@ fn comment_on_investigation(
     mut sounds: Query<&mut Sound>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
+    mut rng: ResMut<GameRng>,
 ) {
+    if *state.current() == AppState::Paused {
+        return;
+    }
     for Actor(actor) in query.iter() {
-        if let Ok((_, children)) = robots.get(*actor) {
+        if let Ok((Robot(robot_type), children)) = robots.get(*actor) {
             let voice = children[0];
             if let Ok(mut timer) = timers.get_mut(voice) {
                 if timer.percent() == 0. {
                     if let Ok(mut sound) = sounds.get_mut(voice) {
                         sound.stop();
                     }
-                    let mut comments = sfx.investigate.clone();
-                    comments.shuffle(&mut thread_rng());
-                    let buffer = buffers.get_handle(comments[0]);
+                    let comments = sfx.investigate_for(robot_type);
+                    let buffer = buffers.get_handle(*comments.choose(&mut **rng).unwrap());
                     let sound = Sound {
                         buffer,
                         state: SoundState::Playing,
@@ -370,6 +1018,7 @@ fn comment_on_investigation(
 
 fn taunt_player(
     mut commands: Commands,
+    state: Res<State<AppState>>,
     query: Query<&Actor, With<PursuePlayer>>,
     time: Res<Time>,
     robots: Query<(&Robot, &Children)>,
@@ -377,18 +1026,21 @@ fn taunt_player(
     mut sounds: Query<&mut Sound>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
+    mut rng: ResMut<GameRng>,
 ) {
+    if *state.current() == AppState::Paused {
+        return;
+    }
     for Actor(actor) in query.iter() {
-        if let Ok((_, children)) = robots.get(*actor) {
+        if let Ok((Robot(robot_type), children)) = robots.get(*actor) {
             let voice = children[0];
             if let Ok(mut timer) = timers.get_mut(voice) {
                 if timer.percent() == 0. {
                     if let Ok(mut sound) = sounds.get_mut(voice) {
                         sound.stop();
                     }
-                    let mut comments = sfx.taunts.clone();
-                    comments.shuffle(&mut thread_rng());
-                    let buffer = buffers.get_handle(comments[0]);
+                    let comments = sfx.taunts_for(robot_type);
+                    let buffer = buffers.get_handle(*comments.choose(&mut **rng).unwrap());
                     let sound = Sound {
                         buffer,
                         state: SoundState::Playing,
@@ -407,9 +1059,130 @@ fn taunt_player(
     }
 }
 
+const KNOCKBACK_DISTANCE: f32 = 0.5;
+
+fn clear_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut Debris, &Coordinates)>,
+    mut map: Query<(&Map, &mut MotionBlocked)>,
+    blockers: Query<&BlocksMotion, Without<Debris>>,
+) {
+    for (entity, mut timer, coordinates) in debris.iter_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            if let Ok((map, mut motion_blocked)) = map.single_mut() {
+                let index = coordinates.to_index(map.width());
+                motion_blocked[index] = map.base.tiles[index] == TileType::Wall
+                    || map.entities[index]
+                        .iter()
+                        .any(|e| *e != entity && blockers.get(*e).is_ok());
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn robot_damaged(
+    mut commands: Commands,
+    mut events: EventReader<RobotDamaged>,
+    mut robots: Query<(&mut Coordinates, &Children), With<Robot>>,
+    map: Query<(&Map, &MotionBlocked)>,
+    mut sounds: Query<&mut Sound>,
+    buffers: Res<Assets<Buffer>>,
+    sfx: Res<Sfx>,
+    mut rng: ResMut<GameRng>,
+) {
+    for RobotDamaged(entity, velocity) in events.iter() {
+        if let Ok((mut coordinates, children)) = robots.get_mut(*entity) {
+            if let Ok((map, motion_blocked)) = map.single() {
+                let push = if velocity.length() > 0. {
+                    velocity.normalize() * KNOCKBACK_DISTANCE
+                } else {
+                    Vec2::ZERO
+                };
+                let x = coordinates.x() + push.x;
+                let y = coordinates.y() + push.y;
+                // The robot blocks its own tile, so only check tiles it would move into.
+                if x >= 0.
+                    && y >= 0.
+                    && (x as usize) < map.width()
+                    && (y as usize) < map.height()
+                    && ((x, y).to_index(map.width()) == coordinates.to_index(map.width())
+                        || !motion_blocked[(x, y).to_index(map.width())])
+                {
+                    **coordinates = (x, y);
+                }
+            }
+            spawn_sound_at(
+                &mut commands,
+                *entity,
+                buffers.get_handle(sfx.robot_hit),
+                &(0., 0.),
+                Default::default(),
+            );
+            let voice = children[0];
+            if let Ok(mut sound) = sounds.get_mut(voice) {
+                sound.stop();
+            }
+            let buffer = buffers.get_handle(*sfx.robot_damaged.choose(&mut **rng).unwrap());
+            commands.entity(voice).insert(Sound {
+                buffer,
+                state: SoundState::Playing,
+                gain: VOICE_GAIN,
+                reference_distance: VOICE_REFERENCE_DISTANCE,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+pub fn apply_spread(bearing: f32, accuracy: f32, rng: &mut impl Rng) -> f32 {
+    if accuracy <= 0. {
+        bearing
+    } else {
+        rng.gen_range(bearing - accuracy..bearing + accuracy)
+    }
+}
+
+fn start_intro_grace(
+    mut commands: Commands,
+    config: Res<IntroGraceConfig>,
+    map: Query<&Map, Added<Areas>>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+) {
+    if config.enabled && map.single().is_ok() {
+        commands.insert_resource(IntroGrace(Timer::from_seconds(config.duration, false)));
+        if let Ok(mut log) = log.single_mut() {
+            let seconds = config.duration.round() as u32;
+            log.push(localization.format_plural("robot.grace", seconds as usize, &[&seconds]));
+        }
+    }
+}
+
+fn intro_grace(
+    mut commands: Commands,
+    time: Res<Time>,
+    grace: Option<ResMut<IntroGrace>>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+) {
+    if let Some(mut grace) = grace {
+        grace.tick(time.delta());
+        if grace.finished() {
+            commands.remove_resource::<IntroGrace>();
+            if let Ok(mut log) = log.single_mut() {
+                log.push_with_priority(localization.get("robot.grace.over"), LogPriority::Urgent);
+            }
+        }
+    }
+}
+
 fn shoot_player(
     mut commands: Commands,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
     query: Query<&Actor, With<PursuePlayer>>,
     mut robots: Query<(
         &Robot,
@@ -424,33 +1197,49 @@ fn shoot_player(
     level: Query<(Entity, &Map)>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
+    clearance: Res<ShotClearanceConfig>,
+    occlusion: Res<OcclusionConfig>,
+    grace: Option<Res<IntroGrace>>,
 ) {
+    if grace.is_some() {
+        return;
+    }
     for Actor(actor) in query.iter() {
-        if let Ok((_, robot_entity, robot_coords, mut timer, range, speed, accuracy)) =
-            robots.get_mut(*actor)
+        if let Ok((
+            Robot(robot_type),
+            robot_entity,
+            robot_coords,
+            mut timer,
+            range,
+            speed,
+            accuracy,
+        )) = robots.get_mut(*actor)
         {
+            if let RobotType::Bomber = robot_type {
+                continue;
+            }
             if let Ok((_, player_coords)) = player.single() {
                 timer.tick(time.delta());
                 if timer.finished() {
-                    if let Ok((level_entity, _)) = level.single() {
-                        let transform = Transform::from_translation(Vec3::new(
-                            robot_coords.x(),
-                            robot_coords.y(),
-                            0.,
-                        ));
-                        let buffer = buffers.get_handle(sfx.robot_shoot);
-                        let shot_sound = commands
-                            .spawn()
-                            .insert(Sound {
-                                buffer,
-                                state: SoundState::Playing,
+                    if let Ok((level_entity, map)) = level.single() {
+                        let walls = walls_between(robot_coords, player_coords, map);
+                        if clearance.enabled && walls > 0 {
+                            continue;
+                        }
+                        let (gain, pitch) = occlusion.attenuation(walls);
+                        spawn_sound_at(
+                            &mut commands,
+                            level_entity,
+                            buffers.get_handle(sfx.robot_shoot),
+                            robot_coords,
+                            SoundParams {
+                                gain,
+                                pitch,
                                 ..Default::default()
-                            })
-                            .insert(transform)
-                            .id();
+                            },
+                        );
                         let bearing = robot_coords.bearing(player_coords);
-                        let bearing =
-                            thread_rng().gen_range(bearing - **accuracy..bearing + **accuracy);
+                        let bearing = apply_spread(bearing, **accuracy, &mut **rng);
                         let x = bearing.cos();
                         let y = bearing.sin();
                         let velocity = Vec2::new(x, y) * (**speed as f32);
@@ -466,9 +1255,7 @@ fn shoot_player(
                                 range,
                             )
                             .id();
-                        commands
-                            .entity(level_entity)
-                            .push_children(&[shot_sound, bullet]);
+                        commands.entity(level_entity).push_children(&[bullet]);
                     }
                     timer.reset();
                 }
@@ -477,6 +1264,17 @@ fn shoot_player(
     }
 }
 
+fn attenuated_loudness(
+    from: &dyn PointLike,
+    to: &dyn PointLike,
+    loudness: f32,
+    map: &Map,
+    occlusion: &OcclusionConfig,
+) -> f32 {
+    let (gain, _) = occlusion.attenuation(walls_between(from, to, map));
+    loudness * gain
+}
+
 fn investigate_coordinates(
     mut commands: Commands,
     actors: Query<(Entity, &Viewshed, &Coordinates), With<Robot>>,
@@ -485,6 +1283,10 @@ fn investigate_coordinates(
     mut robot_kills: EventReader<RobotKilled>,
     level: Query<(&Map, &MotionBlocked, &Areas)>,
     mut wall_collisions: EventReader<WallCollision>,
+    alert_config: Res<RobotAlertConfig>,
+    mut sightings: EventReader<PlayerSighted>,
+    mut heard: EventReader<Heard>,
+    occlusion: Res<OcclusionConfig>,
 ) {
     let mut investigations: Vec<(i32, i32)> = vec![];
     let mut rng = thread_rng();
@@ -578,6 +1380,35 @@ fn investigate_coordinates(
             }
         }
     }
+    for PlayerSighted(sighter, sighter_coords, player_coords) in sightings.iter() {
+        if !alert_config.enabled {
+            continue;
+        }
+        for (entity, _, robot_coords) in actors.iter() {
+            if entity != *sighter && robot_coords.distance(sighter_coords) <= alert_config.radius {
+                commands
+                    .entity(entity)
+                    .insert(InvestigateCoordinates(player_coords.i32()));
+            }
+        }
+    }
+    for Heard {
+        coordinates: heard_coords,
+        loudness,
+    } in heard.iter()
+    {
+        if let Ok((map, _, _)) = level.single() {
+            for (entity, _, robot_coords) in actors.iter() {
+                let loudness =
+                    attenuated_loudness(heard_coords, robot_coords, *loudness, map, &occlusion);
+                if robot_coords.distance(heard_coords) <= loudness {
+                    commands
+                        .entity(entity)
+                        .insert(InvestigateCoordinates(heard_coords.i32()));
+                }
+            }
+        }
+    }
 }
 
 fn curious_scorer(
@@ -647,13 +1478,81 @@ fn investigate(
     }
 }
 
+fn idle_scorer(mut query: Query<(&Actor, &mut Score), With<Idle>>, routes: Query<&PatrolRoute>) {
+    for (Actor(actor), mut score) in query.iter_mut() {
+        match routes.get(*actor) {
+            Ok(route) if route.len() > 1 => score.set(0.8),
+            _ => score.set(0.),
+        }
+    }
+}
+
+fn patrol(
+    mut commands: Commands,
+    mut query: Query<(&Actor, &mut ActionState), With<Patrol>>,
+    robots: Query<(&PatrolRoute, Option<&PatrolProgress>, &MaxSpeed)>,
+    destinations: Query<&Destination>,
+) {
+    for (Actor(actor), mut state) in query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                if let Ok((route, progress, max_speed)) = robots.get(*actor) {
+                    let index = progress.map(|v| v.0).unwrap_or_default();
+                    if let Some(waypoint) = route.get(index) {
+                        commands
+                            .entity(*actor)
+                            .insert(Destination(*waypoint))
+                            .insert(Speed(**max_speed * PATROL_SPEED_FACTOR));
+                        *state = ActionState::Executing;
+                        continue;
+                    }
+                }
+                *state = ActionState::Failure;
+            }
+            ActionState::Executing => {
+                if destinations.get(*actor).is_ok() {
+                    continue;
+                }
+                if let Ok((route, progress, max_speed)) = robots.get(*actor) {
+                    let mut next = progress.cloned().unwrap_or_default();
+                    // Walk to the end of the route, then turn around and walk back.
+                    let last = route.len().saturating_sub(1);
+                    if next.1 && next.0 >= last {
+                        next.1 = false;
+                    } else if !next.1 && next.0 == 0 {
+                        next.1 = true;
+                    }
+                    next.0 = if next.1 {
+                        (next.0 + 1).min(last)
+                    } else {
+                        next.0.saturating_sub(1)
+                    };
+                    if let Some(waypoint) = route.get(next.0) {
+                        commands
+                            .entity(*actor)
+                            .insert(next)
+                            .insert(Destination(*waypoint))
+                            .insert(Speed(**max_speed * PATROL_SPEED_FACTOR));
+                    } else {
+                        *state = ActionState::Failure;
+                    }
+                }
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Success;
+            }
+            _ => {}
+        }
+    }
+}
+
 fn robot_killed(
     mut commands: Commands,
     mut events: EventReader<RobotKilled>,
     mut log: Query<&mut Log>,
+    localization: Res<Localization>,
     names: Query<&Name>,
     level: Query<(Entity, &Map)>,
-    transforms: Query<&Transform>,
     buffers: Res<Assets<Buffer>>,
     sfx: Res<Sfx>,
     mut motion_blocked: Query<&mut MotionBlocked>,
@@ -661,51 +1560,64 @@ fn robot_killed(
     coordinates: Query<&Coordinates>,
     non_exploding_robots: Query<(Entity, &Robot, &Coordinates), Without<DeathTimer>>,
     mut killed: Local<HashSet<Entity>>,
+    debris: Res<DebrisConfig>,
 ) {
-    for RobotKilled(entity, _, _, index, cause) in events.iter() {
+    for RobotKilled(entity, _, robot_coordinates, index, cause) in events.iter() {
         if !killed.contains(&entity) {
             if let Ok(mut log) = log.single_mut() {
                 if let Ok(name) = names.get(*entity) {
                     match cause {
                         CauseOfDeath::Bullet(_) => {
                             let mut messages = vec![
-                                "is toast!",
-                                "is defeated!",
-                                "is no more!",
-                                "is obliterated!",
-                                "exits stage left!",
-                                "just suffered a warranty-voiding event!",
+                                "robot.killed.toast",
+                                "robot.killed.defeated",
+                                "robot.killed.no_more",
+                                "robot.killed.obliterated",
+                                "robot.killed.stage_left",
+                                "robot.killed.warranty",
                             ];
                             messages.shuffle(&mut thread_rng());
-                            let message = format!("{} {}", **name, messages[0]);
+                            let message = localization.format(messages[0], &[&**name]);
                             log.push(message);
                         }
-                        CauseOfDeath::Shockwave(owner) => {
-                            log.push(format!(
-                                "{} is taken out by an exploding {}!",
-                                **name, **owner
-                            ));
+                        CauseOfDeath::Melee => {
+                            log.push(localization.format("robot.killed.melee", &[&**name]));
+                        }
+                        CauseOfDeath::Shockwave(owner, _) => {
+                            log.push(
+                                localization.format("robot.killed.shockwave", &[&**name, &**owner]),
+                            );
+                        }
+                        CauseOfDeath::SelfDestruct => {
+                            log.push(localization.format("robot.killed.self_destruct", &[&**name]));
                         }
                     };
                 }
             }
             commands.entity(*entity).despawn_recursive();
             if let Ok((level_entity, _)) = level.single() {
-                if let Ok(transform) = transforms.get(*entity) {
+                spawn_sound_at(
+                    &mut commands,
+                    level_entity,
+                    buffers.get_handle(sfx.robot_explode),
+                    robot_coordinates,
+                    SoundParams {
+                        reference_distance: 10.,
+                        ..Default::default()
+                    },
+                );
+            }
+            if debris.enabled {
+                if let Ok((level_entity, _)) = level.single() {
                     let id = commands
                         .spawn()
-                        .insert(Sound {
-                            buffer: buffers.get_handle(sfx.robot_explode),
-                            state: SoundState::Playing,
-                            reference_distance: 10.,
-                            ..Default::default()
-                        })
-                        .insert(*transform)
+                        .insert(Debris(Timer::from_seconds(debris.lifetime, false)))
+                        .insert(*robot_coordinates)
+                        .insert(BlocksMotion)
                         .id();
                     commands.entity(level_entity).push_children(&[id]);
                 }
-            }
-            if let Ok(mut motion_blocked) = motion_blocked.single_mut() {
+            } else if let Ok(mut motion_blocked) = motion_blocked.single_mut() {
                 motion_blocked[*index] = false;
             }
             if let Ok(mut visibility_blocked) = visibility_blocked.single_mut() {
@@ -722,20 +1634,19 @@ fn robot_killed(
                             commands.entity(candidate_entity).insert(DeathTimer(
                                 Timer::from_seconds(distance / 5., false),
                                 name.clone(),
+                                cause.player_caused(),
                             ));
-                            let sound = commands
-                                .spawn()
-                                .insert(Sound {
-                                    buffer: buffers.get_handle(sfx.shockwave),
-                                    state: SoundState::Playing,
+                            spawn_sound_at(
+                                &mut commands,
+                                candidate_entity,
+                                buffers.get_handle(sfx.shockwave),
+                                &(0., 0.),
+                                SoundParams {
                                     looping: true,
                                     reference_distance: 3.,
                                     ..Default::default()
-                                })
-                                .insert(Transform::default())
-                                .insert(GlobalTransform::default())
-                                .id();
-                            commands.entity(candidate_entity).push_children(&[sound]);
+                                },
+                            );
                         }
                     }
                 }
@@ -768,33 +1679,759 @@ fn shockwave(
                     *robot_type,
                     *coordinates,
                     index,
-                    CauseOfDeath::Shockwave(timer.1.clone()),
+                    CauseOfDeath::Shockwave(timer.1.clone(), timer.2),
                 ));
-                bonus.send(AwardBonus);
+                if timer.2 {
+                    bonus.send(AwardBonus);
+                }
             }
         }
     }
 }
 
+fn arm_bombs(
+    mut commands: Commands,
+    query: Query<&Actor, With<PursuePlayer>>,
+    robots: Query<&Robot, Without<BombFuse>>,
+    config: Res<BomberConfig>,
+) {
+    for Actor(actor) in query.iter() {
+        if let Ok(Robot(RobotType::Bomber)) = robots.get(*actor) {
+            commands.entity(*actor).insert(BombFuse::new(config.fuse));
+        }
+    }
+}
+
+fn bomb_fuse(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bombs: Query<(Entity, &Robot, &Coordinates, &Name, &mut BombFuse)>,
+    player: Query<(&Player, Entity, &Coordinates)>,
+    level: Query<(Entity, &Map)>,
+    buffers: Res<Assets<Buffer>>,
+    sfx: Res<Sfx>,
+    mut log: Query<&mut Log>,
+    localization: Res<Localization>,
+    mut robot_killed: EventWriter<RobotKilled>,
+    mut life_lost: EventWriter<LifeLost>,
+    config: Res<BomberConfig>,
+) {
+    for (entity, Robot(robot_type), coordinates, name, mut fuse) in bombs.iter_mut() {
+        fuse.timer.tick(time.delta());
+        fuse.beep.tick(time.delta());
+        if let Ok((level_entity, map)) = level.single() {
+            if fuse.beep.finished() {
+                let interval = BOMB_BEEP_MAX_INTERVAL
+                    - (BOMB_BEEP_MAX_INTERVAL - BOMB_BEEP_MIN_INTERVAL) * fuse.timer.percent();
+                fuse.beep.set_duration(Duration::from_secs_f32(interval));
+                fuse.beep.reset();
+                spawn_sound_at(
+                    &mut commands,
+                    level_entity,
+                    buffers.get_handle(sfx.bomb_beep),
+                    coordinates,
+                    SoundParams {
+                        pitch: 1. + fuse.timer.percent(),
+                        ..Default::default()
+                    },
+                );
+            }
+            if fuse.timer.just_finished() {
+                let index = coordinates.to_index(map.width());
+                robot_killed.send(RobotKilled(
+                    entity,
+                    *robot_type,
+                    *coordinates,
+                    index,
+                    CauseOfDeath::SelfDestruct,
+                ));
+                if let Ok((_, player_entity, player_coordinates)) = player.single() {
+                    if coordinates.distance(player_coordinates) <= config.blast_radius {
+                        if let Ok(mut log) = log.single_mut() {
+                            log.push_with_priority(
+                                localization.format("robot.blast", &[&**name]),
+                                LogPriority::Urgent,
+                            );
+                        }
+                        life_lost.send(LifeLost);
+                        commands
+                            .entity(player_entity)
+                            .insert(DamageSource(*coordinates));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn robot_description(
+    localization: &Localization,
+    name: &Name,
+    robot_type: &RobotType,
+    player: &Coordinates,
+    robot: &Coordinates,
+    viewshed: &Viewshed,
+) -> String {
+    let sighting = if viewshed.is_visible(player) {
+        "robot.sees_you"
+    } else {
+        "robot.has_not_seen_you"
+    };
+    localization.format(
+        "robot.description",
+        &[
+            &**name,
+            &localization.get(robot_type.localization_key()),
+            &localization.distance_and_direction(player, robot),
+            &localization.get(sighting),
+        ],
+    )
+}
+
+fn describe_robot(
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    robots: Query<(&Robot, &Name, &Coordinates, &Viewshed), With<ExplorationFocused>>,
+    player: Query<(&Player, &Coordinates)>,
+) -> Result<(), Box<dyn Error>> {
+    if input.just_active(DESCRIBE_ROBOT) {
+        if let Ok((_, player_coordinates)) = player.single() {
+            if let Some((Robot(robot_type), name, coordinates, viewshed)) = robots.iter().next() {
+                tts.speak(
+                    robot_description(
+                        &localization,
+                        name,
+                        robot_type,
+                        player_coordinates,
+                        coordinates,
+                        viewshed,
+                    ),
+                    true,
+                )?;
+            } else {
+                tts.speak(localization.get("robot.none_focused"), true)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// With robots on radar, robots the player can't see still count.
+fn nearest_robot<'a>(
+    player: &Coordinates,
+    viewshed: &Viewshed,
+    robots: impl Iterator<Item = (&'a Name, &'a Coordinates)>,
+    on_radar: bool,
+) -> Option<(&'a Name, &'a Coordinates)> {
+    robots
+        .filter(|(_, coordinates)| on_radar || viewshed.is_visible(*coordinates))
+        .min_by(|(_, a), (_, b)| {
+            player
+                .distance(*a)
+                .partial_cmp(&player.distance(*b))
+                .unwrap()
+        })
+}
+
+fn speak_nearest_robot(
+    input: Res<InputMap<String>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+    assist: Res<AssistConfig>,
+    robots: Query<(&Robot, &Name, &Coordinates)>,
+    player: Query<(&Player, &Coordinates, &Viewshed)>,
+) -> Result<(), Box<dyn Error>> {
+    if input.just_active(SPEAK_NEAREST_ROBOT) {
+        if let Ok((_, player_coordinates, viewshed)) = player.single() {
+            let nearest = nearest_robot(
+                player_coordinates,
+                viewshed,
+                robots
+                    .iter()
+                    .map(|(_, name, coordinates)| (name, coordinates)),
+                assist.robots_on_radar,
+            );
+            if let Some((name, coordinates)) = nearest {
+                tts.speak(
+                    localization.format(
+                        "robot.nearest",
+                        &[
+                            &**name,
+                            &localization.distance_and_direction(player_coordinates, coordinates),
+                        ],
+                    ),
+                    true,
+                )?;
+            } else {
+                tts.speak(localization.get("robot.none_nearby"), true)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub struct RobotPlugin;
 
 impl Plugin for RobotPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_event::<RobotKilled>()
+            .add_event::<RobotDamaged>()
+            .add_event::<PlayerSighted>()
+            .init_resource::<PursuitGracePeriod>()
+            .init_resource::<PursuitStuckConfig>()
+            .init_resource::<RobotAlertConfig>()
+            .init_resource::<DebrisConfig>()
+            .init_resource::<BomberConfig>()
+            .init_resource::<FleeConfig>()
+            .init_resource::<IntroGraceConfig>()
+            .init_resource::<FieldOfViewConfig>()
+            .init_resource::<ShotClearanceConfig>()
             .add_plugin(BigBrainPlugin)
+            .add_system(field_of_view.system())
             .add_system(post_process_robot.system())
-            .add_system(sees_player_scorer.system())
-            .add_system_to_stage(CoreStage::PreUpdate, pursue_player.system())
             .add_system_to_stage(CoreStage::PostUpdate, comment_on_investigation.system())
             .add_system_to_stage(CoreStage::PostUpdate, taunt_player.system())
-            .add_system_to_stage(CoreStage::PreUpdate, investigate_coordinates.system())
-            .add_system(curious_scorer.system())
-            .add_system_to_stage(CoreStage::PreUpdate, investigate.system())
+            .add_system_set_to_stage(
+                CoreStage::PreUpdate,
+                SystemSet::new()
+                    .with_run_criteria(thinkers_running.system())
+                    .with_system(flee.system())
+                    .with_system(pursue_player.system())
+                    .with_system(investigate_coordinates.system())
+                    .with_system(investigate.system())
+                    .with_system(patrol.system()),
+            )
+            .add_system(start_intro_grace.system())
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
+                    .with_system(sees_player_scorer.system())
+                    .with_system(gave_up_pursuit.system())
+                    .with_system(dodging.system())
+                    .with_system(low_health_scorer.system())
+                    .with_system(no_escape.system())
+                    .with_system(curious_scorer.system())
+                    .with_system(idle_scorer.system())
+                    .with_system(clear_debris.system())
+                    .with_system(intro_grace.system())
                     .with_system(shoot_player.system())
-                    .with_system(shockwave.system()),
+                    .with_system(shockwave.system())
+                    .with_system(arm_bombs.system())
+                    .with_system(bomb_fuse.system())
+                    .with_system(describe_robot.system().chain(error_handler.system()))
+                    .with_system(speak_nearest_robot.system().chain(error_handler.system())),
+            )
+            .add_system(robot_killed.system())
+            .add_system(robot_damaged.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::Events, ecs::system::CommandQueue};
+    use blackout::{core::Area, mapgen};
+
+    use super::*;
+    use crate::{
+        game::ENGLISH,
+        testing::{open_map, spawn_level, test_world},
+    };
+
+    #[test]
+    fn occluded_shot_is_quieter_than_clear_shot() {
+        let occlusion = OcclusionConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let robot: (f32, f32) = (1.5, 1.5);
+        let player: (f32, f32) = (8.5, 1.5);
+        let mut map = open_map(10, 3);
+        let (clear, _) = occlusion.attenuation(walls_between(&robot, &player, &map));
+        map.base.tiles[10 + 5] = TileType::Wall;
+        let (occluded, _) = occlusion.attenuation(walls_between(&robot, &player, &map));
+        assert_eq!(clear, 1.);
+        assert!(occluded < clear);
+    }
+
+    #[test]
+    fn robots_hold_fire_during_intro_grace() {
+        let mut world = test_world();
+        world.insert_resource(GameRng(StdRng::seed_from_u64(1)));
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Localization::default());
+        world.insert_resource(ShotClearanceConfig::default());
+        world.insert_resource(OcclusionConfig::default());
+        world.insert_resource(IntroGrace(Timer::from_seconds(1., false)));
+        spawn_level(&mut world, open_map(10, 3));
+        world.spawn().insert(Player).insert(Coordinates((8.5, 1.5)));
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((1.5, 1.5)))
+            .insert(ShotTimer(Timer::from_seconds(0., false)))
+            .insert(ShotRange(16))
+            .insert(ShotSpeed(8))
+            .insert(ShotAccuracy(0.))
+            .id();
+        world.spawn().insert(Actor(robot)).insert(PursuePlayer);
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(intro_grace.system())
+            .add_system(shoot_player.system());
+        stage.run(&mut world);
+        assert_eq!(world.query::<&Bullet>().iter(&world).count(), 0);
+        world
+            .get_resource_mut::<IntroGrace>()
+            .unwrap()
+            .tick(Duration::from_secs(1));
+        stage.run(&mut world);
+        assert!(world.get_resource::<IntroGrace>().is_none());
+        assert_eq!(world.query::<&Bullet>().iter(&world).count(), 0);
+        stage.run(&mut world);
+        assert_eq!(world.query::<&Bullet>().iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn no_bullet_when_a_wall_blocks_the_shot() {
+        let mut world = test_world();
+        world.insert_resource(GameRng(StdRng::seed_from_u64(1)));
+        world.insert_resource(Sfx::default());
+        world.insert_resource(ShotClearanceConfig { enabled: true });
+        world.insert_resource(OcclusionConfig::default());
+        let mut map = open_map(10, 3);
+        map.base.tiles[10 + 5] = TileType::Wall;
+        let level = spawn_level(&mut world, map);
+        world.spawn().insert(Player).insert(Coordinates((8.5, 1.5)));
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((1.5, 1.5)))
+            .insert(ShotTimer(Timer::from_seconds(0., false)))
+            .insert(ShotRange(16))
+            .insert(ShotSpeed(8))
+            .insert(ShotAccuracy(0.))
+            .id();
+        world.spawn().insert(Actor(robot)).insert(PursuePlayer);
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(shoot_player.system());
+        stage.run(&mut world);
+        assert_eq!(world.query::<&Bullet>().iter(&world).count(), 0);
+        world.get_mut::<Map>(level).unwrap().base.tiles[10 + 5] = TileType::Floor;
+        stage.run(&mut world);
+        assert_eq!(world.query::<&Bullet>().iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn badass_taunts_come_from_the_badass_pool() {
+        let mut world = test_world();
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(GameRng(StdRng::seed_from_u64(3)));
+        let sfx = Sfx::default();
+        let pool = sfx.voices[&RobotType::Badass].taunts.clone();
+        world.insert_resource(sfx);
+        let voice = world.spawn().insert(Timer::from_seconds(5., false)).id();
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Badass))
+            .insert(Children::with(&[voice]))
+            .id();
+        world.spawn().insert(Actor(robot)).insert(PursuePlayer);
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(taunt_player.system());
+        for _ in 0..20 {
+            world.get_mut::<Timer>(voice).unwrap().reset();
+            stage.run(&mut world);
+            let taunt = world.get::<Sound>(voice).unwrap().buffer.id;
+            assert!(pool.contains(&taunt));
+        }
+    }
+
+    #[test]
+    fn seeded_spread_is_bounded_and_deterministic() {
+        let accuracy = PI / 9.;
+        let mut first = StdRng::seed_from_u64(7);
+        let mut second = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let spread = apply_spread(1., accuracy, &mut first);
+            assert!((spread - 1.).abs() <= accuracy);
+            assert_eq!(spread, apply_spread(1., accuracy, &mut second));
+        }
+    }
+
+    #[test]
+    fn radar_reports_robots_outside_the_viewshed() {
+        let player = Coordinates((1., 1.));
+        let viewshed = Viewshed {
+            visible: [(1, 1), (2, 1)].iter().copied().collect(),
+            ..Default::default()
+        };
+        let name = Name::new("Dumbass 1");
+        let hidden = Coordinates((8., 8.));
+        let robots = || std::iter::once((&name, &hidden));
+        assert!(nearest_robot(&player, &viewshed, robots(), false).is_none());
+        let (nearest, coordinates) = nearest_robot(&player, &viewshed, robots(), true).unwrap();
+        assert_eq!(nearest.as_str(), "Dumbass 1");
+        assert_eq!(*coordinates, hidden);
+    }
+
+    #[test]
+    fn flee_target_only_paths_to_the_farthest_candidates() {
+        let mut map = open_map(12, 3);
+        let areas = Areas(vec![Area {
+            rect: mapgen::geometry::Rect::new(0, 0, 11, 2),
+            description: None,
+            light: 1.,
+        }]);
+        let player = Coordinates((4., 1.));
+        let blocked = |map: &Map| {
+            MotionBlocked(
+                map.base
+                    .tiles
+                    .iter()
+                    .map(|tile| *tile == TileType::Wall)
+                    .collect(),
             )
+        };
+        let start = Coordinates((5., 1.));
+        assert_eq!(
+            flee_target(&start, &player, &map, &blocked(&map), &areas),
+            Some((11, 0))
+        );
+        // Walling off the far end leaves more unreachable candidates than are worth pathing to.
+        for y in 0..3 {
+            map.base.tiles[(8, y).to_index(12)] = TileType::Wall;
+        }
+        assert_eq!(
+            flee_target(&start, &player, &map, &blocked(&map), &areas),
+            None
+        );
+    }
+
+    #[test]
+    fn debris_blocks_the_robot_tile_for_its_lifetime() {
+        let mut world = test_world();
+        world.insert_resource(DebrisConfig {
+            enabled: true,
+            lifetime: 1.,
+        });
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Localization::default());
+        world.insert_resource(Events::<RobotKilled>::default());
+        let index = (3, 1).to_index(6);
+        let level = spawn_level(&mut world, open_map(6, 3));
+        world.get_mut::<MotionBlocked>(level).unwrap()[index] = true;
+        let coordinates = Coordinates((3., 1.));
+        let robot = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(coordinates)
+            .insert(BlocksMotion)
+            .id();
+        world
+            .get_resource_mut::<Events<RobotKilled>>()
+            .unwrap()
+            .send(RobotKilled(
+                robot,
+                RobotType::Dumbass,
+                coordinates,
+                index,
+                CauseOfDeath::Melee,
+            ));
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(robot_killed.system())
+            .add_system(clear_debris.system());
+        let blocked = |world: &World| world.get::<MotionBlocked>(level).unwrap()[index];
+        let age = |world: &mut World, duration: Duration| {
+            for mut debris in world.query::<&mut Debris>().iter_mut(world) {
+                debris.tick(duration);
+            }
+        };
+        stage.run(&mut world);
+        assert!(world.get_entity(robot).is_none());
+        assert!(blocked(&world));
+        age(&mut world, Duration::from_millis(999));
+        stage.run(&mut world);
+        assert!(blocked(&world));
+        age(&mut world, Duration::from_millis(1));
+        stage.run(&mut world);
+        assert!(!blocked(&world));
+    }
+
+    fn spawn_robot(world: &mut World, robot_type: RobotType, difficulty: Difficulty) -> Entity {
+        spawn_robot_on_level(
+            world,
+            robot_type,
+            difficulty,
+            &DifficultyCurve::default(),
+            1,
+        )
+    }
+
+    fn spawn_robot_on_level(
+        world: &mut World,
+        robot_type: RobotType,
+        difficulty: Difficulty,
+        curve: &DifficultyCurve,
+        level: u32,
+    ) -> Entity {
+        let mut queue = CommandQueue::default();
+        let robot = Commands::new(&mut queue, world)
+            .spawn()
+            .insert_robot(&robot_type, &difficulty, curve, level)
+            .id();
+        queue.apply(world);
+        robot
+    }
+
+    #[test]
+    fn badass_field_of_view_is_off_by_default() {
+        let mut world = World::default();
+        world.insert_resource(FieldOfViewConfig::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(field_of_view.system());
+        let default = spawn_robot(&mut world, RobotType::Badass, Difficulty::Normal);
+        stage.run(&mut world);
+        assert!(world.get::<Viewshed>(default).unwrap().fov.is_none());
+        let cone = (Angle::Degrees(60.), Angle::Degrees(0.));
+        world.insert_resource(FieldOfViewConfig { badass: Some(cone) });
+        let badass = spawn_robot(&mut world, RobotType::Badass, Difficulty::Normal);
+        let dumbass = spawn_robot(&mut world, RobotType::Dumbass, Difficulty::Normal);
+        stage.run(&mut world);
+        let fov = world.get::<Viewshed>(badass).unwrap().fov.unwrap();
+        assert_eq!(fov.0.degrees(), 60.);
+        assert!(world.get::<Viewshed>(dumbass).unwrap().fov.is_none());
+    }
+
+    #[test]
+    fn hard_robots_shoot_farther_and_faster() {
+        let mut world = World::default();
+        for robot_type in [RobotType::Dumbass, RobotType::Jackass, RobotType::Badass].iter() {
+            let normal = spawn_robot(&mut world, *robot_type, Difficulty::Normal);
+            let hard = spawn_robot(&mut world, *robot_type, Difficulty::Hard);
+            assert_eq!(**world.get::<ShotRange>(normal).unwrap(), 16);
+            assert_eq!(**world.get::<ShotSpeed>(normal).unwrap(), 8);
+            assert!(**world.get::<ShotRange>(hard).unwrap() > 16);
+            assert!(**world.get::<ShotSpeed>(hard).unwrap() > 8);
+        }
+    }
+
+    #[test]
+    fn later_levels_field_faster_more_accurate_robots_under_the_curve() {
+        let mut world = World::default();
+        let curve = DifficultyCurve {
+            enabled: true,
+            ..Default::default()
+        };
+        let spawn = |world: &mut World, level| {
+            spawn_robot_on_level(world, RobotType::Jackass, Difficulty::Normal, &curve, level)
+        };
+        let early = spawn(&mut world, 1);
+        let late = spawn(&mut world, 5);
+        assert!(**world.get::<MaxSpeed>(late).unwrap() > **world.get::<MaxSpeed>(early).unwrap());
+        assert!(
+            **world.get::<ShotAccuracy>(late).unwrap()
+                < **world.get::<ShotAccuracy>(early).unwrap()
+        );
+        let interval = |world: &World, robot| world.get::<ShotTimer>(robot).unwrap().duration();
+        assert!(interval(&world, late) < interval(&world, early));
+        let flat = spawn_robot_on_level(
+            &mut world,
+            RobotType::Jackass,
+            Difficulty::Normal,
+            &DifficultyCurve::default(),
+            5,
+        );
+        assert_eq!(
+            **world.get::<MaxSpeed>(flat).unwrap(),
+            **world.get::<MaxSpeed>(early).unwrap()
+        );
+    }
+
+    #[test]
+    fn expired_bomb_fuse_sends_a_shockwave_from_the_bomber() {
+        let mut world = test_world();
+        world.insert_resource(BomberConfig::default());
+        world.insert_resource(DebrisConfig::default());
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Localization::default());
+        world.insert_resource(Events::<RobotKilled>::default());
+        world.insert_resource(Events::<LifeLost>::default());
+        spawn_level(&mut world, open_map(20, 3));
+        let bomber = world
+            .spawn()
+            .insert(Robot(RobotType::Bomber))
+            .insert(Coordinates((2., 1.)))
+            .insert(Name::new("Bomber 1"))
+            .insert(BombFuse::new(0.))
+            .id();
+        let near = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((6., 1.)))
+            .id();
+        let far = world
+            .spawn()
+            .insert(Robot(RobotType::Dumbass))
+            .insert(Coordinates((15., 1.)))
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(bomb_fuse.system())
             .add_system(robot_killed.system());
+        stage.run(&mut world);
+        assert!(world.get_entity(bomber).is_none());
+        let DeathTimer(_, owner, player_caused) = world.get::<DeathTimer>(near).unwrap();
+        assert_eq!(owner.as_str(), "Bomber 1");
+        assert!(!player_caused);
+        assert!(world.get::<DeathTimer>(far).is_none());
+    }
+
+    fn stuck_pursuit(moved: f32) -> (ActionState, Option<(i32, i32)>) {
+        let mut world = test_world();
+        world.insert_resource(PursuitStuckConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        world.insert_resource(Localization::default());
+        world.insert_resource(Events::<PlayerSighted>::default());
+        world.spawn().insert(Player).insert(Coordinates((9., 1.)));
+        let mut progress = Timer::from_seconds(3., false);
+        progress.tick(Duration::from_secs(3));
+        let robot = world
+            .spawn()
+            .insert(MaxSpeed(2.))
+            .insert(Coordinates((2. + moved, 1.)))
+            .insert(PursuitProgress(progress, Coordinates((2., 1.))))
+            .insert(LastSeen(0., Coordinates((7., 1.))))
+            .id();
+        let action = world
+            .spawn()
+            .insert(Actor(robot))
+            .insert(ActionState::Executing)
+            .insert(PursuePlayer)
+            .id();
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(pursue_player.system());
+        stage.run(&mut world);
+        (
+            world.get::<ActionState>(action).unwrap().clone(),
+            world
+                .get::<InvestigateCoordinates>(robot)
+                .map(|InvestigateCoordinates(target)| *target),
+        )
+    }
+
+    #[test]
+    fn stuck_robot_gives_up_and_investigates_where_it_last_saw_the_player() {
+        assert_eq!(stuck_pursuit(0.2), (ActionState::Failure, Some((7, 1))));
+        assert_eq!(stuck_pursuit(3.), (ActionState::Executing, None));
+    }
+
+    #[test]
+    fn walls_muffle_heard_sounds_through_the_shared_occlusion() {
+        let mut map = open_map(10, 3);
+        map.base.tiles[10 + 5] = TileType::Wall;
+        let (from, to) = ((1.5, 1.5), (8.5, 1.5));
+        let disabled = OcclusionConfig::default();
+        assert_eq!(attenuated_loudness(&from, &to, 20., &map, &disabled), 20.);
+        let occlusion = OcclusionConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            attenuated_loudness(&from, &to, 20., &map, &occlusion),
+            20. * occlusion.gain_per_wall
+        );
+    }
+
+    #[test]
+    fn sightings_alert_allies_within_the_radius() {
+        let mut world = World::default();
+        world.insert_resource(RobotAlertConfig {
+            enabled: true,
+            radius: 20.,
+        });
+        world.insert_resource(Events::<RobotKilled>::default());
+        world.insert_resource(Events::<WallCollision>::default());
+        world.insert_resource(Events::<PlayerSighted>::default());
+        world.insert_resource(Events::<Heard>::default());
+        world.insert_resource(OcclusionConfig::default());
+        let robot = |world: &mut World, x: f32| {
+            world
+                .spawn()
+                .insert(Robot(RobotType::Dumbass))
+                .insert(Viewshed::default())
+                .insert(Coordinates((x, 1.)))
+                .id()
+        };
+        let sighter = robot(&mut world, 2.);
+        let near = robot(&mut world, 12.);
+        let far = robot(&mut world, 40.);
+        world
+            .get_resource_mut::<Events<PlayerSighted>>()
+            .unwrap()
+            .send(PlayerSighted(
+                sighter,
+                Coordinates((2., 1.)),
+                Coordinates((9., 1.)),
+            ));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(investigate_coordinates.system());
+        stage.run(&mut world);
+        let target = |entity| {
+            world
+                .get::<InvestigateCoordinates>(entity)
+                .map(|InvestigateCoordinates(target)| *target)
+        };
+        assert_eq!(target(near), Some((9, 1)));
+        assert_eq!(target(sighter), None);
+        assert_eq!(target(far), None);
+    }
+
+    #[test]
+    fn description_names_type_distance_and_sighting() {
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        let name = Name::new("Badass Bob");
+        let player = Coordinates((2., 2.));
+        let robot = Coordinates((2., 5.));
+        let mut viewshed = Viewshed::default();
+        let description = robot_description(
+            &localization,
+            &name,
+            &RobotType::Badass,
+            &player,
+            &robot,
+            &viewshed,
+        );
+        assert!(description.starts_with("Badass Bob: Badass, 3 tiles "));
+        assert!(description.ends_with(", hasn't seen you"));
+        viewshed.visible.insert((2, 2));
+        let description = robot_description(
+            &localization,
+            &name,
+            &RobotType::Badass,
+            &player,
+            &robot,
+            &viewshed,
+        );
+        assert!(description.ends_with(", sees you"));
+    }
+
+    #[test]
+    fn robot_types_have_localized_names() {
+        let mut localization = Localization::default();
+        localization.add_fallbacks(ENGLISH);
+        for (robot_type, english) in [
+            (RobotType::Dumbass, "Dumbass"),
+            (RobotType::Jackass, "Jackass"),
+            (RobotType::Badass, "Badass"),
+            (RobotType::Bomber, "Bomber"),
+        ]
+        .iter()
+        {
+            assert_eq!(localization.get(robot_type.localization_key()), *english);
+        }
     }
 }
@@ -0,0 +1,456 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use blackout::{
+    bevy_input_actionmap::InputMap,
+    bevy_tts::Tts,
+    core::{Coordinates, Player},
+    error::error_handler,
+    localization::Localization,
+    map::{Areas, Map},
+    speech::SpeechQueue,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{AppState, Reset, SpeechConfig, Volumes, CYCLE_AUTO_SAVE, LOAD, SAVE},
+    level::{Level, LevelSeed, LevelSeedOverride, POSITION_PLAYER_AT_START_LABEL},
+    player::{Checkpoint, Lives, Score},
+};
+
+#[derive(Clone, Debug)]
+pub struct SaveConfig {
+    pub path: PathBuf,
+    pub settings_path: PathBuf,
+    // Save automatically after every this many completed levels.
+    pub auto_save_every: Option<u32>,
+    // The longer interval offered after "every level" when cycling the setting.
+    pub auto_save_interval: u32,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            path: "save.ron".into(),
+            settings_path: "settings.ron".into(),
+            auto_save_every: None,
+            auto_save_interval: 5,
+        }
+    }
+}
+
+impl SaveConfig {
+    pub fn should_auto_save(&self, completed_levels: u32) -> bool {
+        match self.auto_save_every {
+            Some(every) if every > 0 => completed_levels > 0 && completed_levels % every == 0,
+            _ => false,
+        }
+    }
+
+    // Off, then every level, then every `auto_save_interval` levels.
+    pub fn next_auto_save(&self) -> Option<u32> {
+        match self.auto_save_every {
+            None => Some(1),
+            Some(1) if self.auto_save_interval > 1 => Some(self.auto_save_interval),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct MapDescriptor {
+    pub level: Level,
+    pub seed: u64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct SavedGame {
+    pub lives: Lives,
+    pub score: Score,
+    pub map: MapDescriptor,
+    pub coordinates: (f32, f32),
+    pub rotation: f32,
+    pub checkpoint: ((f32, f32), f32),
+}
+
+// Player preferences kept across sessions, separate from any saved game.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub speech_rate: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+struct PendingLoad(Option<SavedGame>);
+
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut)]
+struct PendingAutoSave(bool);
+
+fn yaw(rotation: Quat) -> f32 {
+    let forward = rotation * Vec3::X;
+    forward.y.atan2(forward.x)
+}
+
+fn saved_game(
+    seed: u64,
+    lives: &Lives,
+    score: &Score,
+    level: &Level,
+    checkpoint: &Checkpoint,
+    coordinates: &Coordinates,
+    transform: &Transform,
+) -> SavedGame {
+    let Checkpoint(checkpoint_coordinates, checkpoint_rotation) = checkpoint;
+    SavedGame {
+        lives: *lives,
+        score: *score,
+        map: MapDescriptor {
+            level: *level,
+            seed,
+        },
+        coordinates: **coordinates,
+        rotation: yaw(transform.rotation),
+        checkpoint: (**checkpoint_coordinates, yaw(*checkpoint_rotation)),
+    }
+}
+
+fn save_game(
+    input: Res<InputMap<String>>,
+    config: Res<SaveConfig>,
+    level_seed: Res<LevelSeed>,
+    player: Query<(
+        &Player,
+        &Lives,
+        &Score,
+        &Level,
+        &Checkpoint,
+        &Coordinates,
+        &Transform,
+    )>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !input.just_active(SAVE) {
+        return Ok(());
+    }
+    if let Ok((_, lives, score, level, checkpoint, coordinates, transform)) = player.single() {
+        let saved = saved_game(
+            **level_seed,
+            lives,
+            score,
+            level,
+            checkpoint,
+            coordinates,
+            transform,
+        );
+        let serialized = ron::ser::to_string_pretty(&saved, Default::default())?;
+        fs::write(&config.path, serialized)?;
+        tts.speak(localization.get("save.saved"), true)?;
+    }
+    Ok(())
+}
+
+fn cycle_auto_save(
+    input: Res<InputMap<String>>,
+    mut config: ResMut<SaveConfig>,
+    mut speech: ResMut<SpeechQueue>,
+    localization: Res<Localization>,
+) {
+    if input.just_active(CYCLE_AUTO_SAVE) {
+        config.auto_save_every = config.next_auto_save();
+        let message = match config.auto_save_every {
+            Some(every) => localization.format_plural("save.auto", every as usize, &[&every]),
+            None => localization.get("save.auto.off"),
+        };
+        speech.interrupt(message);
+    }
+}
+
+fn queue_auto_save(config: Res<SaveConfig>, mut pending: ResMut<PendingAutoSave>) {
+    if config.auto_save_every.is_some() {
+        **pending = true;
+    }
+}
+
+fn auto_save(
+    config: Res<SaveConfig>,
+    mut pending: ResMut<PendingAutoSave>,
+    level_seed: Res<LevelSeed>,
+    pool: Res<IoTaskPool>,
+    player: Query<(
+        &Player,
+        &Lives,
+        &Score,
+        &Level,
+        &Checkpoint,
+        &Coordinates,
+        &Transform,
+    )>,
+    map: Query<&Areas, Added<Areas>>,
+) -> Result<(), Box<dyn Error>> {
+    if !**pending || map.single().is_err() {
+        return Ok(());
+    }
+    **pending = false;
+    if let Ok((_, lives, score, level, checkpoint, coordinates, transform)) = player.single() {
+        if !config.should_auto_save(level.saturating_sub(1)) {
+            return Ok(());
+        }
+        let saved = saved_game(
+            **level_seed,
+            lives,
+            score,
+            level,
+            checkpoint,
+            coordinates,
+            transform,
+        );
+        let serialized = ron::ser::to_string_pretty(&saved, Default::default())?;
+        let path = config.path.clone();
+        // Keep disk access off the frame so the level transition doesn't hitch.
+        pool.spawn(async move {
+            if let Err(e) = fs::write(&path, serialized) {
+                error!("Auto-save failed: {}", e);
+            }
+        })
+        .detach();
+    }
+    Ok(())
+}
+
+fn load_settings(
+    config: Res<SaveConfig>,
+    mut volumes: ResMut<Volumes>,
+    mut speech: ResMut<SpeechConfig>,
+) -> Result<(), Box<dyn Error>> {
+    if !config.settings_path.exists() {
+        return Ok(());
+    }
+    let settings: Settings = ron::from_str(&fs::read_to_string(&config.settings_path)?)?;
+    volumes.master = settings.master_volume;
+    speech.rate = settings.speech_rate;
+    Ok(())
+}
+
+fn save_settings(
+    config: Res<SaveConfig>,
+    volumes: Res<Volumes>,
+    speech: Res<SpeechConfig>,
+    mut last: Local<Option<Settings>>,
+) -> Result<(), Box<dyn Error>> {
+    let settings = Settings {
+        master_volume: volumes.master,
+        speech_rate: speech.rate,
+    };
+    // The first run only records what was loaded.
+    if last.is_none() {
+        *last = Some(settings);
+        return Ok(());
+    }
+    if *last != Some(settings) {
+        *last = Some(settings);
+        let serialized = ron::ser::to_string_pretty(&settings, Default::default())?;
+        fs::write(&config.settings_path, serialized)?;
+    }
+    Ok(())
+}
+
+fn load_game(
+    input: Res<InputMap<String>>,
+    config: Res<SaveConfig>,
+    mut pending: ResMut<PendingLoad>,
+    mut state: ResMut<State<AppState>>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !input.just_active(LOAD) {
+        return Ok(());
+    }
+    if !config.path.exists() {
+        tts.speak(localization.get("save.none"), true)?;
+        return Ok(());
+    }
+    let saved: SavedGame = ron::from_str(&fs::read_to_string(&config.path)?)?;
+    // Leave `InGame` so that re-entering it runs `setup_level` for the saved map.
+    state.overwrite_replace(AppState::LoadingSave)?;
+    **pending = Some(saved);
+    tts.speak(localization.get("save.loaded"), true)?;
+    Ok(())
+}
+
+fn apply_load(
+    mut commands: Commands,
+    pending: Res<PendingLoad>,
+    mut player: Query<(&Player, &mut Lives, &mut Score, &mut Level)>,
+    map: Query<Entity, With<Map>>,
+    mut seed_override: ResMut<LevelSeedOverride>,
+    mut events: EventWriter<Reset>,
+    mut state: ResMut<State<AppState>>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(saved) = **pending {
+        if let Ok((_, mut lives, mut score, mut level)) = player.single_mut() {
+            *lives = saved.lives;
+            *score = saved.score;
+            // `setup_level` advances the level before generating its map.
+            **level = saved.map.level.saturating_sub(1);
+            for entity in map.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            **seed_override = Some(saved.map.seed);
+        }
+    }
+    events.send(Reset::NewLevel);
+    state.overwrite_replace(AppState::InGame)?;
+    Ok(())
+}
+
+fn restore_position(
+    mut pending: ResMut<PendingLoad>,
+    mut player: Query<(&Player, &mut Coordinates, &mut Transform, &mut Checkpoint)>,
+    map: Query<&Areas, Added<Areas>>,
+) {
+    if map.single().is_err() {
+        return;
+    }
+    if let Some(saved) = pending.take() {
+        if let Ok((_, mut coordinates, mut transform, mut checkpoint)) = player.single_mut() {
+            *coordinates = saved.coordinates.into();
+            transform.rotation = Quat::from_rotation_z(saved.rotation);
+            let (checkpoint_coordinates, checkpoint_rotation) = saved.checkpoint;
+            *checkpoint = Checkpoint(
+                checkpoint_coordinates.into(),
+                Quat::from_rotation_z(checkpoint_rotation),
+            );
+        }
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<SaveConfig>()
+            .init_resource::<PendingLoad>()
+            .init_resource::<PendingAutoSave>()
+            .add_startup_system(load_settings.system().chain(error_handler.system()))
+            .add_system(save_settings.system().chain(error_handler.system()))
+            .add_system_set(
+                SystemSet::on_enter(AppState::LevelUp).with_system(queue_auto_save.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::LoadingSave)
+                    .with_system(apply_load.system().chain(error_handler.system())),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(save_game.system().chain(error_handler.system()))
+                    .with_system(load_game.system().chain(error_handler.system())),
+            )
+            .add_system(cycle_auto_save.system())
+            .add_system(
+                restore_position
+                    .system()
+                    .after(POSITION_PLAYER_AT_START_LABEL),
+            )
+            .add_system(
+                auto_save
+                    .system()
+                    .chain(error_handler.system())
+                    .after(POSITION_PLAYER_AT_START_LABEL),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+
+    #[test]
+    fn cycles_auto_save_through_off_every_level_and_interval() {
+        let mut config = SaveConfig::default();
+        config.auto_save_every = config.next_auto_save();
+        assert_eq!(config.auto_save_every, Some(1));
+        config.auto_save_every = config.next_auto_save();
+        assert_eq!(config.auto_save_every, Some(5));
+        config.auto_save_every = config.next_auto_save();
+        assert_eq!(config.auto_save_every, None);
+    }
+
+    #[test]
+    fn master_volume_persists_across_sessions() {
+        let path =
+            std::env::temp_dir().join(format!("rampage-settings-{}.ron", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let config = SaveConfig {
+            settings_path: path.clone(),
+            ..Default::default()
+        };
+        let mut world = World::default();
+        world.insert_resource(config.clone());
+        world.insert_resource(Volumes::default());
+        world.insert_resource(SpeechConfig::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(save_settings.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        world.get_resource_mut::<Volumes>().unwrap().master = 0.4;
+        stage.run(&mut world);
+        let mut world = World::default();
+        world.insert_resource(config);
+        world.insert_resource(Volumes::default());
+        world.insert_resource(SpeechConfig::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(load_settings.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        let _ = fs::remove_file(&path);
+        assert!((world.get_resource::<Volumes>().unwrap().master - 0.4).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn auto_save_records_the_new_level() {
+        let path =
+            std::env::temp_dir().join(format!("rampage-auto-save-{}.ron", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut world = World::default();
+        world.insert_resource(SaveConfig {
+            path: path.clone(),
+            auto_save_every: Some(1),
+            ..Default::default()
+        });
+        world.insert_resource(PendingAutoSave(true));
+        world.insert_resource(LevelSeed(42));
+        world.insert_resource(IoTaskPool(TaskPool::new()));
+        let mut level = Level::default();
+        *level = 3;
+        world
+            .spawn()
+            .insert(Player)
+            .insert(Lives(2))
+            .insert(Score(100))
+            .insert(level)
+            .insert(Checkpoint::default())
+            .insert(Coordinates::default())
+            .insert(Transform::default());
+        world.spawn().insert(Areas::default());
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(auto_save.system().chain(error_handler.system()));
+        stage.run(&mut world);
+        let mut contents = None;
+        for _ in 0..100 {
+            if let Ok(v) = fs::read_to_string(&path) {
+                if let Ok(saved) = ron::from_str::<SavedGame>(&v) {
+                    contents = Some(saved);
+                    break;
+                }
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let _ = fs::remove_file(&path);
+        let saved = contents.expect("auto-save wrote no file");
+        assert_eq!(*saved.map.level, 3);
+        assert_eq!(saved.map.seed, 42);
+    }
+}
@@ -0,0 +1,208 @@
+use std::error::Error;
+
+use bevy::prelude::*;
+use blackout::{
+    bevy_input_actionmap::InputMap,
+    bevy_openal::Buffer,
+    bevy_tts::Tts,
+    core::{Area, Coordinates, Player, PointLike},
+    error::error_handler,
+    localization::Localization,
+    map::{Areas, Map},
+    navigation::MotionBlocked,
+    sound::{spawn_sound_at, SoundParams},
+};
+
+use crate::game::{AppState, Sfx, SCAN_AREA};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScanConfig {
+    pub enabled: bool,
+    pub staggered: bool,
+    pub interval: f32,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            staggered: true,
+            interval: 0.05,
+        }
+    }
+}
+
+const WALL_PITCH: f32 = 1.;
+const OPENING_PITCH: f32 = 1.5;
+
+pub fn perimeter(area: &Area) -> Vec<(i32, i32)> {
+    let x1 = area.rect.x1 as i32 - 1;
+    let y1 = area.rect.y1 as i32 - 1;
+    let x2 = area.rect.x2 as i32 + 1;
+    let y2 = area.rect.y2 as i32 + 1;
+    let mut tiles = vec![];
+    for x in x1..x2 {
+        tiles.push((x, y2));
+    }
+    for y in (y1 + 1..=y2).rev() {
+        tiles.push((x2, y));
+    }
+    for x in (x1 + 1..=x2).rev() {
+        tiles.push((x, y1));
+    }
+    for y in y1..y2 {
+        tiles.push((x1, y));
+    }
+    tiles
+}
+
+#[derive(Clone, Debug, Default)]
+struct Sweep {
+    tiles: Vec<((i32, i32), bool)>,
+    timer: Timer,
+}
+
+fn start_scan(
+    input: Res<InputMap<String>>,
+    config: Res<ScanConfig>,
+    player: Query<(&Player, &Coordinates)>,
+    map: Query<(&Map, &MotionBlocked, &Areas)>,
+    mut sweep: ResMut<Sweep>,
+    mut tts: ResMut<Tts>,
+    localization: Res<Localization>,
+) -> Result<(), Box<dyn Error>> {
+    if !config.enabled || !input.just_active(SCAN_AREA) {
+        return Ok(());
+    }
+    if !sweep.tiles.is_empty() {
+        *sweep = Default::default();
+        return Ok(());
+    }
+    if let (Ok((_, coordinates)), Ok((map, motion_blocked, areas))) =
+        (player.single(), map.single())
+    {
+        if let Some(area) = areas.iter().find(|a| a.contains(coordinates)) {
+            let tiles = perimeter(area)
+                .into_iter()
+                .filter(|(x, y)| {
+                    *x >= 0
+                        && *y >= 0
+                        && (*x as usize) < map.width()
+                        && (*y as usize) < map.height()
+                })
+                .map(|point| (point, motion_blocked[point.to_index(map.width())]))
+                .collect();
+            *sweep = Sweep {
+                tiles,
+                timer: Timer::from_seconds(config.interval, true),
+            };
+        } else {
+            tts.speak(localization.get("scan.no_area"), true)?;
+        }
+    }
+    Ok(())
+}
+
+fn play_sweep(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<ScanConfig>,
+    buffers: Res<Assets<Buffer>>,
+    sfx: Res<Sfx>,
+    map: Query<Entity, With<Map>>,
+    mut sweep: ResMut<Sweep>,
+) {
+    if !sweep.tiles.is_empty() {
+        let count = if config.staggered {
+            sweep.timer.tick(time.delta());
+            sweep.timer.times_finished() as usize
+        } else {
+            sweep.tiles.len()
+        };
+        if let Ok(map_entity) = map.single() {
+            for _ in 0..count.min(sweep.tiles.len()) {
+                let ((x, y), blocked) = sweep.tiles.remove(0);
+                spawn_sound_at(
+                    &mut commands,
+                    map_entity,
+                    buffers.get_handle(sfx.scan_tone),
+                    &(x, y),
+                    SoundParams {
+                        gain: 0.5,
+                        pitch: if blocked { WALL_PITCH } else { OPENING_PITCH },
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub struct ScanPlugin;
+
+impl Plugin for ScanPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ScanConfig>()
+            .init_resource::<Sweep>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(start_scan.system().chain(error_handler.system()))
+                    .with_system(play_sweep.system()),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blackout::{bevy_openal::Sound, mapgen};
+
+    use super::*;
+    use crate::testing::test_world;
+
+    #[test]
+    fn sweep_plays_perimeter_tiles_in_order() {
+        let area = Area {
+            rect: mapgen::geometry::Rect::new(2, 2, 2, 2),
+            description: None,
+            light: 1.,
+        };
+        let tiles = perimeter(&area);
+        assert_eq!(tiles.len(), 16);
+        assert_eq!(tiles[0], (1, 5));
+        assert_eq!(tiles[4], (5, 5));
+        assert_eq!(tiles[8], (5, 1));
+        assert_eq!(tiles[12], (1, 1));
+        let mut world = test_world();
+        world.insert_resource(ScanConfig {
+            enabled: true,
+            staggered: false,
+            ..Default::default()
+        });
+        world.insert_resource(Sfx::default());
+        world.insert_resource(Sweep {
+            tiles: tiles.iter().map(|t| (*t, *t != (3, 5))).collect(),
+            ..Default::default()
+        });
+        world.spawn().insert(Map::new(mapgen::Map::new(8, 8)));
+        let mut stage = SystemStage::single_threaded();
+        stage.add_system(play_sweep.system());
+        stage.run(&mut world);
+        let mut tones = world
+            .query::<(Entity, &Sound, &Transform)>()
+            .iter(&world)
+            .map(|(entity, sound, transform)| {
+                let tile = (
+                    transform.translation.x as i32,
+                    transform.translation.y as i32,
+                );
+                (entity.id(), tile, sound.pitch)
+            })
+            .collect::<Vec<_>>();
+        tones.sort_by_key(|(id, _, _)| *id);
+        let played: Vec<(i32, i32)> = tones.iter().map(|(_, tile, _)| *tile).collect();
+        assert_eq!(played, tiles);
+        assert_eq!(tones[2].2, OPENING_PITCH);
+        assert_eq!(tones[3].2, WALL_PITCH);
+        assert!(world.get_resource::<Sweep>().unwrap().tiles.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::{app::Events, ecs::component::Component, prelude::*};
+use blackout::{
+    bevy_openal::Buffer,
+    map::Map,
+    mapgen::{self, TileType},
+    navigation::MotionBlocked,
+    visibility::VisibilityBlocked,
+};
+
+// A world with the asset and time resources that most game systems expect.
+pub(crate) fn test_world() -> World {
+    let mut app = App::build();
+    app.add_plugin(bevy::core::CorePlugin)
+        .add_plugin(bevy::asset::AssetPlugin)
+        .add_asset::<Buffer>()
+        .add_asset::<ColorMaterial>();
+    std::mem::take(app.world_mut())
+}
+
+pub(crate) fn open_map(width: usize, height: usize) -> Map {
+    let mut base = mapgen::Map::new(width, height);
+    for tile in base.tiles.iter_mut() {
+        *tile = TileType::Floor;
+    }
+    Map::new(base)
+}
+
+// Spawns `map` with blocking that matches its walls.
+pub(crate) fn spawn_level(world: &mut World, map: Map) -> Entity {
+    let blocked = map
+        .base
+        .tiles
+        .iter()
+        .map(|tile| *tile == TileType::Wall)
+        .collect::<Vec<bool>>();
+    world
+        .spawn()
+        .insert(map)
+        .insert(MotionBlocked(blocked.clone()))
+        .insert(VisibilityBlocked(blocked))
+        .id()
+}
+
+pub(crate) fn sent<T: Component + Clone>(world: &World) -> Vec<T> {
+    let events = world.get_resource::<Events<T>>().unwrap();
+    events.get_reader().iter(events).cloned().collect()
+}